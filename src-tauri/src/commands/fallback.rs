@@ -0,0 +1,16 @@
+use crate::services::fallback;
+
+#[tauri::command]
+pub fn set_fallback_config(config: fallback::FallbackConfig) {
+    fallback::get_fallback().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_fallback_config() -> fallback::FallbackConfig {
+    fallback::get_fallback().get_config()
+}
+
+#[tauri::command]
+pub fn get_provider_health() -> Vec<fallback::ProviderHealth> {
+    fallback::get_fallback().all()
+}
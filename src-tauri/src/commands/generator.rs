@@ -0,0 +1,11 @@
+use crate::sources::generated;
+
+#[tauri::command]
+pub fn set_generator_config(config: generated::GeneratorConfig) {
+    generated::get_generator().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_generator_config() -> generated::GeneratorConfig {
+    generated::get_generator().get_config()
+}
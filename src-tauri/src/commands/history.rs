@@ -0,0 +1,45 @@
+use crate::services::apply;
+use crate::services::history::{self, ApplyTrigger, HistoryEntry};
+use crate::types::PaginatedResponse;
+
+use tauri::AppHandle;
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+#[tauri::command]
+pub fn get_wallpaper_history(page: u32, per_page: Option<u32>) -> PaginatedResponse<HistoryEntry> {
+    history::get_history().page(page, per_page.unwrap_or(DEFAULT_PER_PAGE))
+}
+
+#[tauri::command]
+pub fn clear_wallpaper_history() {
+    history::get_history().clear();
+}
+
+/**
+ * 重新应用历史中的上一张壁纸（撤销最近一次切换），没有更早的记录时返回 None
+ */
+#[tauri::command]
+pub async fn previous_wallpaper(app: AppHandle) -> Result<Option<HistoryEntry>, String> {
+    step(&app, -1).await
+}
+
+#[tauri::command]
+pub async fn next_wallpaper(app: AppHandle) -> Result<Option<HistoryEntry>, String> {
+    step(&app, 1).await
+}
+
+async fn step(app: &AppHandle, delta: isize) -> Result<Option<HistoryEntry>, String> {
+    let history = history::get_history();
+    let Some((index, entry)) = history.neighbor(delta) else {
+        return Ok(None);
+    };
+
+    apply::apply_wallpaper(app, entry.wallpaper.clone(), ApplyTrigger::History)
+        .await
+        .map_err(|e| format!("History error: {}", e))?;
+
+    // 设置成功后才移动位置，失败时保持不变
+    history.set_cursor(index);
+    Ok(Some(entry))
+}
@@ -0,0 +1,11 @@
+use crate::services::hooks;
+
+#[tauri::command]
+pub fn set_hooks_config(config: hooks::HooksConfig) {
+    hooks::get_hooks().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_hooks_config() -> hooks::HooksConfig {
+    hooks::get_hooks().get_config()
+}
@@ -0,0 +1,13 @@
+use crate::services::http;
+
+#[tauri::command]
+pub fn set_http_config(config: http::HttpConfig) -> Result<(), String> {
+    http::get_http()
+        .set_config(config)
+        .map_err(|e| format!("Network config error: {}", e))
+}
+
+#[tauri::command]
+pub fn get_http_config() -> http::HttpConfig {
+    http::get_http().get_config()
+}
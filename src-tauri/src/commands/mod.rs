@@ -1 +1,21 @@
 pub mod wallpaper;
+pub mod overlay;
+pub mod palette;
+pub mod hooks;
+pub mod history;
+pub mod watcher;
+pub mod original;
+pub mod generator;
+pub mod slideshow;
+pub mod rotation;
+pub mod playlist;
+pub mod prefetch;
+pub mod triggers;
+pub mod power;
+pub mod provider;
+pub mod http;
+pub mod fallback;
+pub mod offline;
+pub mod secrets;
+pub mod wallhaven;
+pub mod unsplash;
@@ -0,0 +1,19 @@
+use crate::services::offline;
+
+#[tauri::command]
+pub fn set_offline_config(config: offline::OfflineConfig) {
+    offline::get_offline().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_offline_config() -> offline::OfflineConfig {
+    offline::get_offline().get_config()
+}
+
+/**
+ * 当前是否离线，以及自动检测到断网的时间和原因
+ */
+#[tauri::command]
+pub fn get_offline_state() -> offline::OfflineState {
+    offline::get_offline().state()
+}
@@ -0,0 +1,27 @@
+use crate::services::original::{self, OriginalWallpaper};
+
+#[tauri::command]
+pub fn set_original_config(config: original::OriginalConfig) {
+    original::get_original().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_original_config() -> original::OriginalConfig {
+    original::get_original().get_config()
+}
+
+#[tauri::command]
+pub fn get_original_wallpaper() -> Option<OriginalWallpaper> {
+    original::get_original().get()
+}
+
+/**
+ * 恢复应用第一次修改前的壁纸，没有保存过时返回 None
+ */
+#[tauri::command]
+pub async fn restore_original_wallpaper() -> Result<Option<OriginalWallpaper>, String> {
+    tauri::async_runtime::spawn_blocking(|| original::get_original().restore())
+        .await
+        .map_err(|e| format!("Restore error: {}", e))?
+        .map_err(|e| format!("Restore error: {}", e))
+}
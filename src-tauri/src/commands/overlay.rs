@@ -0,0 +1,49 @@
+use crate::services::{agenda, apply, overlay};
+
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn set_overlay_config(
+    app: AppHandle,
+    config: overlay::OverlayConfig,
+) -> Result<(), String> {
+    config.validate().map_err(|e| e.to_string())?;
+    overlay::get_overlay().set_config(config);
+
+    apply::reapply_current(&app)
+        .await
+        .map_err(|e| format!("Overlay error: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_overlay_config() -> overlay::OverlayConfig {
+    overlay::get_overlay().get_config()
+}
+
+#[tauri::command]
+pub async fn set_agenda_config(
+    app: AppHandle,
+    config: agenda::AgendaConfig,
+) -> Result<(), String> {
+    agenda::get_agenda().set_config(config);
+
+    apply::reapply_current(&app)
+        .await
+        .map_err(|e| format!("Agenda error: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_agenda_config() -> agenda::AgendaConfig {
+    agenda::get_agenda().get_config()
+}
+
+#[tauri::command]
+pub fn get_agenda_events() -> Result<Vec<agenda::AgendaEvent>, String> {
+    agenda::get_agenda()
+        .events(&chrono::Local::now())
+        .map_err(|e| format!("Agenda error: {}", e))
+}
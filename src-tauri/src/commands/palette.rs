@@ -0,0 +1,22 @@
+use crate::services::{apply, palette};
+
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn set_palette_config(app: AppHandle, config: palette::PaletteConfig) {
+    palette::get_palette().set_config(config);
+
+    if let Some(path) = apply::current_wallpaper().and_then(|info| info.local_path) {
+        palette::spawn_update(app, path);
+    }
+}
+
+#[tauri::command]
+pub fn get_palette_config() -> palette::PaletteConfig {
+    palette::get_palette().get_config()
+}
+
+#[tauri::command]
+pub fn get_current_palette() -> Option<palette::Palette> {
+    palette::get_palette().current()
+}
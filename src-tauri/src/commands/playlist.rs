@@ -0,0 +1,15 @@
+use crate::services::playlist;
+
+#[tauri::command]
+pub fn set_playlist_config(config: playlist::PlaylistConfig) -> Result<(), String> {
+    config
+        .validate()
+        .map_err(|e| format!("Playlist error: {}", e))?;
+    playlist::get_playlist().set_config(config);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_playlist_config() -> playlist::PlaylistConfig {
+    playlist::get_playlist().get_config()
+}
@@ -0,0 +1,21 @@
+use crate::services::power;
+
+#[tauri::command]
+pub fn set_power_config(config: power::PowerConfig) {
+    power::get_power().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_power_config() -> power::PowerConfig {
+    power::get_power().get_config()
+}
+
+/**
+ * 读取状态可能需要调用 D-Bus，放到阻塞线程中执行
+ */
+#[tauri::command]
+pub async fn get_power_state() -> Result<power::PowerState, String> {
+    tauri::async_runtime::spawn_blocking(|| power::get_power().state())
+        .await
+        .map_err(|e| format!("Power state error: {}", e))
+}
@@ -0,0 +1,17 @@
+use crate::services::prefetch;
+use crate::types::WallpaperInfo;
+
+#[tauri::command]
+pub fn set_prefetch_config(config: prefetch::PrefetchConfig) {
+    prefetch::get_prefetcher().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_prefetch_config() -> prefetch::PrefetchConfig {
+    prefetch::get_prefetcher().get_config()
+}
+
+#[tauri::command]
+pub fn get_prefetch_queue() -> Vec<WallpaperInfo> {
+    prefetch::get_prefetcher().queued()
+}
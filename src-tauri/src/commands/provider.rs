@@ -0,0 +1,16 @@
+use crate::sources::list_sources;
+use crate::sources::ratelimit::{get_rate_limiter, ProviderStatus};
+use crate::types::WallpaperSource;
+
+/**
+ * 各在线来源的剩余配额和冷却时间
+ */
+#[tauri::command]
+pub fn provider_status() -> Vec<ProviderStatus> {
+    list_sources()
+        .iter()
+        .filter_map(|source| WallpaperSource::from_str(source))
+        .filter(|source| source.is_remote())
+        .map(|source| get_rate_limiter().status(&source))
+        .collect()
+}
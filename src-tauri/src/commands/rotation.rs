@@ -0,0 +1,16 @@
+use crate::services::rotation;
+
+#[tauri::command]
+pub fn set_rotation_config(config: rotation::RotationConfig) {
+    rotation::get_rotation().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_rotation_config() -> rotation::RotationConfig {
+    rotation::get_rotation().get_config()
+}
+
+#[tauri::command]
+pub fn reset_rotation() {
+    rotation::get_rotation().reset();
+}
@@ -0,0 +1,71 @@
+use crate::services::secrets::{self, KeyStatus, SecretBackend};
+use crate::sources::list_sources;
+use crate::sources::validation::{self, KeyCheck};
+use crate::types::WallpaperSource;
+
+fn remote_source(source: &str) -> Result<WallpaperSource, String> {
+    WallpaperSource::from_str(source)
+        .filter(|source| source.is_remote())
+        .ok_or_else(|| format!("Invalid source: {}", source))
+}
+
+/**
+ * 保存 API Key，返回实际的存放位置。访问钥匙串会启动子进程，放到阻塞线程中执行
+ */
+#[tauri::command]
+pub async fn set_api_key(source: String, key: String) -> Result<SecretBackend, String> {
+    let source = remote_source(&source)?;
+    tauri::async_runtime::spawn_blocking(move || secrets::get_secrets().set(&source, &key))
+        .await
+        .map_err(|e| format!("Secret store error: {}", e))?
+        .map_err(|e| format!("Secret store error: {}", e))
+}
+
+#[tauri::command]
+pub async fn clear_api_key(source: String) -> Result<(), String> {
+    let source = remote_source(&source)?;
+    tauri::async_runtime::spawn_blocking(move || secrets::get_secrets().clear(&source))
+        .await
+        .map_err(|e| format!("Secret store error: {}", e))?
+        .map_err(|e| format!("Secret store error: {}", e))
+}
+
+/**
+ * 各在线来源是否已保存 API Key，只返回最后几位
+ */
+#[tauri::command]
+pub async fn get_api_key_status() -> Result<Vec<KeyStatus>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        list_sources()
+            .iter()
+            .filter_map(|source| WallpaperSource::from_str(source))
+            .filter(|source| source.is_remote())
+            .map(|source| secrets::get_secrets().status(&source))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Secret store error: {}", e))
+}
+
+/**
+ * 验证 API Key，不会保存
+ */
+#[tauri::command]
+pub async fn validate_api_key(source: String, key: String) -> Result<KeyCheck, String> {
+    let source = remote_source(&source)?;
+    validation::validate_api_key(source, &key).await
+}
+
+/**
+ * 验证已保存的 API Key
+ */
+#[tauri::command]
+pub async fn test_api_key(source: String) -> Result<KeyCheck, String> {
+    let source = remote_source(&source)?;
+    let lookup = source.clone();
+    let key = tauri::async_runtime::spawn_blocking(move || secrets::get_secrets().get(&lookup))
+        .await
+        .map_err(|e| format!("Secret store error: {}", e))?
+        .ok_or_else(|| format!("No API key saved for {}", source.as_str()))?;
+    validation::validate_api_key(source, &key).await
+}
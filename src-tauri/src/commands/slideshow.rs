@@ -0,0 +1,40 @@
+use crate::services::{slideshow, store};
+use crate::sources::local::{self, LocalCollection};
+
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/**
+ * 把选中的缓存壁纸导出为 GNOME/KDE 原生幻灯片，可选直接设置为桌面壁纸
+ */
+#[tauri::command]
+pub async fn export_slideshow(
+    app: AppHandle,
+    paths: Vec<String>,
+    options: slideshow::SlideshowOptions,
+) -> Result<slideshow::SlideshowExport, String> {
+    let output_dir = store::data_file(&app, "slideshows")
+        .map_err(|e| format!("Slideshow error: {}", e))?;
+    let images: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+
+    tauri::async_runtime::spawn_blocking(move || slideshow::export(&output_dir, &images, &options))
+        .await
+        .map_err(|e| format!("Slideshow error: {}", e))?
+        .map_err(|e| format!("Slideshow error: {}", e))
+}
+
+#[tauri::command]
+pub fn import_gnome_slideshow(path: String, name: Option<String>) -> Result<LocalCollection, String> {
+    slideshow::import_gnome(&PathBuf::from(path), name)
+        .map_err(|e| format!("Import error: {}", e))
+}
+
+#[tauri::command]
+pub fn list_local_collections() -> Vec<LocalCollection> {
+    local::get_local_library().list()
+}
+
+#[tauri::command]
+pub fn remove_local_collection(name: String) -> bool {
+    local::get_local_library().remove(&name)
+}
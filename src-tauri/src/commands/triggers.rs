@@ -0,0 +1,11 @@
+use crate::services::triggers;
+
+#[tauri::command]
+pub fn set_trigger_config(config: triggers::TriggerConfig) {
+    triggers::get_triggers().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_trigger_config() -> triggers::TriggerConfig {
+    triggers::get_triggers().get_config()
+}
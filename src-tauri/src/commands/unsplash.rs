@@ -0,0 +1,14 @@
+use crate::sources::unsplash;
+
+/**
+ * 设置默认的搜索关键词、方向、话题、合集和内容过滤
+ */
+#[tauri::command]
+pub fn set_unsplash_config(config: unsplash::UnsplashConfig) -> Result<(), String> {
+    unsplash::get_unsplash().set_config(config)
+}
+
+#[tauri::command]
+pub fn get_unsplash_config() -> unsplash::UnsplashConfig {
+    unsplash::get_unsplash().get_config()
+}
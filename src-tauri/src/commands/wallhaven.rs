@@ -0,0 +1,29 @@
+use crate::services::secrets;
+use crate::sources::wallhaven::{self, AccountSettings, WallhavenCollection};
+use crate::types::WallpaperSource;
+
+/**
+ * API Key 对应账号的默认搜索条件
+ */
+#[tauri::command]
+pub async fn get_wallhaven_account_settings() -> Result<AccountSettings, String> {
+    let key = secrets::api_key(&WallpaperSource::Wallhaven)
+        .ok_or("Wallhaven API key is not configured".to_string())?;
+    wallhaven::fetch_account_settings(&key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/**
+ * 列出自己的收藏夹，指定 username 时列出该用户公开的收藏夹
+ *
+ * 浏览收藏夹时在 fetch_wallpapers_list 的 filters 中传 collection: "用户名/ID"
+ */
+#[tauri::command]
+pub async fn list_wallhaven_collections(
+    username: Option<String>,
+) -> Result<Vec<WallhavenCollection>, String> {
+    wallhaven::list_collections(username.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,9 +1,9 @@
-use crate::services::{cache, wallpaper, scheduler};
+use crate::services::{apply, cache, fetcher, offline, wallpaper, scheduler, watcher};
+use crate::services::history::ApplyTrigger;
 use crate::types::{PaginatedResponse, WallpaperInfo, WallpaperListItem, WallpaperSource};
-use crate::sources::provider::{fetch_paginated, fetch_random, ProviderError};
+use crate::sources::provider::{fetch_paginated, search_paginated, ProviderError, SourceQuery};
 
-use tauri::{AppHandle, Emitter};
-use rand::prelude::IndexedRandom;
+use tauri::AppHandle;
 
 impl WallpaperSource {
     pub fn from_str(s: &str) -> Option<Self> {
@@ -14,9 +14,31 @@ impl WallpaperSource {
             "pixabay" => Some(WallpaperSource::Pixabay),
             "pexels" => Some(WallpaperSource::Pexels),
             "reddit" => Some(WallpaperSource::Reddit),
+            "generated" => Some(WallpaperSource::Generated),
+            "local" => Some(WallpaperSource::Local),
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WallpaperSource::Bing => "bing",
+            WallpaperSource::Wallhaven => "wallhaven",
+            WallpaperSource::Unsplash => "unsplash",
+            WallpaperSource::Pixabay => "pixabay",
+            WallpaperSource::Pexels => "pexels",
+            WallpaperSource::Reddit => "reddit",
+            WallpaperSource::Generated => "generated",
+            WallpaperSource::Local => "local",
+        }
+    }
+
+    /**
+     * 是否需要联网，生成的壁纸和本地图片离线时也可用
+     */
+    pub fn is_remote(&self) -> bool {
+        !matches!(self, WallpaperSource::Generated | WallpaperSource::Local)
+    }
 }
 
 impl From<ProviderError> for String {
@@ -26,79 +48,32 @@ impl From<ProviderError> for String {
 }
 
 #[tauri::command]
-pub async fn fetch_next_wallpaper(
-    app: AppHandle,
-    source: String,
-    api_key: Option<String>,
-) -> Result<WallpaperInfo, String> {
+pub async fn fetch_next_wallpaper(app: AppHandle, source: String) -> Result<WallpaperInfo, String> {
     let wallpaper_source = WallpaperSource::from_str(&source)
         .ok_or("Invalid source".to_string())?;
 
-    let wallpapers = fetch_random(wallpaper_source.clone(), api_key.clone()).await
-        .map_err(|e| e.to_string())?;
-
-    if wallpapers.is_empty() {
-        return Err("No wallpapers found".to_string());
-    }
-
-    let mut selected = wallpapers.choose(&mut rand::rng())
-        .ok_or("Failed to select wallpaper".to_string())?
-        .clone();
-
-    if let WallpaperSource::Wallhaven = wallpaper_source {
-        if let Ok(tags) = crate::sources::wallhaven::get_wallpaper_details(&selected.id, api_key).await {
-            if !tags.is_empty() {
-                let title_tags = tags.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
-                let resolution = selected.title.split('(').nth(1)
-                    .map(|s| format!("({})", s.trim_end_matches(')')))
-                    .unwrap_or_default();
-                selected.title = format!("{} {}", title_tags, resolution);
-
-                if let Some(c) = selected.title.get_mut(0..1) {
-                    c.make_ascii_uppercase();
-                }
-            }
-        }
-    }
-
-    let cached_path = cache::download_and_cache(&app, &selected)
+    fetcher::fetch_next(&app, wallpaper_source)
         .await
-        .map_err(|e| format!("Cache error: {}", e))?;
-
-    let result_info = WallpaperInfo {
-        id: selected.id.clone(),
-        title: selected.title.clone(),
-        url: selected.url.clone(),
-        source: selected.source.clone(),
-        local_path: Some(cached_path),
-        cached: true,
-    };
-
-    app.emit("wallpaper-fetched", &result_info)
-        .map_err(|e| format!("Emit error: {}", e))?;
-
-    Ok(result_info)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn set_wallpaper_from_info(
     app: AppHandle,
     wallpaper: WallpaperInfo,
+    trigger: Option<ApplyTrigger>,
 ) -> Result<(), String> {
-    let path = if let Some(cached_path) = wallpaper.local_path {
-        cached_path
-    } else {
-        cache::download_and_cache(&app, &wallpaper)
-            .await
-            .map_err(|e| format!("Cache error: {}", e))?
+    // 其余触发方式由后端流程产生，前端传入会跳过或伪造历史记录
+    let trigger = match trigger {
+        None | Some(ApplyTrigger::Manual) => ApplyTrigger::Manual,
+        Some(ApplyTrigger::Tray) => ApplyTrigger::Tray,
+        Some(other) => return Err(format!("Unsupported trigger: {:?}", other)),
     };
 
-    wallpaper::set_wallpaper(&path)
+    apply::apply_wallpaper(&app, wallpaper, trigger)
+        .await
         .map_err(|e| format!("Set wallpaper error: {}", e))?;
 
-    app.emit("wallpaper-set", &path)
-        .map_err(|e| format!("Emit error: {}", e))?;
-
     Ok(())
 }
 
@@ -110,17 +85,43 @@ pub async fn get_current_wallpaper_path() -> Result<String, String> {
     Ok(path)
 }
 
+/**
+ * 当前系统壁纸对应的壁纸信息，不是由应用设置时返回 None
+ */
+#[tauri::command]
+pub async fn get_current_wallpaper_info(app: AppHandle) -> Result<Option<WallpaperInfo>, String> {
+    let path = wallpaper::get_current_wallpaper()
+        .map_err(|e| format!("Get wallpaper error: {}", e))?;
+
+    Ok(watcher::resolve(&app, &path))
+}
+
+/**
+ * 分页列出壁纸，query 为本次请求的搜索关键词和筛选条件
+ */
 #[tauri::command]
 pub async fn fetch_wallpapers_list(
+    app: AppHandle,
     source: String,
     page: u32,
-    api_key: Option<String>,
+    query: Option<SourceQuery>,
 ) -> Result<PaginatedResponse<WallpaperListItem>, String> {
     let wallpaper_source = WallpaperSource::from_str(&source)
         .ok_or("Invalid source".to_string())?;
 
-    fetch_paginated(wallpaper_source, api_key, page).await
-        .map_err(|e| e.to_string())
+    // 离线时列出已缓存的壁纸
+    let network = offline::get_offline();
+    if network.is_offline() && wallpaper_source.is_remote() {
+        let filter = network.get_config().filter_by_source.then_some(&wallpaper_source);
+        return cache::cached_page(&app, filter, page)
+            .map_err(|e| format!("Cache error: {}", e));
+    }
+
+    match query {
+        Some(query) => search_paginated(wallpaper_source, &query, page).await,
+        None => fetch_paginated(wallpaper_source, page).await,
+    }
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -135,15 +136,25 @@ pub async fn download_wallpaper(
     Ok(cached_path.to_string_lossy().to_string())
 }
 
+/**
+ * 设置来源的定时切换，query 为切换时使用的关键词和筛选条件
+ */
 #[tauri::command]
 pub fn set_auto_switch_config(
     source: String,
     enabled: bool,
     interval_seconds: u64,
+    query: Option<SourceQuery>,
 ) -> Result<(), String> {
+    let wallpaper_source = WallpaperSource::from_str(&source)
+        .ok_or("Invalid source".to_string())?;
+    let query = query.unwrap_or_default();
+    query.validate(&wallpaper_source)?;
+
     let config = scheduler::AutoSwitchConfig {
         enabled,
         interval_seconds,
+        query,
     };
     scheduler::get_scheduler().set_config(&source, config);
     Ok(())
@@ -154,6 +165,16 @@ pub fn get_auto_switch_config(source: String) -> Option<scheduler::AutoSwitchCon
     scheduler::get_scheduler().get_config(&source)
 }
 
+#[tauri::command]
+pub fn set_auto_switch_paused(paused: bool) {
+    scheduler::get_scheduler().set_paused(paused);
+}
+
+#[tauri::command]
+pub fn is_auto_switch_paused() -> bool {
+    scheduler::get_scheduler().is_paused()
+}
+
 #[tauri::command]
 pub fn list_downloads(app: AppHandle) -> Result<Vec<(String, String)>, String> {
     let files = cache::list_cached_files(&app)
@@ -0,0 +1,11 @@
+use crate::services::watcher;
+
+#[tauri::command]
+pub fn set_watcher_config(config: watcher::WatcherConfig) {
+    watcher::get_watcher().set_config(config);
+}
+
+#[tauri::command]
+pub fn get_watcher_config() -> watcher::WatcherConfig {
+    watcher::get_watcher().get_config()
+}
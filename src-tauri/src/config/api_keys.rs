@@ -1,11 +0,0 @@
-/**
- * API Keys 配置
- *
- * 硬编码的 API Keys，仅在开发环境使用
- * 不会发布到生产环境
- */
-
-pub const WALLHAVEN_API_KEY: &str = "";
-pub const UNSPLASH_ACCESS_KEY: &str = "WTTaMbbcc6iOhs82QlFAQPJyyQE9aFr4plfwr7RqksY";
-pub const PIXABAY_API_KEY: &str = "8887457-0f5fdf0071b0426fb4f814a11";
-pub const PEXELS_API_KEY: &str = "";
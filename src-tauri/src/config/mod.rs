@@ -1,3 +0,0 @@
-pub mod api_keys;
-
-pub use api_keys::{PEXELS_API_KEY, PIXABAY_API_KEY, UNSPLASH_ACCESS_KEY, WALLHAVEN_API_KEY};
@@ -1,5 +1,4 @@
 pub mod commands;
-pub mod config;
 pub mod services;
 pub mod sources;
 pub mod types;
@@ -44,33 +43,168 @@ pub fn run() {
               .icon(icon)
               .on_menu_event(|app: &tauri::AppHandle, event: tauri::menu::MenuEvent| {
                   if event.id.as_ref() == "fetch_wallpaper" {
-                      let _ = app.emit("fetch-wallpaper", ());
+                      let app = app.clone();
+                      tauri::async_runtime::spawn(async move {
+                          if let Err(e) = services::fetcher::next_from_tray(&app).await {
+                              log::warn!("Tray switch failed: {}", e);
+                          }
+                      });
                   } else if event.id.as_ref() == "open_settings" {
                       let _ = app.emit("open-settings", ());
                   } else if event.id.as_ref() == "quit" {
-                      std::process::exit(0);
+                      app.exit(0);
                   }
               })
               .build(app)
               .expect("Failed to create tray");
       }
 
+      if let Ok(Some(monitor)) = app.primary_monitor() {
+          let size = monitor.size();
+          sources::generated::get_generator().set_screen_size(size.width, size.height);
+      }
+
+      if let Err(e) = services::history::get_history().init(app.handle()) {
+          log::warn!("Failed to load wallpaper history: {}", e);
+      }
+      if let Err(e) = services::original::get_original().init(app.handle()) {
+          log::warn!("Failed to load original wallpaper: {}", e);
+      }
+      if let Err(e) = sources::local::get_local_library().init(app.handle()) {
+          log::warn!("Failed to load local library: {}", e);
+      }
+      if let Err(e) = services::rotation::get_rotation().init(app.handle()) {
+          log::warn!("Failed to load rotation config: {}", e);
+      }
+      if let Err(e) = services::triggers::get_triggers().init(app.handle()) {
+          log::warn!("Failed to load trigger config: {}", e);
+      }
+      if let Err(e) = services::http::get_http().init(app.handle()) {
+          log::warn!("Failed to load network config: {}", e);
+      }
+      if let Err(e) = services::offline::get_offline().init(app.handle()) {
+          log::warn!("Failed to load offline config: {}", e);
+      }
+      if let Err(e) = services::secrets::get_secrets().init(app.handle()) {
+          log::warn!("Failed to open secret store: {}", e);
+      }
+      if let Err(e) = services::hooks::get_hooks().init(app.handle()) {
+          log::warn!("Failed to load hooks config: {}", e);
+      }
+      if let Err(e) = services::playlist::get_playlist().init(app.handle()) {
+          log::warn!("Failed to load playlist: {}", e);
+      }
+      if let Err(e) = services::fallback::get_fallback().init(app.handle()) {
+          log::warn!("Failed to load fallback config: {}", e);
+      }
+      if let Err(e) = services::overlay::get_overlay().init(app.handle()) {
+          log::warn!("Failed to load overlay config: {}", e);
+      }
+      if let Err(e) = sources::generated::get_generator().init(app.handle()) {
+          log::warn!("Failed to load generator config: {}", e);
+      }
+      if let Err(e) = services::prefetch::get_prefetcher().init(app.handle()) {
+          log::warn!("Failed to load prefetch config: {}", e);
+      }
+      if let Err(e) = services::watcher::get_watcher().init(app.handle()) {
+          log::warn!("Failed to load watcher config: {}", e);
+      }
+      if let Err(e) = services::agenda::get_agenda().init(app.handle()) {
+          log::warn!("Failed to load agenda config: {}", e);
+      }
+      if let Err(e) = services::palette::get_palette().init(app.handle()) {
+          log::warn!("Failed to load palette config: {}", e);
+      }
+      if let Err(e) = services::power::get_power().init(app.handle()) {
+          log::warn!("Failed to load power config: {}", e);
+      }
+
+      services::overlay::spawn_refresh_task(app.handle().clone());
+      services::scheduler::spawn_runner(app.handle().clone());
+      services::watcher::spawn_watcher(app.handle().clone());
+      services::triggers::spawn_triggers(app.handle().clone());
+
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
         commands::wallpaper::fetch_next_wallpaper,
         commands::wallpaper::set_wallpaper_from_info,
         commands::wallpaper::get_current_wallpaper_path,
+        commands::wallpaper::get_current_wallpaper_info,
         commands::wallpaper::fetch_wallpapers_list,
         commands::wallpaper::download_wallpaper,
         commands::wallpaper::set_auto_switch_config,
         commands::wallpaper::get_auto_switch_config,
+        commands::wallpaper::set_auto_switch_paused,
+        commands::wallpaper::is_auto_switch_paused,
         commands::wallpaper::list_downloads,
         commands::wallpaper::delete_download,
         commands::wallpaper::reveal_in_finder,
+        commands::overlay::set_overlay_config,
+        commands::overlay::get_overlay_config,
+        commands::overlay::set_agenda_config,
+        commands::overlay::get_agenda_config,
+        commands::overlay::get_agenda_events,
+        commands::palette::set_palette_config,
+        commands::palette::get_palette_config,
+        commands::palette::get_current_palette,
+        commands::hooks::set_hooks_config,
+        commands::hooks::get_hooks_config,
+        commands::history::get_wallpaper_history,
+        commands::history::clear_wallpaper_history,
+        commands::history::previous_wallpaper,
+        commands::history::next_wallpaper,
+        commands::watcher::set_watcher_config,
+        commands::watcher::get_watcher_config,
+        commands::original::set_original_config,
+        commands::original::get_original_config,
+        commands::original::get_original_wallpaper,
+        commands::original::restore_original_wallpaper,
+        commands::generator::set_generator_config,
+        commands::generator::get_generator_config,
+        commands::slideshow::export_slideshow,
+        commands::slideshow::import_gnome_slideshow,
+        commands::slideshow::list_local_collections,
+        commands::slideshow::remove_local_collection,
+        commands::rotation::set_rotation_config,
+        commands::rotation::get_rotation_config,
+        commands::rotation::reset_rotation,
+        commands::playlist::set_playlist_config,
+        commands::playlist::get_playlist_config,
+        commands::prefetch::set_prefetch_config,
+        commands::prefetch::get_prefetch_config,
+        commands::prefetch::get_prefetch_queue,
+        commands::triggers::set_trigger_config,
+        commands::triggers::get_trigger_config,
+        commands::power::set_power_config,
+        commands::power::get_power_config,
+        commands::power::get_power_state,
+        commands::provider::provider_status,
+        commands::http::set_http_config,
+        commands::http::get_http_config,
+        commands::fallback::set_fallback_config,
+        commands::fallback::get_fallback_config,
+        commands::fallback::get_provider_health,
+        commands::offline::set_offline_config,
+        commands::offline::get_offline_config,
+        commands::offline::get_offline_state,
+        commands::secrets::set_api_key,
+        commands::secrets::clear_api_key,
+        commands::secrets::get_api_key_status,
+        commands::secrets::test_api_key,
+        commands::secrets::validate_api_key,
+        commands::wallhaven::get_wallhaven_account_settings,
+        commands::wallhaven::list_wallhaven_collections,
+        commands::unsplash::set_unsplash_config,
+        commands::unsplash::get_unsplash_config,
     ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|_app, event| {
+      if let tauri::RunEvent::Exit = event {
+        services::original::restore_on_exit();
+      }
+    });
 }
 
 #[cfg(test)]
@@ -0,0 +1,698 @@
+use crate::services::overlay::{OverlayConfig, OverlayLayer, OverlayPosition};
+use crate::services::store;
+use crate::types::WallpaperError;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use rrule::RRuleSet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+// 单个重复事件在窗口内最多展开的次数
+const AGENDA_FILE: &str = "agenda.json";
+const MAX_OCCURRENCES: u16 = 100;
+const WEEKDAYS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgendaConfig {
+    pub enabled: bool,
+    pub ics_path: Option<PathBuf>,
+    pub include_tomorrow: bool,
+    /// 每天最多显示的日程条数
+    pub max_events: usize,
+    pub font_path: Option<PathBuf>,
+    pub font_size: f32,
+    pub position: OverlayPosition,
+    pub margin: f32,
+    pub color: String,
+    pub shadow: bool,
+    pub backdrop: Option<String>,
+}
+
+impl Default for AgendaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ics_path: None,
+            include_tomorrow: true,
+            max_events: 8,
+            font_path: None,
+            font_size: 22.0,
+            position: OverlayPosition::TopRight,
+            margin: 48.0,
+            color: "#FFFFFF".to_string(),
+            shadow: false,
+            backdrop: Some("#00000099".to_string()),
+        }
+    }
+}
+
+impl AgendaConfig {
+    fn style(&self) -> OverlayConfig {
+        OverlayConfig {
+            enabled: true,
+            template: String::new(),
+            font_path: self.font_path.clone(),
+            font_size: self.font_size,
+            position: self.position,
+            margin: self.margin,
+            color: self.color.clone(),
+            shadow: self.shadow,
+            backdrop: self.backdrop.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AgendaEvent {
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub all_day: bool,
+}
+
+/**
+ * 解析后的 VEVENT，重复规则保留原始行交给 rrule 展开
+ */
+#[derive(Debug, Clone)]
+struct CalendarEvent {
+    uid: String,
+    summary: String,
+    location: Option<String>,
+    start: DateTime<Local>,
+    duration: Duration,
+    all_day: bool,
+    recurrence_lines: Vec<String>,
+    recurrence_id: Option<DateTime<Local>>,
+    cancelled: bool,
+}
+
+struct CachedCalendar {
+    path: PathBuf,
+    modified: SystemTime,
+    events: Vec<CalendarEvent>,
+}
+
+pub struct AgendaManager {
+    config: RwLock<AgendaConfig>,
+    calendar: RwLock<Option<CachedCalendar>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl AgendaManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(AgendaConfig::default()),
+            calendar: RwLock::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载日程配置，重启后叠加层继续读取同一个日历文件
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, AGENDA_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: AgendaConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save agenda config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> AgendaConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 今天（以及按配置包含明天）的日程
+     *
+     * 文件修改时间不变时复用上次的解析结果
+     */
+    pub fn events(&self, now: &DateTime<Local>) -> Result<Vec<AgendaEvent>, WallpaperError> {
+        let config = self.get_config();
+        let path = config
+            .ics_path
+            .ok_or_else(|| WallpaperError::ApiError("No calendar file configured".to_string()))?;
+        let modified = fs::metadata(&path)?.modified()?;
+
+        let cached = self
+            .calendar
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|c| c.path == path && c.modified == modified)
+            .map(|c| c.events.clone());
+        let events = match cached {
+            Some(events) => events,
+            None => {
+                let events = parse_calendar(&fs::read_to_string(&path)?);
+                *self.calendar.write().unwrap() = Some(CachedCalendar {
+                    path,
+                    modified,
+                    events: events.clone(),
+                });
+                events
+            }
+        };
+
+        let days = if config.include_tomorrow { 2 } else { 1 };
+        let window_start = start_of_day(now.date_naive());
+        let window_end = start_of_day(now.date_naive() + Duration::days(days));
+        Ok(expand_events(&events, window_start, window_end))
+    }
+
+    /**
+     * 生成日程面板叠加层，未启用或读取失败时返回 None
+     */
+    pub fn layer(&self, now: &DateTime<Local>) -> Option<OverlayLayer> {
+        let config = self.get_config();
+        if !config.enabled {
+            return None;
+        }
+        match self.events(now) {
+            Ok(events) => Some(OverlayLayer {
+                text: format_agenda(&events, now, &config),
+                style: config.style(),
+            }),
+            Err(e) => {
+                log::warn!("Failed to load agenda: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl Default for AgendaManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static AGENDA: OnceLock<AgendaManager> = OnceLock::new();
+
+pub fn get_agenda() -> &'static AgendaManager {
+    AGENDA.get_or_init(AgendaManager::new)
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+/**
+ * 展开续行（以空格或制表符开头的行拼接到上一行）
+ */
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+            }
+        } else if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, String)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    // 参数值可能带引号并包含冒号，只在引号外寻找分隔符
+    let mut in_quotes = false;
+    let split = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ':' && !in_quotes
+    })?;
+    let (head, value) = (&line[..split.0], &line[split.0 + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+
+    Some(Property { name, params, value })
+}
+
+/**
+ * 交给 rrule 的 DTSTART、RDATE、EXDATE 行
+ *
+ * rrule 只认识 IANA 时区，遇到无法识别的 TZID 会拒绝整个规则，
+ * 这里去掉这类 TZID，与 parse_datetime 一样按本地时间处理
+ */
+fn recurrence_line(line: &str) -> String {
+    let Some(prop) = parse_property(line) else {
+        return line.to_string();
+    };
+    match prop.param("TZID") {
+        Some(id) if chrono_tz::Tz::from_str(id).is_err() => {
+            let params: String = prop
+                .params
+                .iter()
+                .filter(|(key, _)| key != "TZID")
+                .map(|(key, value)| {
+                    if value.contains([':', ';', ',']) {
+                        format!(";{}=\"{}\"", key, value)
+                    } else {
+                        format!(";{}={}", key, value)
+                    }
+                })
+                .collect();
+            format!("{}{}:{}", prop.name, params, prop.value)
+        }
+        _ => line.to_string(),
+    }
+}
+
+fn unescape_text(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/**
+ * 解析 DATE 或 DATE-TIME，统一转换为本地时间
+ *
+ * 支持 UTC（Z 结尾）、TZID 指定的 IANA 时区和浮动时间，
+ * 无法识别的 TZID（如 Outlook 的 Windows 时区名）按本地时间处理
+ */
+fn parse_datetime(value: &str, tzid: Option<&str>) -> Option<(DateTime<Local>, bool)> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((start_of_day(date), true));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive).with_timezone(&Local), false));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let local = match tzid.and_then(|id| chrono_tz::Tz::from_str(id).ok()) {
+        Some(tz) => tz.from_local_datetime(&naive).earliest()?.with_timezone(&Local),
+        None => Local.from_local_datetime(&naive).earliest()?,
+    };
+    Some((local, false))
+}
+
+/**
+ * 解析 RFC 5545 DURATION，如 PT1H30M、P1D、P2W
+ */
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+fn parse_calendar(content: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<String>> = None;
+    let mut nested = 0;
+
+    for line in unfold_lines(content) {
+        let upper = line.to_ascii_uppercase();
+        match (upper.as_str(), current.is_some()) {
+            ("BEGIN:VEVENT", false) => current = Some(Vec::new()),
+            ("END:VEVENT", true) => {
+                if let Some(event) = current.take().and_then(|lines| parse_event(&lines)) {
+                    events.push(event);
+                }
+            }
+            // 跳过 VALARM 等嵌套组件
+            (_, true) if upper.starts_with("BEGIN:") => nested += 1,
+            (_, true) if upper.starts_with("END:") => nested -= 1,
+            (_, true) if nested == 0 => current.as_mut().unwrap().push(line),
+            _ => {}
+        }
+    }
+
+    events
+}
+
+fn parse_event(lines: &[String]) -> Option<CalendarEvent> {
+    let mut uid = String::new();
+    let mut summary = String::new();
+    let mut location = None;
+    let mut start = None;
+    let mut end = None;
+    let mut duration = None;
+    let mut recurrence_lines = Vec::new();
+    let mut recurrence_id = None;
+    let mut cancelled = false;
+    let mut has_rule = false;
+
+    for line in lines {
+        let Some(prop) = parse_property(line) else {
+            continue;
+        };
+        match prop.name.as_str() {
+            "UID" => uid = prop.value.to_string(),
+            "SUMMARY" => summary = unescape_text(prop.value),
+            "LOCATION" => location = Some(unescape_text(prop.value)).filter(|l| !l.is_empty()),
+            "STATUS" => cancelled = prop.value.eq_ignore_ascii_case("CANCELLED"),
+            "DTSTART" => {
+                start = parse_datetime(prop.value, prop.param("TZID"));
+                recurrence_lines.insert(0, recurrence_line(line));
+            }
+            "DTEND" => end = parse_datetime(prop.value, prop.param("TZID")).map(|(dt, _)| dt),
+            "DURATION" => duration = parse_duration(prop.value),
+            "RECURRENCE-ID" => {
+                recurrence_id = parse_datetime(prop.value, prop.param("TZID")).map(|(dt, _)| dt)
+            }
+            "RRULE" | "RDATE" => {
+                has_rule = true;
+                recurrence_lines.push(recurrence_line(line));
+            }
+            "EXDATE" => recurrence_lines.push(recurrence_line(line)),
+            _ => {}
+        }
+    }
+
+    let (start, all_day) = start?;
+    let duration = match (end, duration) {
+        (Some(end), _) => end - start,
+        (None, Some(duration)) => duration,
+        (None, None) if all_day => Duration::days(1),
+        (None, None) => Duration::zero(),
+    };
+    if !has_rule {
+        recurrence_lines.clear();
+    }
+
+    Some(CalendarEvent {
+        uid,
+        summary,
+        location,
+        start,
+        duration,
+        all_day,
+        recurrence_lines,
+        recurrence_id,
+        cancelled,
+    })
+}
+
+/**
+ * 展开窗口内的所有日程实例
+ *
+ * 带 RECURRENCE-ID 的修改实例会替换重复事件中对应的原始实例
+ */
+fn expand_events(
+    events: &[CalendarEvent],
+    window_start: DateTime<Local>,
+    window_end: DateTime<Local>,
+) -> Vec<AgendaEvent> {
+    let overrides: HashSet<(String, i64)> = events
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|id| (e.uid.clone(), id.timestamp())))
+        .collect();
+
+    let mut result = Vec::new();
+    for event in events.iter().filter(|e| !e.cancelled) {
+        let starts = if event.recurrence_lines.is_empty() || event.recurrence_id.is_some() {
+            vec![event.start]
+        } else {
+            occurrences(event, window_start - event.duration, window_end)
+                .into_iter()
+                .filter(|start| !overrides.contains(&(event.uid.clone(), start.timestamp())))
+                .collect()
+        };
+
+        for start in starts {
+            let end = start + event.duration;
+            let overlaps = if event.duration > Duration::zero() {
+                end > window_start && start < window_end
+            } else {
+                start >= window_start && start < window_end
+            };
+            if overlaps {
+                result.push(AgendaEvent {
+                    summary: event.summary.clone(),
+                    location: event.location.clone(),
+                    start,
+                    end,
+                    all_day: event.all_day,
+                });
+            }
+        }
+    }
+
+    result.sort_by_key(|e| (!e.all_day, e.start));
+    result
+}
+
+fn occurrences(
+    event: &CalendarEvent,
+    after: DateTime<Local>,
+    before: DateTime<Local>,
+) -> Vec<DateTime<Local>> {
+    let set = match event.recurrence_lines.join("\n").parse::<RRuleSet>() {
+        Ok(set) => set,
+        Err(e) => {
+            log::warn!("Failed to parse recurrence of {}: {}", event.summary, e);
+            return vec![event.start];
+        }
+    };
+
+    set.after(after.with_timezone(&rrule::Tz::LOCAL))
+        .before(before.with_timezone(&rrule::Tz::LOCAL))
+        .all(MAX_OCCURRENCES)
+        .dates
+        .into_iter()
+        .map(|dt| dt.with_timezone(&Local))
+        .collect()
+}
+
+/**
+ * 生成日程面板文本，按天分组
+ */
+fn format_agenda(events: &[AgendaEvent], now: &DateTime<Local>, config: &AgendaConfig) -> String {
+    let today = now.date_naive();
+    let days = if config.include_tomorrow { 2 } else { 1 };
+    let mut lines = Vec::new();
+
+    for offset in 0..days {
+        let date = today + Duration::days(offset);
+        let label = if offset == 0 { "今天" } else { "明天" };
+        lines.push(format!(
+            "{} {}/{} {}",
+            label,
+            date.month(),
+            date.day(),
+            WEEKDAYS[date.weekday().num_days_from_monday() as usize]
+        ));
+
+        let day_start = start_of_day(date);
+        let day_end = start_of_day(date + Duration::days(1));
+        let day_events: Vec<&AgendaEvent> = events
+            .iter()
+            .filter(|e| e.start < day_end && (e.end > day_start || e.start >= day_start))
+            .collect();
+
+        if day_events.is_empty() {
+            lines.push("  无日程".to_string());
+        }
+        for event in day_events.iter().take(config.max_events) {
+            let time = if event.all_day || (event.start <= day_start && event.end >= day_end) {
+                "全天".to_string()
+            } else if event.end > event.start {
+                format!("{}-{}", event.start.format("%H:%M"), event.end.format("%H:%M"))
+            } else {
+                event.start.format("%H:%M").to_string()
+            };
+            let summary: String = event.summary.chars().take(40).collect();
+            lines.push(format!("  {}  {}", time, summary));
+        }
+        if day_events.len() > config.max_events {
+            lines.push(format!("  还有 {} 项", day_events.len() - config.max_events));
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+SUMMARY:Daily standup\r\n\
+DTSTART:20260105T090000\r\n\
+DTEND:20260105T091500\r\n\
+RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR\r\n\
+EXDATE:20260107T090000\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT5M\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+RECURRENCE-ID:20260106T090000\r\n\
+SUMMARY:Daily standup (moved)\r\n\
+DTSTART:20260106T100000\r\n\
+DURATION:PT30M\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:offsite\r\n\
+SUMMARY:Team offsite\\, day one\r\n\
+DTSTART;VALUE=DATE:20260106\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+        assert_eq!(parse_duration("1H"), None);
+    }
+
+    #[test]
+    fn test_parse_datetime_with_timezone() {
+        let (dt, all_day) = parse_datetime("20260105T090000Z", None).unwrap();
+        assert!(!all_day);
+        assert_eq!(dt.with_timezone(&Utc), Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap());
+
+        let (dt, _) = parse_datetime("20260105T090000", Some("Asia/Shanghai")).unwrap();
+        assert_eq!(dt.with_timezone(&Utc), Utc.with_ymd_and_hms(2026, 1, 5, 1, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_expand_recurring_with_override() {
+        let events = parse_calendar(SAMPLE);
+        assert_eq!(events.len(), 3);
+
+        let start = start_of_day(NaiveDate::from_ymd_opt(2026, 1, 6).unwrap());
+        let end = start_of_day(NaiveDate::from_ymd_opt(2026, 1, 10).unwrap());
+        let agenda = expand_events(&events, start, end);
+
+        let summaries: Vec<&str> = agenda.iter().map(|e| e.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            vec!["Team offsite, day one", "Daily standup (moved)", "Daily standup", "Daily standup"]
+        );
+        assert_eq!(agenda[1].start, local(2026, 1, 6, 10, 0));
+        assert_eq!(agenda[1].end, local(2026, 1, 6, 10, 30));
+
+        // 7 日被 EXDATE 排除，8、9 日按规则展开
+        assert_eq!(agenda[2].start, local(2026, 1, 8, 9, 0));
+        assert_eq!(agenda[2].end, local(2026, 1, 8, 9, 15));
+        assert_eq!(agenda[3].start, local(2026, 1, 9, 9, 0));
+        assert!(agenda.iter().all(|e| e.start.date_naive() != NaiveDate::from_ymd_opt(2026, 1, 7).unwrap()));
+    }
+
+    #[test]
+    fn test_unknown_tzid_keeps_recurrence() {
+        let content = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:review\r\n\
+SUMMARY:Weekly review\r\n\
+DTSTART;TZID=W. Europe Standard Time:20260105T140000\r\n\
+DTEND;TZID=W. Europe Standard Time:20260105T150000\r\n\
+RRULE:FREQ=DAILY;COUNT=3\r\n\
+EXDATE;TZID=W. Europe Standard Time:20260106T140000\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let events = parse_calendar(content);
+
+        let start = start_of_day(NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        let end = start_of_day(NaiveDate::from_ymd_opt(2026, 1, 8).unwrap());
+        let starts: Vec<DateTime<Local>> = expand_events(&events, start, end).iter().map(|e| e.start).collect();
+        assert_eq!(starts, vec![local(2026, 1, 5, 14, 0), local(2026, 1, 7, 14, 0)]);
+
+        assert_eq!(
+            recurrence_line("DTSTART;TZID=Europe/Berlin:20260105T140000"),
+            "DTSTART;TZID=Europe/Berlin:20260105T140000"
+        );
+    }
+
+    #[test]
+    fn test_format_agenda() {
+        let events = vec![AgendaEvent {
+            summary: "Review".to_string(),
+            location: None,
+            start: local(2026, 1, 5, 14, 0),
+            end: local(2026, 1, 5, 15, 0),
+            all_day: false,
+        }];
+        let config = AgendaConfig::default();
+        let text = format_agenda(&events, &local(2026, 1, 5, 8, 0), &config);
+        assert_eq!(text, "今天 1/5 周一\n  14:00-15:00  Review\n明天 1/6 周二\n  无日程");
+    }
+}
@@ -0,0 +1,124 @@
+use crate::services::history::{self, ApplyTrigger};
+use crate::services::{cache, hooks, original, overlay, palette, rotation, triggers, wallpaper};
+use crate::types::{WallpaperError, WallpaperInfo};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Default)]
+struct CurrentState {
+    info: Option<WallpaperInfo>,
+    /// 最近一次交给系统的文件路径
+    applied_path: Option<PathBuf>,
+}
+
+static CURRENT: OnceLock<RwLock<CurrentState>> = OnceLock::new();
+
+fn current_state() -> &'static RwLock<CurrentState> {
+    CURRENT.get_or_init(|| RwLock::new(CurrentState::default()))
+}
+
+/**
+ * 当前由应用设置的壁纸
+ *
+ * local_path 始终指向缓存中的原图，而非渲染后的副本
+ */
+pub fn current_wallpaper() -> Option<WallpaperInfo> {
+    current_state().read().unwrap().info.clone()
+}
+
+pub fn applied_path() -> Option<PathBuf> {
+    current_state().read().unwrap().applied_path.clone()
+}
+
+/**
+ * 壁纸被外部修改后清除当前状态，避免叠加层刷新时覆盖用户的选择
+ */
+pub fn clear_current() {
+    *current_state().write().unwrap() = CurrentState::default();
+}
+
+/**
+ * 设置壁纸的统一入口
+ *
+ * 确保原图已缓存，按需渲染叠加层后设置为系统壁纸并写入历史记录，然后在后台更新配色并执行钩子。
+ * 返回实际交给系统的文件路径（可能是渲染后的副本）
+ */
+pub async fn apply_wallpaper(
+    app: &AppHandle,
+    wallpaper: WallpaperInfo,
+    trigger: ApplyTrigger,
+) -> Result<PathBuf, WallpaperError> {
+    // 缓存文件可能已被删除，此时重新下载
+    let original = match &wallpaper.local_path {
+        Some(path) if path.exists() => path.clone(),
+        _ => cache::download_and_cache(app, &wallpaper).await?,
+    };
+
+    let info = WallpaperInfo {
+        local_path: Some(original.clone()),
+        cached: true,
+        ..wallpaper
+    };
+
+    // 第一次修改前保存用户原来的壁纸
+    if original::get_original().get().is_none() {
+        let cache_dir = cache::get_cache_dir(app)?;
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            original::get_original().ensure_captured(&cache_dir)
+        })
+        .await;
+    }
+
+    let path = render_and_set(app, &info).await?;
+    current_state().write().unwrap().info = Some(info.clone());
+
+    if trigger != ApplyTrigger::History {
+        history::get_history().record(&info, trigger);
+        rotation::get_rotation().mark_shown(&info, chrono::Local::now());
+    }
+    // 成功换了壁纸，之前失败的定时切换不再需要在网络恢复后重试
+    triggers::get_triggers().record_success();
+
+    let handle = app.clone();
+    let applied = path.clone();
+    tauri::async_runtime::spawn(async move {
+        // 先更新配色再执行钩子，钩子脚本可以读取最新的主题文件
+        palette::update(&handle, original).await;
+        hooks::run_all(&handle, &info, &applied).await;
+    });
+
+    Ok(path)
+}
+
+/**
+ * 基于缓存原图重新渲染并设置当前壁纸
+ *
+ * 用于叠加层配置变化或模板中的动态字段（如日期）变化时刷新
+ */
+pub async fn reapply_current(app: &AppHandle) -> Result<Option<PathBuf>, WallpaperError> {
+    match current_wallpaper() {
+        Some(info) => render_and_set(app, &info).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+async fn render_and_set(app: &AppHandle, info: &WallpaperInfo) -> Result<PathBuf, WallpaperError> {
+    let cache_dir = cache::get_cache_dir(app)?;
+    let job = info.clone();
+    let path = tauri::async_runtime::spawn_blocking(move || overlay::prepare(&cache_dir, &job))
+        .await
+        .map_err(|e| WallpaperError::ApiError(e.to_string()))??;
+
+    // 先记录路径再设置，避免外部变化检测把本次切换误判为外部修改
+    let previous = current_state().write().unwrap().applied_path.replace(path.clone());
+    if let Err(e) = wallpaper::set_wallpaper(&path) {
+        current_state().write().unwrap().applied_path = previous;
+        return Err(e);
+    }
+
+    app.emit("wallpaper-set", &path)
+        .map_err(|e| WallpaperError::ApiError(e.to_string()))?;
+
+    Ok(path)
+}
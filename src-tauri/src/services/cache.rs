@@ -1,7 +1,8 @@
-use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use crate::services::http;
+use crate::types::{PaginatedResponse, WallpaperError, WallpaperInfo, WallpaperListItem, WallpaperSource};
 use std::{
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tauri::Manager;
 
@@ -23,15 +24,16 @@ pub async fn download_and_cache<R: tauri::Runtime>(
     app: &impl Manager<R>,
     wallpaper: &WallpaperInfo,
 ) -> Result<PathBuf, WallpaperError> {
+    if wallpaper.source == WallpaperSource::Local {
+        // 本地图片不复制到缓存，文件被移走时直接报错
+        return match &wallpaper.local_path {
+            Some(path) if path.exists() => Ok(path.clone()),
+            _ => Err(WallpaperError::WallpaperError(format!("Local file not found: {}", wallpaper.url))),
+        };
+    }
+
     let cache_dir = get_cache_dir(app)?;
-    let source_dir = cache_dir.join(match wallpaper.source {
-        WallpaperSource::Bing => "bing",
-        WallpaperSource::Wallhaven => "wallhaven",
-        WallpaperSource::Unsplash => "unsplash",
-        WallpaperSource::Pixabay => "pixabay",
-        WallpaperSource::Pexels => "pexels",
-        WallpaperSource::Reddit => "reddit",
-    });
+    let source_dir = cache_dir.join(wallpaper.source.as_str());
 
     if !source_dir.exists() {
         fs::create_dir_all(&source_dir)?;
@@ -40,17 +42,98 @@ pub async fn download_and_cache<R: tauri::Runtime>(
     let file_name = format!("{}.jpg", wallpaper.id);
     let file_path = source_dir.join(&file_name);
 
-    if file_path.exists() {
-        return Ok(file_path);
+    if !file_path.exists() {
+        if wallpaper.source == WallpaperSource::Generated {
+            // 生成的壁纸没有远程地址，按 url 中的参数在本地渲染
+            let url = wallpaper.url.clone();
+            let target = file_path.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                crate::sources::generated::render_to_file(&url, &target)
+            })
+            .await
+            .map_err(|e| WallpaperError::ApiError(e.to_string()))??;
+            crate::sources::generated::prune_cache(&source_dir, &file_path);
+        } else {
+            let bytes = http::download(&wallpaper.url).await?;
+            fs::write(&file_path, bytes)?;
+            track_download(wallpaper);
+        }
     }
 
-    let response = reqwest::get(&wallpaper.url).await?;
-    let bytes = response.bytes().await?;
-    fs::write(&file_path, bytes)?;
+    if !metadata_path(&file_path).exists() {
+        save_metadata(&file_path, wallpaper)?;
+    }
 
     Ok(file_path)
 }
 
+/**
+ * Unsplash 要求每次下载都通知统计地址，在后台发送，失败不影响缓存
+ */
+fn track_download(wallpaper: &WallpaperInfo) {
+    if wallpaper.source != WallpaperSource::Unsplash {
+        return;
+    }
+    let Some(location) = wallpaper
+        .attribution
+        .as_ref()
+        .and_then(|attribution| attribution.download_location.clone())
+    else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::sources::unsplash::track_download(&location).await {
+            log::warn!("Failed to track Unsplash download: {}", e);
+        }
+    });
+}
+
+/**
+ * 缓存图片旁边保存的元数据文件路径
+ */
+pub fn metadata_path(image: &Path) -> PathBuf {
+    image.with_extension("json")
+}
+
+pub fn save_metadata(image: &Path, wallpaper: &WallpaperInfo) -> Result<(), WallpaperError> {
+    let info = WallpaperInfo {
+        local_path: Some(image.to_path_buf()),
+        cached: true,
+        ..wallpaper.clone()
+    };
+    fs::write(metadata_path(image), serde_json::to_vec_pretty(&info)?)?;
+    Ok(())
+}
+
+/**
+ * 读取缓存图片的元数据，没有元数据时根据目录结构推断来源和 ID
+ */
+pub fn load_metadata(image: &Path) -> Option<WallpaperInfo> {
+    if let Some(info) = fs::read_to_string(metadata_path(image))
+        .ok()
+        .and_then(|content| serde_json::from_str::<WallpaperInfo>(&content).ok())
+    {
+        return Some(WallpaperInfo {
+            local_path: Some(image.to_path_buf()),
+            ..info
+        });
+    }
+
+    let id = image.file_stem()?.to_string_lossy().to_string();
+    let source_name = image.parent()?.file_name()?.to_string_lossy().to_string();
+    let source = WallpaperSource::from_str(&source_name)?;
+    Some(WallpaperInfo {
+        title: id.clone(),
+        id,
+        url: String::new(),
+        source,
+        local_path: Some(image.to_path_buf()),
+        cached: true,
+        author: None,
+        attribution: None,
+    })
+}
+
 pub fn get_cached_path<R: tauri::Runtime>(app: &impl Manager<R>, id: &str) -> Option<PathBuf> {
     let cache_dir = get_cache_dir(app).ok()?;
     let bing_path = cache_dir.join("bing").join(format!("{}.jpg", id));
@@ -75,13 +158,14 @@ pub fn list_cached_files<R: tauri::Runtime>(app: &impl Manager<R>) -> Result<Vec
     let cache_dir = get_cache_dir(app)?;
     let mut files = Vec::new();
 
-    for source in ["bing", "wallhaven", "unsplash", "pixabay", "reddit"] {
+    for source in ["bing", "wallhaven", "unsplash", "pixabay", "pexels", "reddit", "generated"] {
         let source_dir = cache_dir.join(source);
         if source_dir.exists() {
             if let Ok(entries) = fs::read_dir(&source_dir) {
                 for entry in entries.flatten() {
                     if let Ok(metadata) = entry.metadata() {
-                        if metadata.is_file() {
+                        let is_image = entry.path().extension().is_some_and(|ext| ext == "jpg");
+                        if metadata.is_file() && is_image {
                             let file_name = entry.file_name().to_string_lossy().to_string();
                             let id = file_name.trim_end_matches(".jpg").to_string();
                             files.push((id, entry.path()));
@@ -95,10 +179,64 @@ pub fn list_cached_files<R: tauri::Runtime>(app: &impl Manager<R>) -> Result<Vec
     Ok(files)
 }
 
+/**
+ * 按分页列出已缓存的壁纸，可以只列指定来源，离线时代替在线列表
+ *
+ * 缩略图返回本地路径，webview 不能直接加载 file://，前端需要用 convertFileSrc 转换
+ */
+pub fn cached_page<R: tauri::Runtime>(
+    app: &impl Manager<R>,
+    source: Option<&WallpaperSource>,
+    page: u32,
+) -> Result<PaginatedResponse<WallpaperListItem>, WallpaperError> {
+    const PER_PAGE: u32 = 24;
+
+    let mut cached: Vec<WallpaperInfo> = list_cached_files(app)?
+        .into_iter()
+        .filter_map(|(_, path)| load_metadata(&path))
+        .filter(|info| source.map_or(true, |source| &info.source == source))
+        .collect();
+    cached.sort_by(|a, b| a.source.as_str().cmp(b.source.as_str()).then_with(|| a.id.cmp(&b.id)));
+
+    let page = page.max(1);
+    let total = cached.len() as u32;
+    let data = cached
+        .into_iter()
+        .skip((page - 1).saturating_mul(PER_PAGE) as usize)
+        .take(PER_PAGE as usize)
+        .map(|info| {
+            let local_path = info
+                .local_path
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default();
+            WallpaperListItem {
+                url: if info.url.is_empty() { local_path.clone() } else { info.url },
+                thumb_url: local_path,
+                id: info.id,
+                title: info.title,
+                source: info.source,
+                author: info.author,
+                attribution: info.attribution,
+            }
+        })
+        .collect();
+
+    Ok(PaginatedResponse {
+        data,
+        current_page: page,
+        last_page: total.div_ceil(PER_PAGE).max(1),
+        per_page: PER_PAGE,
+        total,
+        seed: None,
+    })
+}
+
 pub fn delete_cached_file<R: tauri::Runtime>(app: &impl Manager<R>, id: &str) -> Result<bool, WallpaperError> {
     if let Some(path) = get_cached_path(app, id) {
         if path.exists() {
             fs::remove_file(&path)?;
+            let _ = fs::remove_file(metadata_path(&path));
             return Ok(true);
         }
     }
@@ -131,6 +269,8 @@ mod tests {
             source: WallpaperSource::Bing,
             local_path: None,
             cached: false,
+            author: None,
+            attribution: None,
         };
         let path = PathBuf::from("/cache").join("bing").join("test.jpg");
         let expected = format!("{:?}", path);
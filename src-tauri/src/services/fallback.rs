@@ -0,0 +1,392 @@
+use crate::services::{fetcher, offline, rotation, scheduler, store};
+use crate::sources::provider::SourceQuery;
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+
+const FALLBACK_FILE: &str = "fallback.json";
+
+/**
+ * 熔断器状态
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    /// 正常请求
+    Closed,
+    /// 连续失败，冷却结束前不再请求
+    Open,
+    /// 冷却结束，放行一次试探请求
+    HalfOpen,
+}
+
+/**
+ * 请求失败时依次尝试的备用来源
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FallbackStep {
+    Source { source: WallpaperSource },
+    /// 本地缓存，优先选原来源的缓存
+    Cache,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FallbackConfig {
+    /// 关闭后只请求指定的来源，熔断器仍然生效
+    pub enabled: bool,
+    pub order: Vec<FallbackStep>,
+    /// 连续失败多少次后熔断
+    pub failure_threshold: u32,
+    /// 熔断后多久放行试探请求
+    pub cooldown_seconds: u64,
+}
+
+impl Default for FallbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            order: vec![
+                FallbackStep::Source { source: WallpaperSource::Bing },
+                FallbackStep::Cache,
+            ],
+            failure_threshold: 3,
+            cooldown_seconds: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealth {
+    pub source: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// 熔断时在此之后放行试探请求
+    pub retry_at: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+    pub last_success: Option<DateTime<Local>>,
+}
+
+impl ProviderHealth {
+    fn new(source: &WallpaperSource) -> Self {
+        Self {
+            source: source.as_str().to_string(),
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            retry_at: None,
+            last_error: None,
+            last_success: None,
+        }
+    }
+}
+
+/**
+ * 通知前端实际提供壁纸的来源
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FallbackEvent {
+    pub requested: WallpaperSource,
+    pub served_by: FallbackStep,
+    pub wallpaper: WallpaperInfo,
+    /// 前面各来源的失败原因
+    pub errors: Vec<String>,
+}
+
+pub struct FallbackManager {
+    config: RwLock<FallbackConfig>,
+    providers: RwLock<HashMap<String, ProviderHealth>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl FallbackManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(FallbackConfig::default()),
+            providers: RwLock::new(HashMap::new()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载备用顺序等配置，熔断器状态不保存，重启后重新统计
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, FALLBACK_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: FallbackConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save fallback config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> FallbackConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn health(&self, source: &WallpaperSource) -> ProviderHealth {
+        self.providers
+            .read()
+            .unwrap()
+            .get(source.as_str())
+            .cloned()
+            .unwrap_or_else(|| ProviderHealth::new(source))
+    }
+
+    pub fn all(&self) -> Vec<ProviderHealth> {
+        let mut providers: Vec<ProviderHealth> = self.providers.read().unwrap().values().cloned().collect();
+        providers.sort_by(|a, b| a.source.cmp(&b.source));
+        providers
+    }
+
+    /**
+     * 是否可以请求该来源
+     *
+     * 熔断冷却结束后进入半开状态并放行一次请求；试探请求一直没有结果时，
+     * 再过一个冷却时间放行下一次
+     */
+    pub fn allow(&self, source: &WallpaperSource, now: DateTime<Local>) -> bool {
+        let cooldown = chrono::Duration::seconds(self.get_config().cooldown_seconds as i64);
+        let mut providers = self.providers.write().unwrap();
+        let Some(health) = providers.get_mut(source.as_str()) else {
+            return true;
+        };
+        if health.state == CircuitState::Closed {
+            return true;
+        }
+        if health.retry_at.is_some_and(|retry_at| now < retry_at) {
+            return false;
+        }
+        health.state = CircuitState::HalfOpen;
+        health.retry_at = Some(now + cooldown);
+        true
+    }
+
+    pub fn record_success(&self, source: &WallpaperSource, now: DateTime<Local>) {
+        let mut providers = self.providers.write().unwrap();
+        let health = providers
+            .entry(source.as_str().to_string())
+            .or_insert_with(|| ProviderHealth::new(source));
+        health.state = CircuitState::Closed;
+        health.consecutive_failures = 0;
+        health.retry_at = None;
+        health.last_success = Some(now);
+    }
+
+    /**
+     * 记录一次失败，连续失败达到阈值或试探请求失败时熔断
+     */
+    pub fn record_failure(&self, source: &WallpaperSource, error: &str, now: DateTime<Local>) {
+        let config = self.get_config();
+        let mut providers = self.providers.write().unwrap();
+        let health = providers
+            .entry(source.as_str().to_string())
+            .or_insert_with(|| ProviderHealth::new(source));
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+
+        if health.state == CircuitState::HalfOpen
+            || health.consecutive_failures >= config.failure_threshold.max(1)
+        {
+            if health.state != CircuitState::Open {
+                log::warn!("Circuit opened for {}: {}", source.as_str(), error);
+            }
+            health.state = CircuitState::Open;
+            health.retry_at = Some(now + chrono::Duration::seconds(config.cooldown_seconds as i64));
+        }
+    }
+}
+
+impl Default for FallbackManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static FALLBACK: OnceLock<FallbackManager> = OnceLock::new();
+
+pub fn get_fallback() -> &'static FallbackManager {
+    FALLBACK.get_or_init(FallbackManager::new)
+}
+
+/**
+ * 尝试顺序：先请求的来源，启用备用时再按配置顺序，重复的去掉
+ */
+pub fn chain(requested: &WallpaperSource, config: &FallbackConfig) -> Vec<FallbackStep> {
+    let mut steps = vec![FallbackStep::Source { source: requested.clone() }];
+    if config.enabled {
+        for step in &config.order {
+            if !steps.contains(step) {
+                steps.push(step.clone());
+            }
+        }
+    }
+    steps
+}
+
+/**
+ * 从单个来源取下一张壁纸并下载，使用该来源定时切换配置中的搜索条件
+ */
+pub async fn fetch_source(app: &AppHandle, source: &WallpaperSource) -> Result<WallpaperInfo, WallpaperError> {
+    let query = scheduler::get_scheduler().query(source);
+    fetch_query(app, source, &query).await
+}
+
+/**
+ * 按搜索条件从来源取下一张壁纸并下载，结果计入该来源的熔断器
+ */
+pub async fn fetch_query(
+    app: &AppHandle,
+    source: &WallpaperSource,
+    query: &SourceQuery,
+) -> Result<WallpaperInfo, WallpaperError> {
+    let manager = get_fallback();
+    if !manager.allow(source, Local::now()) {
+        let retry_at = manager
+            .health(source)
+            .retry_at
+            .map(|at| at.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        return Err(WallpaperError::ApiError(format!(
+            "{} is unavailable, retry after {}",
+            source.as_str(),
+            retry_at
+        )));
+    }
+
+    let result = match rotation::next(source.clone(), query).await {
+        Ok(selected) => fetcher::prepare(app, selected).await,
+        Err(e) => Err(e),
+    };
+    match &result {
+        Ok(_) => manager.record_success(source, Local::now()),
+        Err(e) => manager.record_failure(source, &e.to_string(), Local::now()),
+    }
+    result
+}
+
+/**
+ * 按备用顺序取下一张壁纸
+ *
+ * 跳过熔断中的来源；离线模式下跳过在线来源并始终尝试本地缓存。
+ * 不是由请求的来源提供时发送 wallpaper-fallback 事件
+ */
+pub async fn fetch(app: &AppHandle, requested: WallpaperSource) -> Result<WallpaperInfo, WallpaperError> {
+    let mut errors = Vec::new();
+    let mut steps = chain(&requested, &get_fallback().get_config());
+    if offline::get_offline().is_offline() && !steps.contains(&FallbackStep::Cache) {
+        steps.push(FallbackStep::Cache);
+    }
+
+    for step in steps {
+        let result = match &step {
+            FallbackStep::Source { source } if source.is_remote() && offline::get_offline().is_offline() => {
+                Err(WallpaperError::WallpaperError(offline::Offline.to_string()))
+            }
+            FallbackStep::Source { source } => fetch_source(app, source).await,
+            FallbackStep::Cache => fetcher::from_cache_or_any(app, Some(&requested)).await,
+        };
+
+        match result {
+            Ok(info) => {
+                if !errors.is_empty() {
+                    log::info!("Served {} request from {:?}", requested.as_str(), step);
+                    let event = FallbackEvent {
+                        requested,
+                        served_by: step,
+                        wallpaper: info.clone(),
+                        errors,
+                    };
+                    let _ = app.emit("wallpaper-fallback", event);
+                }
+                return Ok(info);
+            }
+            Err(e) => {
+                log::warn!("Fallback step {:?} failed: {}", step, e);
+                let name = match &step {
+                    FallbackStep::Source { source } => source.as_str(),
+                    FallbackStep::Cache => "cache",
+                };
+                errors.push(format!("{}: {}", name, e));
+            }
+        }
+    }
+
+    Err(WallpaperError::ApiError(format!(
+        "All sources failed: {}",
+        errors.join("; ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_transitions() {
+        let manager = FallbackManager::new();
+        let source = WallpaperSource::Wallhaven;
+        let now = Local::now();
+
+        assert!(manager.allow(&source, now));
+        manager.record_failure(&source, "timed out", now);
+        manager.record_failure(&source, "timed out", now);
+        assert_eq!(manager.health(&source).state, CircuitState::Closed);
+        manager.record_failure(&source, "timed out", now);
+        assert_eq!(manager.health(&source).state, CircuitState::Open);
+        assert!(!manager.allow(&source, now + chrono::Duration::seconds(60)));
+
+        // 冷却结束后只放行一次试探请求
+        let later = now + chrono::Duration::seconds(301);
+        assert!(manager.allow(&source, later));
+        assert_eq!(manager.health(&source).state, CircuitState::HalfOpen);
+        assert!(!manager.allow(&source, later));
+
+        // 试探失败重新熔断
+        manager.record_failure(&source, "502", later);
+        assert_eq!(manager.health(&source).state, CircuitState::Open);
+
+        let recovered = later + chrono::Duration::seconds(301);
+        assert!(manager.allow(&source, recovered));
+        manager.record_success(&source, recovered);
+        let health = manager.health(&source);
+        assert_eq!((health.state, health.consecutive_failures), (CircuitState::Closed, 0));
+    }
+
+    #[test]
+    fn test_chain_order() {
+        let config: FallbackConfig = serde_json::from_str(
+            r#"{"order": [{"kind": "source", "source": "wallhaven"}, {"kind": "cache"}, {"kind": "source", "source": "generated"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.failure_threshold, 3);
+
+        let steps = chain(&WallpaperSource::Wallhaven, &config);
+        assert_eq!(
+            steps,
+            vec![
+                FallbackStep::Source { source: WallpaperSource::Wallhaven },
+                FallbackStep::Cache,
+                FallbackStep::Source { source: WallpaperSource::Generated },
+            ]
+        );
+
+        let disabled = FallbackConfig { enabled: false, ..config };
+        assert_eq!(chain(&WallpaperSource::Bing, &disabled).len(), 1);
+    }
+}
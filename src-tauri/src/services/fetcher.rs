@@ -0,0 +1,149 @@
+use crate::services::history::ApplyTrigger;
+use crate::services::playlist::{self, PlaylistConfig};
+use crate::services::prefetch::{self, PrefetchTarget};
+use crate::services::power::{self, PowerPolicy};
+use crate::services::{apply, cache, fallback, offline, rotation};
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+
+/**
+ * 按轮换模式从指定来源选出下一张壁纸并缓存到本地
+ *
+ * 手动切换和定时切换共用此流程。有预取好的壁纸时直接使用，并在后台补充队列；
+ * 来源失败或熔断时按备用顺序改用其他来源或本地缓存
+ */
+pub async fn fetch_next(app: &AppHandle, source: WallpaperSource) -> Result<WallpaperInfo, WallpaperError> {
+    let target = PrefetchTarget::Source { source: source.clone() };
+    let info = match prefetch::get_prefetcher().take(&target.key()) {
+        Some(info) => info,
+        None => fallback::fetch(app, source).await?,
+    };
+    prefetch::spawn_refill(app, target);
+
+    app.emit("wallpaper-fetched", &info)
+        .map_err(|e| WallpaperError::ApiError(e.to_string()))?;
+
+    Ok(info)
+}
+
+/**
+ * 从播放列表中取下一张壁纸，同样优先使用预取结果
+ */
+pub async fn fetch_from_playlist(
+    app: &AppHandle,
+    config: &PlaylistConfig,
+) -> Result<WallpaperInfo, WallpaperError> {
+    let info = match prefetch::get_prefetcher().take(&PrefetchTarget::Playlist.key()) {
+        Some(info) => info,
+        None => playlist::fetch_next(app, config).await?,
+    };
+    prefetch::spawn_refill(app, PrefetchTarget::Playlist);
+
+    app.emit("wallpaper-fetched", &info)
+        .map_err(|e| WallpaperError::ApiError(e.to_string()))?;
+
+    Ok(info)
+}
+
+/**
+ * 补全标题并下载选中的壁纸，下载失败时撤销轮换中的选中记录
+ */
+pub async fn prepare(app: &AppHandle, selected: WallpaperInfo) -> Result<WallpaperInfo, WallpaperError> {
+    let result = download(app, selected.clone()).await;
+    if result.is_err() {
+        rotation::get_rotation().release(&selected);
+    }
+    result
+}
+
+async fn download(app: &AppHandle, mut selected: WallpaperInfo) -> Result<WallpaperInfo, WallpaperError> {
+    if let WallpaperSource::Wallhaven = selected.source {
+        if let Ok(tags) = crate::sources::wallhaven::get_wallpaper_details(&selected.id).await {
+            if !tags.is_empty() {
+                let title_tags = tags.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+                let resolution = selected.title.split('(').nth(1)
+                    .map(|s| format!("({})", s.trim_end_matches(')')))
+                    .unwrap_or_default();
+                selected.title = format!("{} {}", title_tags, resolution);
+
+                if let Some(c) = selected.title.get_mut(0..1) {
+                    c.make_ascii_uppercase();
+                }
+            }
+        }
+    }
+
+    // 使用电池或计费网络时下载较小的版本
+    let download = if power::get_power().policy() >= PowerPolicy::Smaller {
+        power::smaller_variant(&selected)
+    } else {
+        selected.clone()
+    };
+    let cached_path = cache::download_and_cache(app, &download).await?;
+
+    Ok(WallpaperInfo {
+        local_path: Some(cached_path),
+        cached: true,
+        ..selected
+    })
+}
+
+/**
+ * 从已缓存的壁纸中按轮换模式选一张，可以只选指定来源
+ */
+pub async fn from_cache(
+    app: &AppHandle,
+    source: Option<&WallpaperSource>,
+) -> Result<WallpaperInfo, WallpaperError> {
+    let cached: Vec<WallpaperInfo> = cache::list_cached_files(app)?
+        .into_iter()
+        .filter_map(|(_, path)| cache::load_metadata(&path))
+        .filter(|info| source.map_or(true, |source| &info.source == source))
+        .collect();
+
+    let rotation = rotation::get_rotation();
+    let now = chrono::Local::now();
+    let selected = rotation
+        .pick(&cached, &rotation.get_config(), now)
+        .or_else(|| rotation.least_recent(&cached))
+        .ok_or_else(|| WallpaperError::WallpaperError("No cached wallpapers".to_string()))?;
+    rotation.reserve(&selected);
+    Ok(selected)
+}
+
+/**
+ * 优先选指定来源的缓存，没有时选任意缓存；离线模式设置了只用所请求来源时不放宽
+ */
+pub async fn from_cache_or_any(
+    app: &AppHandle,
+    source: Option<&WallpaperSource>,
+) -> Result<WallpaperInfo, WallpaperError> {
+    match from_cache(app, source).await {
+        Ok(info) => Ok(info),
+        Err(e) if source.is_none() || offline::get_offline().restricts_source() => Err(e),
+        Err(_) => from_cache(app, None).await,
+    }
+}
+
+/**
+ * 托盘菜单“下一张壁纸”
+ *
+ * 启用播放列表时从播放列表中抽取，否则沿用当前壁纸的来源（默认 Bing），
+ * API Key 取自该来源的定时切换配置
+ */
+pub async fn next_from_tray(app: &AppHandle) -> Result<PathBuf, WallpaperError> {
+    let config = playlist::get_playlist().get_config();
+    if config.is_active() {
+        let info = fetch_from_playlist(app, &config).await?;
+        return apply::apply_wallpaper(app, info, ApplyTrigger::Tray).await;
+    }
+
+    let source = apply::current_wallpaper()
+        .map(|info| info.source)
+        .unwrap_or(WallpaperSource::Bing);
+
+    let info = fetch_next(app, source).await?;
+    apply::apply_wallpaper(app, info, ApplyTrigger::Tray).await
+}
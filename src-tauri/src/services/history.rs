@@ -0,0 +1,261 @@
+use crate::services::store;
+use crate::types::{PaginatedResponse, WallpaperError, WallpaperInfo};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+// 最多保留的历史记录条数
+const MAX_ENTRIES: usize = 500;
+const HISTORY_FILE: &str = "history.json";
+// 每页最多返回的条数
+const MAX_PER_PAGE: u32 = 100;
+
+/**
+ * 壁纸切换的触发方式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplyTrigger {
+    Manual,
+    Schedule,
+    Tray,
+    /// 用户或其他工具在应用外修改了壁纸
+    External,
+    /// 在历史记录中前后切换，不产生新记录
+    History,
+    /// 应用启动时切换
+    Startup,
+    /// 从睡眠中唤醒后切换
+    Wake,
+    /// 网络恢复后重试失败的定时切换
+    Reconnect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub wallpaper: WallpaperInfo,
+    pub applied_at: DateTime<Local>,
+    pub trigger: ApplyTrigger,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct HistoryState {
+    /// 按时间从旧到新排列
+    entries: Vec<HistoryEntry>,
+    /// 当前显示的记录下标
+    cursor: Option<usize>,
+    next_id: u64,
+}
+
+impl HistoryState {
+    fn push(&mut self, entry: HistoryEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.cursor = Some(self.entries.len() - 1);
+    }
+
+    fn neighbor(&self, delta: isize) -> Option<usize> {
+        let cursor = self.cursor? as isize;
+        let index = cursor + delta;
+        if index < 0 || index as usize >= self.entries.len() {
+            return None;
+        }
+        Some(index as usize)
+    }
+
+    fn page(&self, page: u32, per_page: u32) -> PaginatedResponse<HistoryEntry> {
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
+        let page = page.max(1);
+        let total = self.entries.len() as u32;
+        let data = self
+            .entries
+            .iter()
+            .rev()
+            .skip((page - 1).saturating_mul(per_page) as usize)
+            .take(per_page as usize)
+            .cloned()
+            .collect();
+
+        PaginatedResponse {
+            data,
+            current_page: page,
+            last_page: total.div_ceil(per_page).max(1),
+            per_page,
+            total,
+            seed: None,
+        }
+    }
+}
+
+pub struct HistoryManager {
+    state: RwLock<HistoryState>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl HistoryManager {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(HistoryState::default()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 从应用数据目录加载历史记录
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, HISTORY_FILE)?;
+        if let Some(state) = store::load_json::<HistoryState>(&path) {
+            *self.state.write().unwrap() = state;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    /**
+     * 记录一次壁纸切换，并把当前位置移到最新一条
+     */
+    pub fn record(&self, wallpaper: &WallpaperInfo, trigger: ApplyTrigger) -> HistoryEntry {
+        let entry = {
+            let mut state = self.state.write().unwrap();
+            state.next_id += 1;
+            let entry = HistoryEntry {
+                id: state.next_id,
+                wallpaper: wallpaper.clone(),
+                applied_at: Local::now(),
+                trigger,
+            };
+            state.push(entry.clone());
+            entry
+        };
+        self.save();
+        entry
+    }
+
+    /**
+     * 相对当前位置的历史记录，delta 为 -1 表示上一张
+     */
+    pub fn neighbor(&self, delta: isize) -> Option<(usize, HistoryEntry)> {
+        let state = self.state.read().unwrap();
+        state
+            .neighbor(delta)
+            .map(|index| (index, state.entries[index].clone()))
+    }
+
+    pub fn set_cursor(&self, index: usize) {
+        {
+            let mut state = self.state.write().unwrap();
+            if index < state.entries.len() {
+                state.cursor = Some(index);
+            }
+        }
+        self.save();
+    }
+
+    /**
+     * 分页查询，最新的记录在前
+     */
+    pub fn page(&self, page: u32, per_page: u32) -> PaginatedResponse<HistoryEntry> {
+        self.state.read().unwrap().page(page, per_page)
+    }
+
+    pub fn clear(&self) {
+        *self.state.write().unwrap() = HistoryState::default();
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.read().unwrap().clone() else {
+            return;
+        };
+        let state = self.state.read().unwrap();
+        if let Err(e) = store::save_json(&path, &*state) {
+            log::warn!("Failed to save wallpaper history: {}", e);
+        }
+    }
+}
+
+impl Default for HistoryManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static HISTORY: OnceLock<HistoryManager> = OnceLock::new();
+
+pub fn get_history() -> &'static HistoryManager {
+    HISTORY.get_or_init(HistoryManager::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::WallpaperSource;
+
+    fn wallpaper(id: &str) -> WallpaperInfo {
+        WallpaperInfo {
+            id: id.to_string(),
+            title: id.to_string(),
+            url: format!("https://example.com/{}.jpg", id),
+            source: WallpaperSource::Bing,
+            local_path: None,
+            cached: true,
+            author: None,
+            attribution: None,
+        }
+    }
+
+    #[test]
+    fn test_navigation() {
+        let history = HistoryManager::new();
+        assert!(history.neighbor(-1).is_none());
+
+        for id in ["a", "b", "c"] {
+            history.record(&wallpaper(id), ApplyTrigger::Manual);
+        }
+        assert!(history.neighbor(1).is_none());
+
+        let (index, entry) = history.neighbor(-1).unwrap();
+        assert_eq!(entry.wallpaper.id, "b");
+        history.set_cursor(index);
+
+        let (_, entry) = history.neighbor(-1).unwrap();
+        assert_eq!(entry.wallpaper.id, "a");
+        let (_, entry) = history.neighbor(1).unwrap();
+        assert_eq!(entry.wallpaper.id, "c");
+
+        // 新的切换总是追加到末尾
+        history.record(&wallpaper("d"), ApplyTrigger::Schedule);
+        assert!(history.neighbor(1).is_none());
+        assert_eq!(history.neighbor(-1).unwrap().1.wallpaper.id, "c");
+    }
+
+    #[test]
+    fn test_page_newest_first() {
+        let history = HistoryManager::new();
+        for i in 0..(MAX_ENTRIES + 5) {
+            history.record(&wallpaper(&i.to_string()), ApplyTrigger::Tray);
+        }
+
+        let first = history.page(1, 20);
+        assert_eq!(first.total, MAX_ENTRIES as u32);
+        assert_eq!(first.last_page, 25);
+        assert_eq!(first.data[0].wallpaper.id, (MAX_ENTRIES + 4).to_string());
+
+        let last = history.page(25, 20);
+        assert_eq!(last.data.last().unwrap().wallpaper.id, "5");
+        assert!(history.page(26, 20).data.is_empty());
+
+        let clamped = history.page(u32::MAX, u32::MAX);
+        assert_eq!(clamped.per_page, MAX_PER_PAGE);
+        assert!(clamped.data.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::services::store;
+use crate::types::{WallpaperError, WallpaperInfo};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+const HOOKS_FILE: &str = "hooks.json";
+// 捕获输出的最大长度，避免脚本输出过多占用内存
+const MAX_OUTPUT_LEN: usize = 4096;
+// 管道中最多保留的字节数，足够容纳 MAX_OUTPUT_LEN 个 UTF-8 字符
+const MAX_OUTPUT_BYTES: usize = MAX_OUTPUT_LEN * 4;
+// shell 退出后等待输出读完的时间，后台进程可能一直持有管道
+const OUTPUT_GRACE: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HooksConfig {
+    pub enabled: bool,
+    /// 按顺序执行的 shell 命令
+    pub commands: Vec<String>,
+    pub timeout_seconds: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            commands: Vec::new(),
+            timeout_seconds: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookResult {
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+pub struct HookManager {
+    config: RwLock<HooksConfig>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl HookManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(HooksConfig::default()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载钩子配置，启动后的第一次定时切换也会执行钩子
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, HOOKS_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: HooksConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save hooks config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> HooksConfig {
+        self.config.read().unwrap().clone()
+    }
+}
+
+impl Default for HookManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static HOOKS: OnceLock<HookManager> = OnceLock::new();
+
+pub fn get_hooks() -> &'static HookManager {
+    HOOKS.get_or_init(HookManager::new)
+}
+
+/**
+ * 传给钩子脚本的环境变量
+ */
+pub fn hook_env(info: &WallpaperInfo, applied_path: &Path) -> Vec<(String, String)> {
+    let original = info
+        .local_path
+        .as_deref()
+        .unwrap_or(applied_path)
+        .to_string_lossy()
+        .to_string();
+    vec![
+        ("WM_PATH".to_string(), applied_path.to_string_lossy().to_string()),
+        ("WM_ORIGINAL_PATH".to_string(), original),
+        ("WM_SOURCE".to_string(), info.source.as_str().to_string()),
+        ("WM_ID".to_string(), info.id.clone()),
+        ("WM_TITLE".to_string(), info.title.clone()),
+        ("WM_URL".to_string(), info.url.clone()),
+    ]
+}
+
+/**
+ * Unix 上让钩子进程自成一个进程组，超时时可以连同它启动的子进程一起终止
+ */
+fn shell_command(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        #[cfg(unix)]
+        cmd.process_group(0);
+        cmd
+    }
+}
+
+/**
+ * 终止钩子及其所有子进程
+ */
+fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        // 进程组 ID 与 shell 的进程 ID 相同
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+fn truncate_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim();
+    match text.char_indices().nth(MAX_OUTPUT_LEN) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+/**
+ * 在后台读取管道直到关闭，只保留前 MAX_OUTPUT_BYTES 字节
+ *
+ * 超出部分继续读取并丢弃，钩子在后台启动的进程（如 `polybar &`）继承了管道，
+ * 持续读取可以避免它们写满管道阻塞或因管道关闭收到 SIGPIPE
+ */
+fn drain<R>(reader: Option<R>) -> (Arc<Mutex<Vec<u8>>>, tauri::async_runtime::JoinHandle<()>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let target = buffer.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let Some(mut reader) = reader else {
+            return;
+        };
+        let mut chunk = [0u8; 1024];
+        loop {
+            match reader.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let mut buffer = target.lock().unwrap();
+                    let room = MAX_OUTPUT_BYTES.saturating_sub(buffer.len());
+                    buffer.extend_from_slice(&chunk[..n.min(room)]);
+                }
+            }
+        }
+    });
+    (buffer, handle)
+}
+
+/**
+ * 执行单个钩子命令，只等待 shell 本身退出
+ *
+ * 输出在 shell 退出后稍等片刻即取走，不等待后台子进程关闭管道；
+ * 超时时 shell 仍在运行才终止整个进程组
+ */
+pub async fn run_hook(command: &str, env: &[(String, String)], timeout: Duration) -> HookResult {
+    let started = Instant::now();
+    let mut result = HookResult {
+        command: command.to_string(),
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        stdout: String::new(),
+        stderr: String::new(),
+        error: None,
+        duration_ms: 0,
+    };
+
+    let child = shell_command(command)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+
+    match child {
+        Ok(mut child) => {
+            let (stdout, mut stdout_task) = drain(child.stdout.take());
+            let (stderr, mut stderr_task) = drain(child.stderr.take());
+
+            match tokio::time::timeout(timeout, child.wait()).await {
+                Ok(Ok(status)) => {
+                    result.success = status.success();
+                    result.exit_code = status.code();
+                }
+                Ok(Err(e)) => result.error = Some(e.to_string()),
+                Err(_) => {
+                    // 恰好在超时边界退出的 shell 不再终止，以免误杀它在后台启动的进程
+                    if !matches!(child.try_wait(), Ok(Some(_))) {
+                        // Windows 上 taskkill 需要父进程还在，所以先终止进程树
+                        if let Some(pid) = child.id() {
+                            kill_tree(pid);
+                        }
+                    }
+                    result.timed_out = true;
+                    result.error = Some(format!("Timed out after {:?}", timeout));
+                }
+            }
+
+            // 读取任务不会被取消，超过等待时间后继续在后台排空管道
+            let _ = tokio::time::timeout(OUTPUT_GRACE, async {
+                let _ = (&mut stdout_task).await;
+                let _ = (&mut stderr_task).await;
+            })
+            .await;
+            result.stdout = truncate_output(&stdout.lock().unwrap());
+            result.stderr = truncate_output(&stderr.lock().unwrap());
+        }
+        Err(e) => result.error = Some(e.to_string()),
+    }
+
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+/**
+ * 壁纸切换后依次执行所有钩子，失败通过 hook-failed 事件上报
+ */
+pub async fn run_all(app: &AppHandle, info: &WallpaperInfo, applied_path: &Path) {
+    let config = get_hooks().get_config();
+    if !config.enabled || config.commands.is_empty() {
+        return;
+    }
+
+    let env = hook_env(info, applied_path);
+    let timeout = Duration::from_secs(config.timeout_seconds.max(1));
+
+    for command in config.commands.iter().filter(|c| !c.trim().is_empty()) {
+        let result = run_hook(command, &env, timeout).await;
+        if !result.success {
+            log::warn!("Hook failed: {} ({:?})", command, result.error);
+            let _ = app.emit("hook-failed", &result);
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+
+    fn env() -> Vec<(String, String)> {
+        vec![
+            ("WM_ID".to_string(), "abc".to_string()),
+            ("WM_SOURCE".to_string(), "bing".to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_receives_env() {
+        let result = run_hook("echo $WM_SOURCE-$WM_ID", &env(), Duration::from_secs(5)).await;
+        assert!(result.success);
+        assert_eq!(result.stdout, "bing-abc");
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_reports_failure_and_timeout() {
+        let result = run_hook("echo oops >&2; exit 3", &env(), Duration::from_secs(5)).await;
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stderr, "oops");
+
+        let result = run_hook("sleep 5", &env(), Duration::from_millis(100)).await;
+        assert!(result.timed_out);
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_child_processes() {
+        let marker = std::env::temp_dir().join(format!("wallpaper-mate-hook-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        let command = format!("(sleep 1; touch {}) & wait", marker.display());
+        let result = run_hook(&command, &env(), Duration::from_millis(100)).await;
+        assert!(result.timed_out);
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    async fn test_background_process_outlives_hook() {
+        let marker = std::env::temp_dir().join(format!("wallpaper-mate-hook-bg-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        // 后台进程继承了 stdout，钩子不应等它关闭管道，也不应终止它
+        let command = format!("echo started; (sleep 1; touch {}) &", marker.display());
+        let result = run_hook(&command, &env(), Duration::from_secs(5)).await;
+        assert!(result.success);
+        assert!(!result.timed_out);
+        assert_eq!(result.stdout, "started");
+        assert!(result.duration_ms < 1000);
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(marker.exists());
+        let _ = std::fs::remove_file(&marker);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::services::{offline, store};
+use crate::types::WallpaperError;
+use reqwest::{Certificate, Client, NoProxy, Proxy, Response};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tauri::AppHandle;
+
+const HTTP_FILE: &str = "http.json";
+// 空闲连接保留时间
+const POOL_IDLE_SECONDS: u64 = 90;
+// 单个文件的最大下载大小
+const MAX_DOWNLOAD_BYTES: u64 = 256 * 1024 * 1024;
+// 按 Content-Length 预分配的上限，Content-Length 可能不可信
+const MAX_PREALLOC_BYTES: u64 = 64 * 1024 * 1024;
+
+/**
+ * 默认 User-Agent，部分来源会拒绝没有 User-Agent 的请求
+ */
+pub fn default_user_agent() -> String {
+    format!("wallpaper-mate/{}", env!("CARGO_PKG_VERSION"))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpConfig {
+    pub connect_timeout_seconds: u64,
+    /// 两次收到数据之间允许的最长间隔
+    pub read_timeout_seconds: u64,
+    /// 代理地址，支持 http、https、socks5 和 socks5h
+    pub proxy: Option<String>,
+    /// 不走代理的主机，例如 localhost、.corp.example.com、10.0.0.0/8
+    pub no_proxy: Vec<String>,
+    /// 额外信任的 PEM 证书文件，用于企业内部的 HTTPS 代理
+    pub ca_bundle: Option<PathBuf>,
+    /// 为空时使用默认 User-Agent
+    pub user_agent: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_seconds: 10,
+            read_timeout_seconds: 30,
+            proxy: None,
+            no_proxy: Vec::new(),
+            ca_bundle: None,
+            user_agent: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_seconds.max(1))
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        Duration::from_secs(self.read_timeout_seconds.max(1))
+    }
+
+    /**
+     * API 请求整体的超时时间
+     */
+    pub fn request_timeout(&self) -> Duration {
+        self.connect_timeout() + self.read_timeout()
+    }
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<Certificate>, WallpaperError> {
+    let pem = fs::read(path)?;
+    let certificates = Certificate::from_pem_bundle(&pem)?;
+    if certificates.is_empty() {
+        return Err(WallpaperError::WallpaperError(format!(
+            "No certificates found in {}",
+            path.display()
+        )));
+    }
+    Ok(certificates)
+}
+
+/**
+ * 按配置创建 HTTP 客户端，代理地址或证书文件无效时返回错误
+ */
+pub fn build_client(config: &HttpConfig) -> Result<Client, WallpaperError> {
+    let user_agent = config
+        .user_agent
+        .clone()
+        .filter(|agent| !agent.trim().is_empty())
+        .unwrap_or_else(default_user_agent);
+
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .connect_timeout(config.connect_timeout())
+        .pool_idle_timeout(Duration::from_secs(POOL_IDLE_SECONDS));
+
+    if let Some(proxy) = config.proxy.as_deref().filter(|proxy| !proxy.trim().is_empty()) {
+        let no_proxy = NoProxy::from_string(&config.no_proxy.join(","));
+        builder = builder.proxy(Proxy::all(proxy.trim())?.no_proxy(no_proxy));
+    }
+
+    if let Some(path) = &config.ca_bundle {
+        for certificate in load_certificates(path)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+pub struct HttpManager {
+    config: RwLock<HttpConfig>,
+    client: RwLock<Client>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl HttpManager {
+    pub fn new() -> Self {
+        let config = HttpConfig::default();
+        let client = build_client(&config).unwrap_or_default();
+        Self {
+            config: RwLock::new(config),
+            client: RwLock::new(client),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载网络配置，启动时切换和预取需要在前端同步配置之前就走代理
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, HTTP_FILE)?;
+        if let Some(config) = store::load_json::<HttpConfig>(&path) {
+            match build_client(&config) {
+                Ok(client) => {
+                    *self.client.write().unwrap() = client;
+                    *self.config.write().unwrap() = config;
+                }
+                Err(e) => log::warn!("Ignoring invalid network config: {}", e),
+            }
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    /**
+     * 更新配置并重建客户端，配置无效时保留原来的客户端
+     */
+    pub fn set_config(&self, config: HttpConfig) -> Result<(), WallpaperError> {
+        let client = build_client(&config)?;
+        *self.client.write().unwrap() = client;
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save network config: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_config(&self) -> HttpConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 共享的客户端，克隆开销很小并且共用连接池
+     */
+    pub fn client(&self) -> Client {
+        self.client.read().unwrap().clone()
+    }
+}
+
+impl Default for HttpManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static HTTP: OnceLock<HttpManager> = OnceLock::new();
+
+pub fn get_http() -> &'static HttpManager {
+    HTTP.get_or_init(HttpManager::new)
+}
+
+pub fn client() -> Client {
+    get_http().client()
+}
+
+/**
+ * 逐块读取响应内容，超过读取超时没有收到数据或超过最大下载大小时中断
+ *
+ * 图片可能很大，不能像 API 请求那样限制整体时间
+ */
+pub async fn read_body(mut response: Response) -> Result<Vec<u8>, WallpaperError> {
+    let too_large = |url: &reqwest::Url| {
+        WallpaperError::WallpaperError(format!(
+            "Download exceeds {} MiB: {}",
+            MAX_DOWNLOAD_BYTES / 1024 / 1024,
+            url
+        ))
+    };
+    let content_length = response.content_length().unwrap_or(0);
+    if content_length > MAX_DOWNLOAD_BYTES {
+        return Err(too_large(response.url()));
+    }
+
+    let read_timeout = get_http().get_config().read_timeout();
+    let mut body = Vec::with_capacity(content_length.min(MAX_PREALLOC_BYTES) as usize);
+    loop {
+        let chunk = tokio::time::timeout(read_timeout, response.chunk())
+            .await
+            .map_err(|_| WallpaperError::WallpaperError(format!("Read timed out: {}", response.url())))??;
+        match chunk {
+            Some(chunk) if (body.len() + chunk.len()) as u64 > MAX_DOWNLOAD_BYTES => {
+                return Err(too_large(response.url()));
+            }
+            Some(chunk) => body.extend_from_slice(&chunk),
+            None => return Ok(body),
+        }
+    }
+}
+
+/**
+ * 用共享客户端下载文件，离线模式下直接失败
+ */
+pub async fn download(url: &str) -> Result<Vec<u8>, WallpaperError> {
+    let network = offline::get_offline();
+    if network.is_offline() {
+        return Err(WallpaperError::WallpaperError(offline::Offline.to_string()));
+    }
+    let response = match client().get(url).send().await {
+        Ok(response) => {
+            network.record_online();
+            response
+        }
+        Err(e) => {
+            network.record_error(&e).await;
+            return Err(e.into());
+        }
+    };
+    let response = response.error_for_status()?;
+    read_body(response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: HttpConfig = serde_json::from_str(r#"{"proxy": "socks5h://127.0.0.1:1080"}"#).unwrap();
+        assert_eq!(config.connect_timeout_seconds, 10);
+        assert_eq!(config.read_timeout_seconds, 30);
+        assert_eq!(config.request_timeout(), Duration::from_secs(40));
+        assert!(build_client(&config).is_ok());
+
+        let zero = HttpConfig { connect_timeout_seconds: 0, ..Default::default() };
+        assert_eq!(zero.connect_timeout(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_invalid_config_rejected() {
+        let manager = HttpManager::new();
+        let bad_proxy = HttpConfig { proxy: Some("not a url".to_string()), ..Default::default() };
+        assert!(manager.set_config(bad_proxy).is_err());
+
+        let bundle = std::env::temp_dir().join(format!("wallpaper-mate-ca-{}.pem", std::process::id()));
+        fs::write(&bundle, "not a certificate").unwrap();
+        let bad_bundle = HttpConfig { ca_bundle: Some(bundle.clone()), ..Default::default() };
+        assert!(manager.set_config(bad_bundle).is_err());
+        fs::remove_file(&bundle).unwrap();
+
+        // 无效配置不会替换当前配置
+        assert_eq!(manager.get_config(), HttpConfig::default());
+    }
+}
@@ -1,3 +1,23 @@
 pub mod cache;
 pub mod wallpaper;
 pub mod scheduler;
+pub mod apply;
+pub mod overlay;
+pub mod agenda;
+pub mod palette;
+pub mod fetcher;
+pub mod hooks;
+pub mod store;
+pub mod history;
+pub mod watcher;
+pub mod original;
+pub mod slideshow;
+pub mod rotation;
+pub mod playlist;
+pub mod prefetch;
+pub mod triggers;
+pub mod power;
+pub mod http;
+pub mod fallback;
+pub mod offline;
+pub mod secrets;
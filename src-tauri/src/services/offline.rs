@@ -0,0 +1,293 @@
+use crate::services::{http, store};
+use crate::types::WallpaperError;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const OFFLINE_FILE: &str = "offline.json";
+// 自动进入离线模式后检查网络是否恢复的间隔
+const PROBE_SECONDS: u64 = 30;
+const PROBE_TIMEOUT_SECONDS: u64 = 5;
+const PROBE_URL: &str = "https://www.bing.com";
+// 与 PROBE_URL 不同的主机，PROBE_URL 本身连接失败时用它确认
+const SECONDARY_PROBE_URL: &str = "https://www.cloudflare.com";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OfflineConfig {
+    /// 手动开启离线模式
+    pub forced: bool,
+    /// 连接失败时自动进入离线模式，网络恢复后自动退出
+    pub auto_detect: bool,
+    /// 离线时只使用所请求来源的缓存
+    pub filter_by_source: bool,
+}
+
+impl Default for OfflineConfig {
+    fn default() -> Self {
+        Self {
+            forced: false,
+            auto_detect: true,
+            filter_by_source: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineState {
+    pub offline: bool,
+    pub forced: bool,
+    /// 自动检测到断网的时间
+    pub detected_at: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+}
+
+struct Detection {
+    at: DateTime<Local>,
+    error: String,
+}
+
+pub struct OfflineManager {
+    config: RwLock<OfflineConfig>,
+    detected: RwLock<Option<Detection>>,
+    probing: AtomicBool,
+    confirming: AtomicBool,
+    app: RwLock<Option<AppHandle>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl OfflineManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(OfflineConfig::default()),
+            detected: RwLock::new(None),
+            probing: AtomicBool::new(false),
+            confirming: AtomicBool::new(false),
+            app: RwLock::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载配置，手动开启的离线模式在重启后仍然有效
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        *self.app.write().unwrap() = Some(app.clone());
+        let path = store::data_file(app, OFFLINE_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: OfflineConfig) {
+        let before = self.state();
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save offline config: {}", e);
+            }
+        }
+        self.notify(before);
+    }
+
+    pub fn get_config(&self) -> OfflineConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn state(&self) -> OfflineState {
+        let config = self.get_config();
+        let detected = self.detected.read().unwrap();
+        let detected = detected.as_ref().filter(|_| config.auto_detect);
+        OfflineState {
+            offline: config.forced || detected.is_some(),
+            forced: config.forced,
+            detected_at: detected.map(|d| d.at),
+            last_error: detected.map(|d| d.error.clone()),
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.state().offline
+    }
+
+    /**
+     * 离线时是否只使用所请求来源的缓存
+     */
+    pub fn restricts_source(&self) -> bool {
+        self.is_offline() && self.get_config().filter_by_source
+    }
+
+    /**
+     * 记录请求错误，连接失败且另一主机也无法访问时进入离线模式并在后台等待网络恢复
+     *
+     * 单个来源拒绝连接不代表断网，不应影响其他来源
+     */
+    pub async fn record_error(&self, error: &reqwest::Error) {
+        if !error.is_connect() || !self.get_config().auto_detect || self.detected.read().unwrap().is_some() {
+            return;
+        }
+        // 已有请求在确认时不重复探测
+        if self.confirming.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let failed_host = error.url().and_then(|url| url.host_str()).unwrap_or_default();
+        let reachable = is_reachable(probe_url(failed_host)).await;
+        self.confirming.store(false, Ordering::SeqCst);
+
+        if reachable {
+            log::warn!("Connection to {} failed but the network is available: {}", failed_host, error);
+            return;
+        }
+        self.mark_offline(error.to_string());
+    }
+
+    fn mark_offline(&self, error: String) {
+        let before = self.state();
+        {
+            let mut detected = self.detected.write().unwrap();
+            if detected.is_some() {
+                return;
+            }
+            log::warn!("Network unavailable, switching to offline mode: {}", error);
+            *detected = Some(Detection {
+                at: Local::now(),
+                error,
+            });
+        }
+        self.notify(before);
+        self.spawn_probe();
+    }
+
+    /**
+     * 收到任何响应都说明网络可用
+     */
+    pub fn record_online(&self) {
+        if self.detected.read().unwrap().is_none() {
+            return;
+        }
+        let before = self.state();
+        *self.detected.write().unwrap() = None;
+        log::info!("Network available again");
+        self.notify(before);
+    }
+
+    /**
+     * 状态变化时发送 offline-mode-changed 事件
+     */
+    fn notify(&self, before: OfflineState) {
+        let state = self.state();
+        if state.offline == before.offline {
+            return;
+        }
+        if let Some(app) = self.app.read().unwrap().as_ref() {
+            let _ = app.emit("offline-mode-changed", &state);
+        }
+    }
+
+    fn spawn_probe(&self) {
+        if self.app.read().unwrap().is_none() || self.probing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        tauri::async_runtime::spawn(async move {
+            let manager = get_offline();
+            while manager.detected.read().unwrap().is_some() {
+                tokio::time::sleep(Duration::from_secs(PROBE_SECONDS)).await;
+                if is_online().await {
+                    manager.record_online();
+                }
+            }
+            manager.probing.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl Default for OfflineManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static OFFLINE: OnceLock<OfflineManager> = OnceLock::new();
+
+pub fn get_offline() -> &'static OfflineManager {
+    OFFLINE.get_or_init(OfflineManager::new)
+}
+
+/**
+ * 通过共享客户端探测网络，配置了代理时经过代理
+ */
+pub async fn is_online() -> bool {
+    is_reachable(PROBE_URL).await
+}
+
+async fn is_reachable(url: &str) -> bool {
+    http::client()
+        .head(url)
+        .timeout(Duration::from_secs(PROBE_TIMEOUT_SECONDS))
+        .send()
+        .await
+        .is_ok()
+}
+
+/**
+ * 选择与连接失败的主机不同的探测地址
+ */
+fn probe_url(failed_host: &str) -> &'static str {
+    let same_host = reqwest::Url::parse(PROBE_URL)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.eq_ignore_ascii_case(failed_host)))
+        .unwrap_or(false);
+    if same_host {
+        SECONDARY_PROBE_URL
+    } else {
+        PROBE_URL
+    }
+}
+
+/**
+ * 离线时请求直接失败，不等待连接超时
+ */
+#[derive(Debug, thiserror::Error)]
+#[error("Offline mode: network requests are disabled")]
+pub struct Offline;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forced_and_detected() {
+        let manager = OfflineManager::new();
+        assert!(!manager.is_offline());
+
+        manager.set_config(OfflineConfig { forced: true, ..Default::default() });
+        assert!(manager.is_offline());
+        assert!(manager.restricts_source());
+
+        manager.set_config(OfflineConfig::default());
+        manager.mark_offline("connection refused".to_string());
+        let state = manager.state();
+        assert!(state.offline && !state.forced && state.detected_at.is_some());
+
+        // 关闭自动检测后不再视为离线
+        manager.set_config(OfflineConfig { auto_detect: false, ..Default::default() });
+        assert!(!manager.is_offline());
+
+        manager.set_config(OfflineConfig::default());
+        manager.record_online();
+        assert!(!manager.is_offline());
+    }
+
+    #[test]
+    fn test_probe_uses_another_host() {
+        assert_eq!(probe_url("wallhaven.cc"), PROBE_URL);
+        assert_eq!(probe_url("www.bing.com"), SECONDARY_PROBE_URL);
+    }
+}
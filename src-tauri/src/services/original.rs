@@ -0,0 +1,323 @@
+use crate::services::{apply, store, wallpaper, watcher};
+use crate::types::WallpaperError;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+const ORIGINAL_FILE: &str = "original_wallpaper.json";
+const ORIGINAL_CONFIG_FILE: &str = "original_config.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OriginalConfig {
+    /// 退出应用时恢复原始壁纸
+    pub restore_on_quit: bool,
+}
+
+/**
+ * 应用第一次修改前的系统壁纸
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OriginalWallpaper {
+    pub path: String,
+    /// 各显示器的壁纸（macOS 各桌面、KDE 各屏幕），按系统顺序排列
+    #[serde(default)]
+    pub monitors: Vec<String>,
+    /// GNOME 深色主题下的壁纸
+    #[serde(default)]
+    pub dark_path: Option<String>,
+    pub captured_at: DateTime<Local>,
+}
+
+pub struct OriginalManager {
+    config: RwLock<OriginalConfig>,
+    captured: RwLock<Option<OriginalWallpaper>>,
+    path: RwLock<Option<PathBuf>>,
+    config_path: RwLock<Option<PathBuf>>,
+}
+
+impl OriginalManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(OriginalConfig::default()),
+            captured: RwLock::new(None),
+            path: RwLock::new(None),
+            config_path: RwLock::new(None),
+        }
+    }
+
+    pub fn set_config(&self, config: OriginalConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.config_path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save original wallpaper config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> OriginalConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 加载上次运行时保存的原始壁纸和恢复设置
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let config_path = store::data_file(app, ORIGINAL_CONFIG_FILE)?;
+        if let Some(config) = store::load_json(&config_path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.config_path.write().unwrap() = Some(config_path);
+
+        let path = store::data_file(app, ORIGINAL_FILE)?;
+        *self.captured.write().unwrap() = store::load_json(&path);
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn get(&self) -> Option<OriginalWallpaper> {
+        self.captured.read().unwrap().clone()
+    }
+
+    /**
+     * 尚未保存原始壁纸时读取当前系统壁纸
+     *
+     * 当前壁纸已经位于应用缓存目录时说明是应用自己设置的，不作为原始壁纸
+     */
+    pub fn ensure_captured(&self, cache_dir: &Path) {
+        if self.captured.read().unwrap().is_some() {
+            return;
+        }
+
+        let original = match capture() {
+            Ok(original) => original,
+            Err(e) => {
+                log::warn!("Failed to capture original wallpaper: {}", e);
+                return;
+            }
+        };
+        if Path::new(&original.path).starts_with(cache_dir) {
+            return;
+        }
+
+        log::info!("Captured original wallpaper: {}", original.path);
+        *self.captured.write().unwrap() = Some(original);
+        self.save();
+    }
+
+    /**
+     * 恢复原始壁纸，之后应用再次修改壁纸时会重新保存
+     */
+    pub fn restore(&self) -> Result<Option<OriginalWallpaper>, WallpaperError> {
+        let Some(original) = self.get() else {
+            return Ok(None);
+        };
+
+        if !Path::new(&original.path).exists() {
+            return Err(WallpaperError::WallpaperError(format!(
+                "Original wallpaper no longer exists: {}",
+                original.path
+            )));
+        }
+
+        restore_original(&original)?;
+        apply::clear_current();
+        watcher::get_watcher().acknowledge(&original.path);
+
+        *self.captured.write().unwrap() = None;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            let _ = std::fs::remove_file(path);
+        }
+
+        Ok(Some(original))
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.read().unwrap().clone() else {
+            return;
+        };
+        if let Some(original) = self.captured.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(&path, original) {
+                log::warn!("Failed to save original wallpaper: {}", e);
+            }
+        }
+    }
+}
+
+impl Default for OriginalManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static ORIGINAL: OnceLock<OriginalManager> = OnceLock::new();
+
+pub fn get_original() -> &'static OriginalManager {
+    ORIGINAL.get_or_init(OriginalManager::new)
+}
+
+/**
+ * 退出时按配置恢复原始壁纸
+ */
+pub fn restore_on_exit() {
+    if !get_original().get_config().restore_on_quit {
+        return;
+    }
+    if let Err(e) = get_original().restore() {
+        log::warn!("Failed to restore original wallpaper on exit: {}", e);
+    }
+}
+
+const KDE_READ_SCRIPT: &str = r#"
+const images = [];
+for (const desktop of desktops()) {
+    desktop.currentConfigGroup = ["Wallpaper", "org.kde.image", "General"];
+    images.push(desktop.readConfig("Image"));
+}
+print(images.join("\n"));"#;
+
+fn capture() -> Result<OriginalWallpaper, WallpaperError> {
+    let path = watcher::normalize_path(&wallpaper::get_current_wallpaper()?);
+    let mut original = OriginalWallpaper {
+        path,
+        monitors: Vec::new(),
+        dark_path: None,
+        captured_at: Local::now(),
+    };
+
+    // 以下变体读取失败时只保存主壁纸
+    if cfg!(target_os = "macos") {
+        if let Ok(output) = wallpaper::command_output(
+            "osascript",
+            &["-e", r#"tell application "System Events" to get picture of every desktop"#],
+        ) {
+            original.monitors = parse_macos_pictures(&output);
+        }
+    } else if cfg!(target_os = "linux") {
+        let desktop = wallpaper::current_desktop();
+        if wallpaper::is_gnome(&desktop) {
+            original.dark_path = wallpaper::command_output(
+                "gsettings",
+                &["get", "org.gnome.desktop.background", "picture-uri-dark"],
+            )
+            .ok()
+            .map(|uri| watcher::normalize_path(&uri))
+            .filter(|path| !path.is_empty());
+        } else if wallpaper::is_kde(&desktop) {
+            if let Ok(output) = wallpaper::kde_eval(KDE_READ_SCRIPT) {
+                original.monitors = output
+                    .lines()
+                    .map(watcher::normalize_path)
+                    .filter(|path| !path.is_empty())
+                    .collect();
+            }
+        }
+    }
+
+    Ok(original)
+}
+
+fn restore_original(original: &OriginalWallpaper) -> Result<(), WallpaperError> {
+    if !original.monitors.is_empty() {
+        if cfg!(target_os = "macos") {
+            let script = macos_restore_script(&original.monitors);
+            return wallpaper::command_output("osascript", &["-e", &script]).map(|_| ());
+        }
+        if cfg!(target_os = "linux") && wallpaper::is_kde(&wallpaper::current_desktop()) {
+            return wallpaper::kde_eval(&kde_restore_script(&original.monitors)).map(|_| ());
+        }
+    }
+
+    wallpaper::set_wallpaper(Path::new(&original.path))?;
+
+    if let Some(dark_path) = &original.dark_path {
+        wallpaper::gnome_set_background("picture-uri-dark", Path::new(dark_path))?;
+    }
+
+    Ok(())
+}
+
+/**
+ * 解析 AppleScript 返回的桌面图片列表（以 ", " 分隔）
+ */
+fn parse_macos_pictures(output: &str) -> Vec<String> {
+    output
+        .split(", ")
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+fn applescript_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/**
+ * 按顺序恢复每个桌面的图片，桌面数量变多时其余桌面使用最后一张
+ */
+fn macos_restore_script(pictures: &[String]) -> String {
+    let list = pictures
+        .iter()
+        .map(|path| applescript_string(path))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        r#"tell application "System Events"
+    set pics to {{{}}}
+    set allDesktops to every desktop
+    repeat with i from 1 to count of allDesktops
+        if i > (count of pics) then
+            set picture of item i of allDesktops to last item of pics
+        else
+            set picture of item i of allDesktops to item i of pics
+        end if
+    end repeat
+end tell"#,
+        list
+    )
+}
+
+fn kde_restore_script(pictures: &[String]) -> String {
+    let uris = pictures
+        .iter()
+        .map(|path| format!("file://{}", path))
+        .collect::<Vec<_>>();
+    format!(
+        r#"
+const images = {};
+desktops().forEach((desktop, i) => {{
+    desktop.currentConfigGroup = ["Wallpaper", "org.kde.image", "General"];
+    desktop.writeConfig("Image", images[Math.min(i, images.length - 1)]);
+}});"#,
+        serde_json::to_string(&uris).unwrap_or_else(|_| "[]".to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_macos_pictures() {
+        let output = "/Users/me/Pictures/a.jpg, /System/Library/Desktop Pictures/Sonoma.heic";
+        assert_eq!(
+            parse_macos_pictures(output),
+            vec!["/Users/me/Pictures/a.jpg", "/System/Library/Desktop Pictures/Sonoma.heic"]
+        );
+        assert!(parse_macos_pictures("").is_empty());
+    }
+
+    #[test]
+    fn test_restore_scripts_escape_paths() {
+        let pictures = vec!["/tmp/a \"b\".jpg".to_string(), "/tmp/c.jpg".to_string()];
+
+        let script = macos_restore_script(&pictures);
+        assert!(script.contains(r#"set pics to {"/tmp/a \"b\".jpg", "/tmp/c.jpg"}"#));
+
+        let script = kde_restore_script(&pictures);
+        assert!(script.contains(r#"const images = ["file:///tmp/a \"b\".jpg","file:///tmp/c.jpg"];"#));
+    }
+}
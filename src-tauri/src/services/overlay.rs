@@ -0,0 +1,554 @@
+use crate::services::{agenda, apply, store};
+use crate::types::{WallpaperError, WallpaperInfo};
+use ab_glyph::{point, Font, FontVec, Glyph, PxScale, ScaleFont};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Local};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tauri::AppHandle;
+
+const OVERLAY_FILE: &str = "overlay.json";
+
+// 字号和边距按 1080p 高度定义，渲染时按图片实际高度等比缩放
+const REFERENCE_HEIGHT: f32 = 1080.0;
+
+const FONT_CANDIDATES: &[&str] = &[
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+    /// 支持 {title} {source} {date} {time} {weekday} {quote} 占位符
+    pub template: String,
+    pub date_format: String,
+    pub quotes_path: Option<PathBuf>,
+    pub font_path: Option<PathBuf>,
+    pub font_size: f32,
+    pub position: OverlayPosition,
+    pub margin: f32,
+    pub color: String,
+    pub shadow: bool,
+    /// 文字背景框颜色，支持 #RRGGBBAA 半透明
+    pub backdrop: Option<String>,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            template: "{title}\n{date}".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            quotes_path: None,
+            font_path: None,
+            font_size: 28.0,
+            position: OverlayPosition::BottomRight,
+            margin: 48.0,
+            color: "#FFFFFF".to_string(),
+            shadow: true,
+            backdrop: None,
+        }
+    }
+}
+
+impl OverlayConfig {
+    /**
+     * 检查日期格式，chrono 遇到无效的 strftime 格式会在格式化时 panic
+     */
+    pub fn validate(&self) -> Result<(), WallpaperError> {
+        if StrftimeItems::new(&self.date_format).any(|item| matches!(item, Item::Error)) {
+            return Err(WallpaperError::WallpaperError(format!(
+                "Invalid date format: {}",
+                self.date_format
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub struct OverlayManager {
+    config: RwLock<OverlayConfig>,
+    last_signature: RwLock<Option<u64>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl OverlayManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(OverlayConfig::default()),
+            last_signature: RwLock::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载叠加层配置，启动后的第一次切换即可带上叠加层
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, OVERLAY_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: OverlayConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save overlay config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> OverlayConfig {
+        self.config.read().unwrap().clone()
+    }
+}
+
+impl Default for OverlayManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static OVERLAY: OnceLock<OverlayManager> = OnceLock::new();
+
+pub fn get_overlay() -> &'static OverlayManager {
+    OVERLAY.get_or_init(OverlayManager::new)
+}
+
+/**
+ * 一个待绘制的文字块及其样式
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayLayer {
+    pub text: String,
+    pub style: OverlayConfig,
+}
+
+/**
+ * 收集当前需要绘制的叠加层：文字模板和日程面板
+ */
+pub fn collect_layers(info: &WallpaperInfo, now: &DateTime<Local>) -> Vec<OverlayLayer> {
+    let mut layers = Vec::new();
+
+    let config = get_overlay().get_config();
+    if config.enabled {
+        let text = expand_template(&config, info, now);
+        if !text.is_empty() {
+            layers.push(OverlayLayer { text, style: config });
+        }
+    }
+
+    if let Some(layer) = agenda::get_agenda().layer(now) {
+        layers.push(layer);
+    }
+
+    layers
+}
+
+fn layers_signature(original: &Path, layers: &[OverlayLayer]) -> Result<Option<u64>, WallpaperError> {
+    if layers.is_empty() {
+        return Ok(None);
+    }
+    let mut hasher = DefaultHasher::new();
+    original.hash(&mut hasher);
+    serde_json::to_string(layers)?.hash(&mut hasher);
+    Ok(Some(hasher.finish()))
+}
+
+/**
+ * 叠加层内容与上次渲染不同时需要重新渲染
+ */
+pub fn needs_refresh(info: &WallpaperInfo, now: &DateTime<Local>) -> bool {
+    let Some(original) = info.local_path.as_deref() else {
+        return false;
+    };
+    let layers = collect_layers(info, now);
+    match layers_signature(original, &layers) {
+        Ok(signature) => *get_overlay().last_signature.read().unwrap() != signature,
+        Err(_) => false,
+    }
+}
+
+/**
+ * 展开模板中的占位符
+ */
+pub fn expand_template(config: &OverlayConfig, info: &WallpaperInfo, now: &DateTime<Local>) -> String {
+    // 用 write! 格式化，无效格式返回错误而不是 panic
+    let mut date = String::new();
+    if write!(date, "{}", now.format(&config.date_format)).is_err() {
+        log::warn!("Invalid overlay date format: {}", config.date_format);
+        date.clear();
+    }
+
+    let mut text = config
+        .template
+        .replace("{title}", &info.title)
+        .replace("{source}", info.source.as_str())
+        .replace("{date}", &date)
+        .replace("{time}", &now.format("%H:%M").to_string())
+        .replace("{weekday}", &now.format("%A").to_string());
+
+    if text.contains("{quote}") {
+        let quote = config
+            .quotes_path
+            .as_deref()
+            .and_then(|path| quote_of_the_day(path, now))
+            .unwrap_or_default();
+        text = text.replace("{quote}", &quote);
+    }
+
+    text.trim().to_string()
+}
+
+/**
+ * 从语录文件中按日期选取一条，同一天内保持不变
+ *
+ * 文件每行一条语录，空行忽略
+ */
+fn quote_of_the_day(path: &Path, now: &DateTime<Local>) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let quotes: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if quotes.is_empty() {
+        return None;
+    }
+    let index = now.num_days_from_ce() as usize % quotes.len();
+    Some(quotes[index].to_string())
+}
+
+/**
+ * 准备交给系统的壁纸文件
+ *
+ * 没有需要绘制的叠加层时直接返回缓存原图，否则渲染到 cache/rendered 下的新文件。
+ * 每次渲染使用不同文件名，避免系统因路径相同而不刷新壁纸
+ */
+pub fn prepare(cache_dir: &Path, info: &WallpaperInfo) -> Result<PathBuf, WallpaperError> {
+    let original = info
+        .local_path
+        .clone()
+        .ok_or_else(|| WallpaperError::ApiError("Wallpaper is not cached".to_string()))?;
+
+    let layers = collect_layers(info, &Local::now());
+    let manager = get_overlay();
+    let Some(signature) = layers_signature(&original, &layers)? else {
+        *manager.last_signature.write().unwrap() = None;
+        return Ok(original);
+    };
+
+    let rendered_dir = cache_dir.join("rendered");
+    fs::create_dir_all(&rendered_dir)?;
+    let output = rendered_dir.join(format!("{}-{:016x}.jpg", info.id, signature));
+
+    if !output.exists() {
+        let mut img = image::open(&original)
+            .map_err(|e| WallpaperError::ApiError(format!("Failed to decode image: {}", e)))?
+            .to_rgba8();
+        for layer in &layers {
+            let font = load_font(layer.style.font_path.as_deref())?;
+            draw_text_block(&mut img, &font, &layer.text, &layer.style)?;
+        }
+        save_jpeg(img, &output)?;
+    }
+
+    remove_stale_renders(&rendered_dir, &output);
+    *manager.last_signature.write().unwrap() = Some(signature);
+
+    Ok(output)
+}
+
+pub(crate) fn save_jpeg(img: RgbaImage, output: &Path) -> Result<(), WallpaperError> {
+    image::DynamicImage::ImageRgba8(img)
+        .to_rgb8()
+        .save(output)
+        .map_err(|e| WallpaperError::ApiError(format!("Failed to encode image: {}", e)))
+}
+
+fn remove_stale_renders(dir: &Path, keep: &Path) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path != keep {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+pub(crate) fn load_font(font_path: Option<&Path>) -> Result<FontVec, WallpaperError> {
+    let candidates: Vec<PathBuf> = match font_path {
+        Some(path) => vec![path.to_path_buf()],
+        None => FONT_CANDIDATES.iter().map(PathBuf::from).collect(),
+    };
+
+    for path in candidates {
+        if let Ok(bytes) = fs::read(&path) {
+            if let Ok(font) = FontVec::try_from_vec_and_index(bytes, 0) {
+                return Ok(font);
+            }
+        }
+    }
+
+    Err(WallpaperError::ApiError(
+        "No usable font found, please set fontPath".to_string(),
+    ))
+}
+
+/**
+ * 解析 #RRGGBB 或 #RRGGBBAA 格式的颜色
+ */
+pub(crate) fn parse_color(value: &str) -> Option<Rgba<u8>> {
+    let hex = value.trim().trim_start_matches('#');
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        6 => Some(Rgba([channel(0)?, channel(2)?, channel(4)?, 255])),
+        8 => Some(Rgba([channel(0)?, channel(2)?, channel(4)?, channel(6)?])),
+        _ => None,
+    }
+}
+
+/**
+ * 计算文字块左上角坐标
+ */
+fn block_origin(
+    image_size: (u32, u32),
+    block_size: (f32, f32),
+    position: OverlayPosition,
+    margin: f32,
+) -> (f32, f32) {
+    let (width, height) = (image_size.0 as f32, image_size.1 as f32);
+    let (block_w, block_h) = block_size;
+    match position {
+        OverlayPosition::TopLeft => (margin, margin),
+        OverlayPosition::TopRight => (width - block_w - margin, margin),
+        OverlayPosition::BottomLeft => (margin, height - block_h - margin),
+        OverlayPosition::BottomRight => (width - block_w - margin, height - block_h - margin),
+        OverlayPosition::Center => ((width - block_w) / 2.0, (height - block_h) / 2.0),
+    }
+}
+
+fn blend_pixel(img: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, coverage: f32) {
+    if x < 0 || y < 0 || x >= img.width() as i32 || y >= img.height() as i32 {
+        return;
+    }
+    let alpha = coverage.clamp(0.0, 1.0) * color[3] as f32 / 255.0;
+    let pixel = img.get_pixel_mut(x as u32, y as u32);
+    for i in 0..3 {
+        pixel[i] = (pixel[i] as f32 * (1.0 - alpha) + color[i] as f32 * alpha).round() as u8;
+    }
+}
+
+pub(crate) fn fill_rect(img: &mut RgbaImage, origin: (f32, f32), size: (f32, f32), color: Rgba<u8>) {
+    let (x0, y0) = (origin.0.max(0.0) as i32, origin.1.max(0.0) as i32);
+    let (x1, y1) = ((origin.0 + size.0) as i32, (origin.1 + size.1) as i32);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            blend_pixel(img, x, y, color, 1.0);
+        }
+    }
+}
+
+fn layout_line(font: &FontVec, scale: PxScale, line: &str) -> (Vec<Glyph>, f32) {
+    let scaled = font.as_scaled(scale);
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+
+    for ch in line.chars() {
+        let id = scaled.glyph_id(ch);
+        if let Some(prev) = previous {
+            caret += scaled.kern(prev, id);
+        }
+        glyphs.push(id.with_scale_and_position(scale, point(caret, 0.0)));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+
+    (glyphs, caret)
+}
+
+fn draw_glyphs(img: &mut RgbaImage, font: &FontVec, glyphs: &[Glyph], origin: (f32, f32), color: Rgba<u8>) {
+    for glyph in glyphs {
+        let mut glyph = glyph.clone();
+        glyph.position = point(glyph.position.x + origin.0, glyph.position.y + origin.1);
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|x, y, coverage| {
+                blend_pixel(
+                    img,
+                    bounds.min.x as i32 + x as i32,
+                    bounds.min.y as i32 + y as i32,
+                    color,
+                    coverage,
+                );
+            });
+        }
+    }
+}
+
+/**
+ * 绘制多行文字块，可选背景框和阴影
+ */
+pub(crate) fn draw_text_block(
+    img: &mut RgbaImage,
+    font: &FontVec,
+    text: &str,
+    config: &OverlayConfig,
+) -> Result<(), WallpaperError> {
+    let color = parse_color(&config.color)
+        .ok_or_else(|| WallpaperError::ApiError(format!("Invalid color: {}", config.color)))?;
+    let backdrop = match &config.backdrop {
+        Some(value) => Some(
+            parse_color(value)
+                .ok_or_else(|| WallpaperError::ApiError(format!("Invalid color: {}", value)))?,
+        ),
+        None => None,
+    };
+
+    let ratio = img.height() as f32 / REFERENCE_HEIGHT;
+    let scale = PxScale::from(config.font_size * ratio);
+    let margin = config.margin * ratio;
+    let scaled = font.as_scaled(scale);
+    let line_height = scaled.height() + scaled.line_gap();
+    let padding = if backdrop.is_some() { scale.y * 0.5 } else { 0.0 };
+
+    let lines: Vec<(Vec<Glyph>, f32)> = text.lines().map(|line| layout_line(font, scale, line)).collect();
+    let text_w = lines.iter().map(|(_, w)| *w).fold(0.0, f32::max);
+    let text_h = line_height * lines.len() as f32;
+    let block = (text_w + padding * 2.0, text_h + padding * 2.0);
+    let origin = block_origin(img.dimensions(), block, config.position, margin);
+
+    if let Some(backdrop) = backdrop {
+        fill_rect(img, origin, block, backdrop);
+    }
+
+    let shadow_offset = (scale.y / 16.0).max(1.0);
+    for (index, (glyphs, width)) in lines.iter().enumerate() {
+        // 右对齐位置让每行贴近右边缘，其余位置左对齐或居中
+        let x = match config.position {
+            OverlayPosition::TopRight | OverlayPosition::BottomRight => origin.0 + padding + text_w - width,
+            OverlayPosition::Center => origin.0 + padding + (text_w - width) / 2.0,
+            _ => origin.0 + padding,
+        };
+        let baseline = origin.1 + padding + scaled.ascent() + line_height * index as f32;
+
+        if config.shadow {
+            let shadow = Rgba([0, 0, 0, 160]);
+            draw_glyphs(img, font, glyphs, (x + shadow_offset, baseline + shadow_offset), shadow);
+        }
+        draw_glyphs(img, font, glyphs, (x, baseline), color);
+    }
+
+    Ok(())
+}
+
+/**
+ * 后台定时检查叠加层内容（日期、日程文件等），变化时基于原图重新渲染
+ */
+pub fn spawn_refresh_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(30));
+        loop {
+            ticker.tick().await;
+            let Some(info) = apply::current_wallpaper() else {
+                continue;
+            };
+            if !needs_refresh(&info, &Local::now()) {
+                continue;
+            }
+            if let Err(e) = apply::reapply_current(&app).await {
+                log::warn!("Failed to refresh overlay: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::WallpaperSource;
+    use chrono::TimeZone;
+
+    fn sample_info() -> WallpaperInfo {
+        WallpaperInfo {
+            id: "abc".to_string(),
+            title: "Photo by Jane".to_string(),
+            url: "http://example.com".to_string(),
+            source: WallpaperSource::Unsplash,
+            local_path: None,
+            cached: false,
+            author: None,
+            attribution: None,
+        }
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let config = OverlayConfig {
+            template: "{title} · {source}\n{date} {time}".to_string(),
+            ..Default::default()
+        };
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 9, 5, 0).unwrap();
+        let text = expand_template(&config, &sample_info(), &now);
+        assert_eq!(text, "Photo by Jane · unsplash\n2026-03-01 09:05");
+
+        let invalid = OverlayConfig {
+            template: "{date}".to_string(),
+            date_format: "%Q".to_string(),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+        assert!(OverlayConfig::default().validate().is_ok());
+        assert_eq!(expand_template(&invalid, &sample_info(), &now), "");
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#FF8000"), Some(Rgba([255, 128, 0, 255])));
+        assert_eq!(parse_color("00000080"), Some(Rgba([0, 0, 0, 128])));
+        assert_eq!(parse_color("#FFF"), None);
+    }
+
+    #[test]
+    fn test_block_origin() {
+        let origin = block_origin((1920, 1080), (200.0, 100.0), OverlayPosition::BottomRight, 20.0);
+        assert_eq!(origin, (1700.0, 960.0));
+        let origin = block_origin((1920, 1080), (200.0, 100.0), OverlayPosition::Center, 20.0);
+        assert_eq!(origin, (860.0, 490.0));
+    }
+}
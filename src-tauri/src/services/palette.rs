@@ -0,0 +1,444 @@
+use crate::services::{apply, store};
+use crate::types::WallpaperError;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+const PALETTE_FILE: &str = "palette.json";
+const PALETTE_SIZE: usize = 8;
+const KMEANS_ITERATIONS: usize = 12;
+// 终端配色中 color0-7 的名称，用于 alacritty
+const ANSI_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PaletteConfig {
+    pub enabled: bool,
+    /// 主题文件输出目录，为空时使用应用数据目录下的 theme
+    pub output_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteColor {
+    pub hex: String,
+    /// 该颜色在图片中的占比（0-1）
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Palette {
+    pub wallpaper: PathBuf,
+    pub dominant: Vec<PaletteColor>,
+    pub background: String,
+    pub foreground: String,
+    pub cursor: String,
+    /// color0 - color15
+    pub colors: Vec<String>,
+}
+
+pub struct PaletteManager {
+    config: RwLock<PaletteConfig>,
+    current: RwLock<Option<Palette>>,
+    /// 检查是否过期和写出主题文件需要一起完成，避免旧结果覆盖新结果
+    writing: Mutex<()>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl PaletteManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(PaletteConfig::default()),
+            current: RwLock::new(None),
+            writing: Mutex::new(()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载配色配置，重启后继续导出主题文件
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, PALETTE_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: PaletteConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save palette config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> PaletteConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn current(&self) -> Option<Palette> {
+        self.current.read().unwrap().clone()
+    }
+}
+
+impl Default for PaletteManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static PALETTE: OnceLock<PaletteManager> = OnceLock::new();
+
+pub fn get_palette() -> &'static PaletteManager {
+    PALETTE.get_or_init(PaletteManager::new)
+}
+
+type Rgb = [f32; 3];
+
+fn luminance(c: &Rgb) -> f32 {
+    0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2]
+}
+
+fn hue(c: &Rgb) -> f32 {
+    let (r, g, b) = (c[0], c[1], c[2]);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    h * 60.0
+}
+
+fn distance(a: &Rgb, b: &Rgb) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+fn mix(c: &Rgb, target: f32, amount: f32) -> Rgb {
+    [
+        c[0] + (target - c[0]) * amount,
+        c[1] + (target - c[1]) * amount,
+        c[2] + (target - c[2]) * amount,
+    ]
+}
+
+fn to_hex(c: &Rgb) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        c[0].round().clamp(0.0, 255.0) as u8,
+        c[1].round().clamp(0.0, 255.0) as u8,
+        c[2].round().clamp(0.0, 255.0) as u8
+    )
+}
+
+fn from_hex(hex: &str) -> Rgb {
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0) as f32;
+    [channel(1), channel(3), channel(5)]
+}
+
+/**
+ * k-means 聚类，按亮度分位数初始化中心点，结果可复现
+ *
+ * 返回按占比从高到低排列的颜色
+ */
+fn kmeans(pixels: &[Rgb], k: usize, iterations: usize) -> Vec<PaletteColor> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = pixels.to_vec();
+    sorted.sort_by(|a, b| luminance(a).total_cmp(&luminance(b)));
+    let mut centroids: Vec<Rgb> = (0..k)
+        .map(|i| sorted[(2 * i + 1) * sorted.len() / (2 * k)])
+        .collect();
+    let mut counts = vec![0usize; k];
+
+    for _ in 0..iterations {
+        let mut sums = vec![[0.0f32; 3]; k];
+        counts = vec![0; k];
+        for pixel in pixels {
+            let nearest = (0..k)
+                .min_by(|&a, &b| distance(pixel, &centroids[a]).total_cmp(&distance(pixel, &centroids[b])))
+                .unwrap();
+            for c in 0..3 {
+                sums[nearest][c] += pixel[c];
+            }
+            counts[nearest] += 1;
+        }
+        for i in 0..k {
+            if counts[i] > 0 {
+                let n = counts[i] as f32;
+                centroids[i] = [sums[i][0] / n, sums[i][1] / n, sums[i][2] / n];
+            }
+        }
+    }
+
+    let mut colors: Vec<(Rgb, usize)> = centroids.into_iter().zip(counts).collect();
+    colors.sort_by_key(|c| std::cmp::Reverse(c.1));
+    colors
+        .into_iter()
+        .map(|(c, count)| PaletteColor {
+            hex: to_hex(&c),
+            weight: count as f32 / pixels.len() as f32,
+        })
+        .collect()
+}
+
+/**
+ * 对缩小后的图片做聚类，提取主色
+ */
+pub fn extract_dominant(path: &Path) -> Result<Vec<PaletteColor>, WallpaperError> {
+    let img = image::open(path)
+        .map_err(|e| WallpaperError::ApiError(format!("Failed to decode image: {}", e)))?
+        .thumbnail(128, 128)
+        .to_rgb8();
+    let pixels: Vec<Rgb> = img
+        .pixels()
+        .map(|p| [p[0] as f32, p[1] as f32, p[2] as f32])
+        .collect();
+    Ok(kmeans(&pixels, PALETTE_SIZE, KMEANS_ITERATIONS))
+}
+
+/**
+ * 由主色生成 pywal 风格的 16 色方案
+ *
+ * 最暗的颜色作为背景，最亮的作为前景，其余按色相填充 color1-6，
+ * color8-15 为对应颜色的提亮版本
+ */
+pub fn build_palette(wallpaper: &Path, dominant: Vec<PaletteColor>) -> Palette {
+    let mut by_luma: Vec<Rgb> = dominant.iter().map(|c| from_hex(&c.hex)).collect();
+    by_luma.sort_by(|a, b| luminance(a).total_cmp(&luminance(b)));
+    while by_luma.len() < PALETTE_SIZE {
+        let last = by_luma.last().copied().unwrap_or([128.0; 3]);
+        by_luma.push(mix(&last, 255.0, 0.2));
+    }
+
+    let background = mix(&by_luma[0], 0.0, 0.4);
+    let foreground = mix(&by_luma[PALETTE_SIZE - 1], 255.0, 0.6);
+    let mut accents: Vec<Rgb> = by_luma[1..PALETTE_SIZE - 1].to_vec();
+    accents.sort_by(|a, b| hue(a).total_cmp(&hue(b)));
+
+    let mut normal = vec![background];
+    normal.extend(accents.iter().map(|c| mix(c, 255.0, 0.1)));
+    normal.push(foreground);
+    let bright: Vec<Rgb> = normal.iter().map(|c| mix(c, 255.0, 0.25)).collect();
+
+    Palette {
+        wallpaper: wallpaper.to_path_buf(),
+        dominant,
+        background: to_hex(&background),
+        foreground: to_hex(&foreground),
+        cursor: to_hex(&foreground),
+        colors: normal.iter().chain(bright.iter()).map(to_hex).collect(),
+    }
+}
+
+fn render_colors_json(palette: &Palette) -> Result<String, WallpaperError> {
+    let colors: serde_json::Map<String, serde_json::Value> = palette
+        .colors
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (format!("color{}", i), json!(c)))
+        .collect();
+    let value = json!({
+        "wallpaper": palette.wallpaper,
+        "alpha": "100",
+        "special": {
+            "background": palette.background,
+            "foreground": palette.foreground,
+            "cursor": palette.cursor,
+        },
+        "colors": colors,
+    });
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+fn render_xresources(palette: &Palette) -> String {
+    let mut out = format!(
+        "*.background: {}\n*.foreground: {}\n*.cursorColor: {}\n",
+        palette.background, palette.foreground, palette.cursor
+    );
+    for (i, c) in palette.colors.iter().enumerate() {
+        out.push_str(&format!("*.color{}: {}\n", i, c));
+    }
+    out
+}
+
+fn render_css(palette: &Palette) -> String {
+    let mut out = format!(
+        ":root {{\n  --wallpaper: url(\"{}\");\n  --background: {};\n  --foreground: {};\n  --cursor: {};\n",
+        palette.wallpaper.display(),
+        palette.background,
+        palette.foreground,
+        palette.cursor
+    );
+    for (i, c) in palette.colors.iter().enumerate() {
+        out.push_str(&format!("  --color{}: {};\n", i, c));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_kitty(palette: &Palette) -> String {
+    let mut out = format!(
+        "background {}\nforeground {}\ncursor {}\n",
+        palette.background, palette.foreground, palette.cursor
+    );
+    for (i, c) in palette.colors.iter().enumerate() {
+        out.push_str(&format!("color{} {}\n", i, c));
+    }
+    out
+}
+
+fn render_alacritty(palette: &Palette) -> String {
+    let mut out = format!(
+        "[colors.primary]\nbackground = \"{}\"\nforeground = \"{}\"\n\n[colors.cursor]\ncursor = \"{}\"\n",
+        palette.background, palette.foreground, palette.cursor
+    );
+    for (section, colors) in [("normal", &palette.colors[..8]), ("bright", &palette.colors[8..])] {
+        out.push_str(&format!("\n[colors.{}]\n", section));
+        for (name, c) in ANSI_NAMES.iter().zip(colors) {
+            out.push_str(&format!("{} = \"{}\"\n", name, c));
+        }
+    }
+    out
+}
+
+/**
+ * 写出 pywal 兼容的主题文件
+ */
+pub fn write_theme_files(dir: &Path, palette: &Palette) -> Result<(), WallpaperError> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("colors.json"), render_colors_json(palette)?)?;
+    fs::write(dir.join("colors.Xresources"), render_xresources(palette))?;
+    fs::write(dir.join("colors.css"), render_css(palette))?;
+    fs::write(dir.join("colors-kitty.conf"), render_kitty(palette))?;
+    fs::write(dir.join("colors-alacritty.toml"), render_alacritty(palette))?;
+    Ok(())
+}
+
+fn output_dir(app: &AppHandle, config: &PaletteConfig) -> Result<PathBuf, WallpaperError> {
+    match &config.output_dir {
+        Some(dir) => Ok(dir.clone()),
+        None => Ok(app
+            .path()
+            .app_data_dir()
+            .map_err(|e| WallpaperError::ApiError(e.to_string()))?
+            .join("theme")),
+    }
+}
+
+/**
+ * 是否仍是当前壁纸，多次切换时较早的提取任务可能最后才完成
+ */
+fn is_current(wallpaper: &Path) -> bool {
+    apply::current_wallpaper()
+        .and_then(|info| info.local_path)
+        .is_some_and(|path| path == wallpaper)
+}
+
+/**
+ * 提取配色、写出主题文件并发送 palette-updated 事件
+ *
+ * 完成时壁纸已经不是当前壁纸的结果直接丢弃
+ */
+pub async fn update(app: &AppHandle, wallpaper: PathBuf) {
+    let config = get_palette().get_config();
+    if !config.enabled {
+        return;
+    }
+
+    let result = async {
+        let dir = output_dir(app, &config)?;
+        let palette = tauri::async_runtime::spawn_blocking(move || {
+            let dominant = extract_dominant(&wallpaper)?;
+            let palette = build_palette(&wallpaper, dominant);
+
+            let manager = get_palette();
+            let _writing = manager.writing.lock().unwrap();
+            if !is_current(&wallpaper) {
+                log::info!("Discarding palette of {}, wallpaper changed", wallpaper.display());
+                return Ok(None);
+            }
+            write_theme_files(&dir, &palette)?;
+            *manager.current.write().unwrap() = Some(palette.clone());
+            Ok::<_, WallpaperError>(Some(palette))
+        })
+        .await
+        .map_err(|e| WallpaperError::ApiError(e.to_string()))??;
+
+        match palette {
+            Some(palette) => app
+                .emit("palette-updated", &palette)
+                .map_err(|e| WallpaperError::ApiError(e.to_string())),
+            None => Ok(()),
+        }
+    }
+    .await;
+
+    if let Err(e) = result {
+        log::warn!("Failed to update palette: {}", e);
+    }
+}
+
+pub fn spawn_update(app: AppHandle, wallpaper: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        update(&app, wallpaper).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmeans_finds_dominant_colors() {
+        let mut pixels = vec![[200.0, 20.0, 20.0]; 75];
+        pixels.extend(vec![[10.0, 10.0, 80.0]; 25]);
+        let colors = kmeans(&pixels, 2, 5);
+        assert_eq!(colors[0].hex, "#c81414");
+        assert_eq!(colors[0].weight, 0.75);
+        assert_eq!(colors[1].hex, "#0a0a50");
+    }
+
+    #[test]
+    fn test_build_palette_has_sixteen_colors() {
+        let dominant = vec![
+            PaletteColor { hex: "#102030".to_string(), weight: 0.6 },
+            PaletteColor { hex: "#f0e0d0".to_string(), weight: 0.4 },
+        ];
+        let palette = build_palette(Path::new("/tmp/a.jpg"), dominant);
+        assert_eq!(palette.colors.len(), 16);
+        assert_eq!(palette.colors[0], palette.background);
+        assert_eq!(palette.colors[7], palette.foreground);
+        assert!(luminance(&from_hex(&palette.foreground)) > luminance(&from_hex(&palette.background)));
+    }
+
+    #[test]
+    fn test_render_theme_formats() {
+        let palette = build_palette(
+            Path::new("/tmp/a.jpg"),
+            vec![PaletteColor { hex: "#808080".to_string(), weight: 1.0 }],
+        );
+        let json: serde_json::Value = serde_json::from_str(&render_colors_json(&palette).unwrap()).unwrap();
+        assert_eq!(json["special"]["background"], palette.background);
+        assert_eq!(json["colors"]["color15"], palette.colors[15]);
+        assert!(render_xresources(&palette).contains("*.color8: "));
+        assert!(render_alacritty(&palette).contains("[colors.bright]\nblack = "));
+        assert!(render_kitty(&palette).starts_with("background #"));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::services::prefetch::{self, PrefetchTarget};
+use crate::services::{fallback, fetcher, rotation, scheduler, store};
+use crate::sources::local;
+use crate::sources::provider::{ProviderError, SourceQuery};
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+const PLAYLIST_FILE: &str = "playlist.json";
+
+/**
+ * 播放列表条目指向的壁纸集合
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PlaylistTarget {
+    /// 来源的默认列表
+    Source { source: WallpaperSource },
+    /// 来源的搜索结果
+    Query { source: WallpaperSource, query: SourceQuery },
+    /// 导入的本地合集
+    Collection { name: String },
+    /// 本地文件夹
+    Folder { path: PathBuf },
+}
+
+impl PlaylistTarget {
+    /**
+     * 轮换时记录翻页位置用的键
+     */
+    pub fn key(&self) -> String {
+        match self {
+            PlaylistTarget::Source { source } => source.as_str().to_string(),
+            PlaylistTarget::Query { source, query } => rotation::page_key(source, query),
+            PlaylistTarget::Collection { name } => format!("collection:{}", name),
+            PlaylistTarget::Folder { path } => format!("folder:{}", path.display()),
+        }
+    }
+
+    fn source(&self) -> WallpaperSource {
+        match self {
+            PlaylistTarget::Source { source } | PlaylistTarget::Query { source, .. } => source.clone(),
+            PlaylistTarget::Collection { .. } | PlaylistTarget::Folder { .. } => WallpaperSource::Local,
+        }
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistEntry {
+    pub target: PlaylistTarget,
+    /// 相对权重，为 0 时不参与抽取
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlaylistConfig {
+    /// 启用后定时切换从播放列表中抽取，单个来源的定时切换不再生效
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+impl Default for PlaylistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: 3600,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl PlaylistConfig {
+    pub fn validate(&self) -> Result<(), WallpaperError> {
+        for entry in &self.entries {
+            if !entry.weight.is_finite() || entry.weight < 0.0 {
+                return Err(WallpaperError::ApiError(format!("Invalid weight: {}", entry.weight)));
+            }
+            if let PlaylistTarget::Query { source, query } = &entry.target {
+                query
+                    .validate(source)
+                    .map_err(|e| WallpaperError::ApiError(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.entries.iter().any(|e| e.enabled && e.weight > 0.0)
+    }
+}
+
+pub struct PlaylistManager {
+    config: RwLock<PlaylistConfig>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl PlaylistManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(PlaylistConfig::default()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载保存的播放列表，从启动时开始计时
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, PLAYLIST_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+            scheduler::get_scheduler().mark_run(scheduler::PLAYLIST_KEY, std::time::Instant::now());
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: PlaylistConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save playlist: {}", e);
+            }
+        }
+        prefetch::get_prefetcher().invalidate(&PrefetchTarget::Playlist.key());
+        // 修改配置后重新计时
+        scheduler::get_scheduler().mark_run(scheduler::PLAYLIST_KEY, std::time::Instant::now());
+    }
+
+    pub fn get_config(&self) -> PlaylistConfig {
+        self.config.read().unwrap().clone()
+    }
+}
+
+impl Default for PlaylistManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static PLAYLIST: OnceLock<PlaylistManager> = OnceLock::new();
+
+pub fn get_playlist() -> &'static PlaylistManager {
+    PLAYLIST.get_or_init(PlaylistManager::new)
+}
+
+/**
+ * 按权重抽取条目的尝试顺序（不放回的加权抽样）
+ */
+pub fn draw_order(entries: &[PlaylistEntry], rng: &mut impl Rng) -> Vec<usize> {
+    let mut keyed: Vec<(f64, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.enabled && entry.weight > 0.0)
+        .map(|(i, entry)| (rng.random::<f64>().powf(1.0 / entry.weight), i))
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    keyed.into_iter().map(|(_, i)| i).collect()
+}
+
+/**
+ * 取条目的下一张壁纸并下载
+ *
+ * 在线来源经过熔断器，来源默认列表使用定时切换配置中的搜索条件
+ */
+async fn fetch_target(app: &AppHandle, target: &PlaylistTarget) -> Result<WallpaperInfo, WallpaperError> {
+    let key = target.key();
+    let wallpapers = match target {
+        PlaylistTarget::Source { source } => return fallback::fetch_source(app, source).await,
+        PlaylistTarget::Query { source, query } => return fallback::fetch_query(app, source, query).await,
+        PlaylistTarget::Collection { name } => local::get_local_library()
+            .collection(name)
+            .ok_or_else(|| WallpaperError::WallpaperError(format!("Collection not found: {}", name)))?,
+        PlaylistTarget::Folder { path } => {
+            let dir = path.clone();
+            tauri::async_runtime::spawn_blocking(move || local::folder_wallpapers(&dir))
+                .await
+                .map_err(|e| WallpaperError::WallpaperError(e.to_string()))?
+        }
+    };
+    let selected = next_local(&key, wallpapers).await?;
+    fetcher::prepare(app, selected).await
+}
+
+async fn next_local(key: &str, wallpapers: Vec<WallpaperInfo>) -> Result<WallpaperInfo, WallpaperError> {
+    rotation::next_in(key, |_| {
+        std::future::ready(Ok::<_, ProviderError>((wallpapers.clone(), 1)))
+    })
+    .await
+}
+
+/**
+ * 从播放列表中取下一张壁纸并下载
+ *
+ * 按权重决定尝试顺序，某个条目失败（网络错误、限流或没有结果）时继续尝试其余条目
+ */
+pub async fn fetch_next(app: &AppHandle, config: &PlaylistConfig) -> Result<WallpaperInfo, WallpaperError> {
+    let order = draw_order(&config.entries, &mut rand::rng());
+    let mut errors = Vec::new();
+
+    for index in order {
+        let target = &config.entries[index].target;
+        match fetch_target(app, target).await {
+            Ok(info) => return Ok(info),
+            Err(e) => {
+                log::warn!("Playlist entry {} failed: {}", target.key(), e);
+                errors.push(format!("{}: {}", target.key(), e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        return Err(WallpaperError::ApiError("Playlist has no enabled entries".to_string()));
+    }
+    Err(WallpaperError::ApiError(format!(
+        "All playlist entries failed: {}",
+        errors.join("; ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: WallpaperSource, weight: f64) -> PlaylistEntry {
+        PlaylistEntry {
+            target: PlaylistTarget::Source { source },
+            weight,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_draw_order_follows_weights() {
+        let entries = vec![
+            entry(WallpaperSource::Bing, 9.0),
+            entry(WallpaperSource::Wallhaven, 1.0),
+            entry(WallpaperSource::Pexels, 0.0),
+            PlaylistEntry { enabled: false, ..entry(WallpaperSource::Unsplash, 5.0) },
+        ];
+
+        let mut rng = rand::rng();
+        let mut first_bing = 0;
+        for _ in 0..1000 {
+            let order = draw_order(&entries, &mut rng);
+            assert_eq!(order.len(), 2);
+            if order[0] == 0 {
+                first_bing += 1;
+            }
+        }
+        // 期望约 90%
+        assert!((820..=970).contains(&first_bing), "{}", first_bing);
+    }
+
+    #[test]
+    fn test_config_roundtrip_and_validation() {
+        let json = r#"{
+            "enabled": true,
+            "entries": [
+                {"target": {"kind": "source", "source": "bing"}},
+                {"target": {"kind": "query", "source": "wallhaven", "query": {"query": "nature", "filters": {"purity": "100"}}}, "weight": 3},
+                {"target": {"kind": "folder", "path": "/tmp/walls"}, "enabled": false}
+            ]
+        }"#;
+        let config: PlaylistConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.interval_seconds, 3600);
+        assert_eq!(config.entries[0].weight, 1.0);
+        assert_eq!(config.entries[1].weight, 3.0);
+        assert!(!config.entries[2].enabled);
+        assert!(config.validate().is_ok());
+        assert!(config.is_active());
+
+        let invalid = PlaylistConfig {
+            entries: vec![PlaylistEntry {
+                target: PlaylistTarget::Query {
+                    source: WallpaperSource::Bing,
+                    query: SourceQuery { query: Some("cats".to_string()), ..Default::default() },
+                },
+                weight: 1.0,
+                enabled: true,
+            }],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,370 @@
+use crate::services::{store, wallpaper};
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+const POWER_FILE: &str = "power.json";
+const SYSFS_POWER_SUPPLY: &str = "/sys/class/power_supply";
+// 电源和网络状态的缓存时间，避免每次检查都读取 sysfs 和调用 D-Bus
+const STATE_TTL_SECONDS: u64 = 60;
+// 较小版本缓存时在 ID 后加的后缀，避免占用原图的缓存文件
+const SMALL_SUFFIX: &str = "-small";
+// Bing 图片的分辨率后缀，每次只降一档
+const BING_STEPS: &[(&str, &str)] = &[("_UHD.jpg", "_1920x1080.jpg"), ("_1920x1080.jpg", "_1366x768.jpg")];
+
+/**
+ * 使用电池或计费网络时的下载策略
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerPolicy {
+    /// 照常下载
+    Normal,
+    /// 下载较小的版本
+    Smaller,
+    /// 只使用已缓存的壁纸
+    CachedOnly,
+    /// 跳过自动切换
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PowerConfig {
+    pub on_battery: PowerPolicy,
+    pub on_metered: PowerPolicy,
+    /// 电量低于该百分比时跳过自动切换，为 0 时不限制
+    pub low_battery_percent: u8,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            on_battery: PowerPolicy::Smaller,
+            on_metered: PowerPolicy::CachedOnly,
+            low_battery_percent: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    pub on_battery: bool,
+    pub battery_percent: Option<u8>,
+    pub metered: bool,
+}
+
+impl PowerState {
+    /**
+     * 按配置得出的策略，同时满足多个条件时取最严格的
+     */
+    pub fn policy(&self, config: &PowerConfig) -> PowerPolicy {
+        let mut policy = PowerPolicy::Normal;
+        if self.on_battery {
+            policy = policy.max(config.on_battery);
+            let low = self
+                .battery_percent
+                .is_some_and(|percent| percent < config.low_battery_percent);
+            if low {
+                policy = PowerPolicy::Skip;
+            }
+        }
+        if self.metered {
+            policy = policy.max(config.on_metered);
+        }
+        policy
+    }
+}
+
+/**
+ * 读取 NetworkManager Metered 属性的函数，返回 NMMetered 枚举值
+ */
+pub type MeteredQuery = Box<dyn Fn() -> Option<u32> + Send + Sync>;
+
+pub struct PowerMonitor {
+    config: RwLock<PowerConfig>,
+    sysfs_root: PathBuf,
+    metered_query: MeteredQuery,
+    cached: Mutex<Option<(Instant, PowerState)>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl PowerMonitor {
+    /**
+     * sysfs 根目录和 NetworkManager 查询可以替换，便于测试
+     */
+    pub fn new(sysfs_root: PathBuf, metered_query: MeteredQuery) -> Self {
+        Self {
+            config: RwLock::new(PowerConfig::default()),
+            sysfs_root,
+            metered_query,
+            cached: Mutex::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::new(PathBuf::from(SYSFS_POWER_SUPPLY), Box::new(query_network_manager))
+    }
+
+    /**
+     * 加载电源策略，定时切换在前端同步配置之前就按用户的设置执行
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, POWER_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: PowerConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save power config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> PowerConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 当前电源和网络状态，短时间内复用上次的结果
+     */
+    pub fn state(&self) -> PowerState {
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((read_at, state)) = cached.as_ref() {
+            if read_at.elapsed() < Duration::from_secs(STATE_TTL_SECONDS) {
+                return state.clone();
+            }
+        }
+
+        let (on_battery, battery_percent) = read_battery(&self.sysfs_root);
+        let state = PowerState {
+            on_battery,
+            battery_percent,
+            metered: (self.metered_query)().is_some_and(is_metered),
+        };
+        *cached = Some((Instant::now(), state.clone()));
+        state
+    }
+
+    pub fn policy(&self) -> PowerPolicy {
+        self.state().policy(&self.get_config())
+    }
+}
+
+static POWER: OnceLock<PowerMonitor> = OnceLock::new();
+
+pub fn get_power() -> &'static PowerMonitor {
+    POWER.get_or_init(PowerMonitor::system)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|value| value.trim().to_string())
+}
+
+/**
+ * 从 /sys/class/power_supply 读取是否在用电池供电及剩余电量
+ *
+ * 有外接电源在线时不算电池供电，没有电池的台式机始终返回 false
+ */
+pub fn read_battery(root: &Path) -> (bool, Option<u8>) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return (false, None);
+    };
+
+    let mut mains_online = false;
+    let mut discharging = false;
+    let mut percent = None;
+
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read_trimmed(&dir.join("type")).as_deref() {
+            Some("Mains") | Some("USB") => {
+                mains_online |= read_trimmed(&dir.join("online")).as_deref() == Some("1");
+            }
+            Some("Battery") => {
+                // 外设电池（鼠标、键盘）不影响供电状态
+                if read_trimmed(&dir.join("scope")).as_deref() == Some("Device") {
+                    continue;
+                }
+                if read_trimmed(&dir.join("status")).as_deref() == Some("Discharging") {
+                    discharging = true;
+                }
+                if percent.is_none() {
+                    percent = read_trimmed(&dir.join("capacity")).and_then(|v| v.parse().ok());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (discharging && !mains_online, percent)
+}
+
+/**
+ * NMMetered：1 为计费，3 为推测计费
+ */
+fn is_metered(value: u32) -> bool {
+    value == 1 || value == 3
+}
+
+/**
+ * 解析 gdbus 返回的属性值，例如 "(<uint32 4>,)"
+ */
+fn parse_gdbus_uint(output: &str) -> Option<u32> {
+    let (_, rest) = output.split_once("uint32")?;
+    rest.trim_start()
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn query_network_manager() -> Option<u32> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let output = wallpaper::command_output(
+        "gdbus",
+        &[
+            "call",
+            "--system",
+            "--dest",
+            "org.freedesktop.NetworkManager",
+            "--object-path",
+            "/org/freedesktop/NetworkManager",
+            "--method",
+            "org.freedesktop.DBus.Properties.Get",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ],
+    )
+    .ok()?;
+    parse_gdbus_uint(&output)
+}
+
+/**
+ * 同一张壁纸较小的版本，来源不支持时原样返回
+ *
+ * ID 带上 -small 后缀，缓存为单独的文件，之后请求原图时不会用到缩小的版本
+ */
+pub fn smaller_variant(wallpaper: &WallpaperInfo) -> WallpaperInfo {
+    let url = match wallpaper.source {
+        WallpaperSource::Bing => BING_STEPS
+            .iter()
+            .find(|(from, _)| wallpaper.url.contains(from))
+            .map(|(from, to)| wallpaper.url.replace(from, to))
+            .unwrap_or_else(|| wallpaper.url.clone()),
+        // Pexels 原图支持按宽度缩放和压缩
+        WallpaperSource::Pexels if !wallpaper.url.contains('?') => {
+            format!("{}?auto=compress&cs=tinysrgb&w=1920", wallpaper.url)
+        }
+        _ => wallpaper.url.clone(),
+    };
+    if url == wallpaper.url {
+        return wallpaper.clone();
+    }
+    WallpaperInfo {
+        id: format!("{}{}", wallpaper.id, SMALL_SUFFIX),
+        url,
+        ..wallpaper.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(root: &Path, name: &str, files: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, value) in files {
+            fs::write(dir.join(file), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_read_battery() {
+        let root = std::env::temp_dir().join(format!("wallpaper-mate-power-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(read_battery(&root), (false, None));
+
+        supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(&root, "BAT0", &[("type", "Battery"), ("status", "Discharging"), ("capacity", "15")]);
+        supply(&root, "hidpp_battery_0", &[("type", "Battery"), ("scope", "Device"), ("capacity", "90")]);
+
+        let monitor = PowerMonitor::new(root.clone(), Box::new(|| Some(4)));
+        let state = monitor.state();
+        assert_eq!(state, PowerState { on_battery: true, battery_percent: Some(15), metered: false });
+        assert_eq!(monitor.policy(), PowerPolicy::Skip);
+
+        supply(&root, "AC", &[("online", "1")]);
+        assert_eq!(read_battery(&root), (false, Some(15)));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_policy() {
+        let config = PowerConfig::default();
+        let on_battery = PowerState { on_battery: true, battery_percent: Some(80), metered: false };
+        assert_eq!(on_battery.policy(&config), PowerPolicy::Smaller);
+
+        let both = PowerState { metered: true, ..on_battery };
+        assert_eq!(both.policy(&config), PowerPolicy::CachedOnly);
+        assert_eq!(PowerState::default().policy(&config), PowerPolicy::Normal);
+
+        let monitor = PowerMonitor::new(PathBuf::from("/nonexistent"), Box::new(|| Some(1)));
+        assert!(monitor.state().metered);
+
+        assert_eq!(parse_gdbus_uint("(<uint32 4>,)"), Some(4));
+        assert_eq!(parse_gdbus_uint("Error: not found"), None);
+    }
+
+    #[test]
+    fn test_smaller_variant() {
+        let info = WallpaperInfo {
+            id: "OHR.Test".to_string(),
+            title: "Test".to_string(),
+            url: "https://www.bing.com/th?id=OHR.Test_ZH-CN123_1920x1080.jpg&rf=LaDigue_1920x1080.jpg&pid=hp".to_string(),
+            source: WallpaperSource::Bing,
+            local_path: None,
+            cached: false,
+            author: None,
+            attribution: None,
+        };
+        let small = smaller_variant(&info);
+        assert!(small.url.contains("OHR.Test_ZH-CN123_1366x768.jpg"));
+        assert_eq!(small.id, "OHR.Test-small");
+
+        let uhd = WallpaperInfo {
+            url: "https://www.bing.com/th?id=OHR.Test_UHD.jpg&pid=hp".to_string(),
+            ..info.clone()
+        };
+        assert_eq!(smaller_variant(&uhd).url, "https://www.bing.com/th?id=OHR.Test_1920x1080.jpg&pid=hp");
+
+        let pexels = WallpaperInfo {
+            url: "https://images.pexels.com/photos/1/pexels-photo-1.jpeg".to_string(),
+            source: WallpaperSource::Pexels,
+            ..info
+        };
+        assert!(smaller_variant(&pexels).url.ends_with("?auto=compress&cs=tinysrgb&w=1920"));
+
+        let wallhaven = WallpaperInfo {
+            source: WallpaperSource::Wallhaven,
+            ..pexels
+        };
+        assert_eq!(smaller_variant(&wallhaven).id, wallhaven.id);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::services::power::{self, PowerPolicy};
+use crate::services::{fallback, offline, playlist, rotation, store};
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use tauri::AppHandle;
+
+const PREFETCH_FILE: &str = "prefetch.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrefetchConfig {
+    pub enabled: bool,
+    /// 预先下载的壁纸数量
+    pub size: usize,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size: 3,
+        }
+    }
+}
+
+/**
+ * 预取的对象：单个来源或播放列表
+ */
+#[derive(Debug, Clone)]
+pub enum PrefetchTarget {
+    Source { source: WallpaperSource },
+    Playlist,
+}
+
+impl PrefetchTarget {
+    pub fn key(&self) -> String {
+        match self {
+            PrefetchTarget::Source { source } => source.as_str().to_string(),
+            PrefetchTarget::Playlist => "playlist".to_string(),
+        }
+    }
+
+    /**
+     * 选出并下载下一张壁纸
+     */
+    async fn fetch(&self, app: &AppHandle) -> Result<WallpaperInfo, WallpaperError> {
+        match self {
+            PrefetchTarget::Source { source } => fallback::fetch_source(app, source).await,
+            PrefetchTarget::Playlist => {
+                let config = playlist::get_playlist().get_config();
+                playlist::fetch_next(app, &config).await
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct PrefetchQueue {
+    /// 当前队列对应的目标，目标变化时清空队列
+    key: Option<String>,
+    items: VecDeque<WallpaperInfo>,
+}
+
+/**
+ * 丢弃预取结果时撤销轮换中的选中记录，否则这些壁纸在重启前都不会再被选中
+ */
+fn discard(items: impl IntoIterator<Item = WallpaperInfo>) {
+    let rotation = rotation::get_rotation();
+    for info in items {
+        rotation.release(&info);
+    }
+}
+
+pub struct Prefetcher {
+    config: RwLock<PrefetchConfig>,
+    queue: Mutex<PrefetchQueue>,
+    filling: AtomicBool,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl Prefetcher {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(PrefetchConfig::default()),
+            queue: Mutex::new(PrefetchQueue::default()),
+            filling: AtomicBool::new(false),
+            path: RwLock::new(None),
+        }
+    }
+
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, PREFETCH_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: PrefetchConfig) {
+        let dropped: Vec<WallpaperInfo> = {
+            let mut queue = self.queue.lock().unwrap();
+            let size = if config.enabled { config.size } else { 0 };
+            let keep = size.min(queue.items.len());
+            queue.items.drain(keep..).collect()
+        };
+        discard(dropped);
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save prefetch config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> PrefetchConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 取出一张已下载的壁纸，目标与队列不一致时清空队列并返回 None
+     */
+    pub fn take(&self, key: &str) -> Option<WallpaperInfo> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.key.as_deref() != Some(key) {
+            queue.key = Some(key.to_string());
+            discard(queue.items.drain(..));
+            return None;
+        }
+
+        // 缓存文件可能已被用户删除
+        while let Some(info) = queue.items.pop_front() {
+            if info.local_path.as_deref().is_some_and(|path| path.exists()) {
+                return Some(info);
+            }
+            discard([info]);
+        }
+        None
+    }
+
+    /**
+     * 当前队列中的壁纸
+     */
+    pub fn queued(&self) -> Vec<WallpaperInfo> {
+        self.queue.lock().unwrap().items.iter().cloned().collect()
+    }
+
+    /**
+     * 丢弃指定目标的预取结果，例如播放列表修改后
+     */
+    pub fn invalidate(&self, key: &str) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.key.as_deref() == Some(key) {
+            discard(queue.items.drain(..));
+        }
+    }
+
+    /**
+     * 放入预取结果，目标已经切换或队列已满时返回 false
+     */
+    fn push(&self, key: &str, info: WallpaperInfo) -> bool {
+        let size = self.get_config().size;
+        let mut queue = self.queue.lock().unwrap();
+        if queue.key.as_deref() != Some(key) || queue.items.len() >= size {
+            return false;
+        }
+        queue.items.push_back(info);
+        true
+    }
+
+    fn needs_more(&self, key: &str) -> bool {
+        let config = self.get_config();
+        let queue = self.queue.lock().unwrap();
+        config.enabled && queue.key.as_deref() == Some(key) && queue.items.len() < config.size
+    }
+}
+
+impl Default for Prefetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static PREFETCHER: OnceLock<Prefetcher> = OnceLock::new();
+
+pub fn get_prefetcher() -> &'static Prefetcher {
+    PREFETCHER.get_or_init(Prefetcher::new)
+}
+
+/**
+ * 在后台把队列补满，同一时间只有一个补充任务
+ */
+pub fn spawn_refill(app: &AppHandle, target: PrefetchTarget) {
+    // 离线或使用电池、计费网络时策略要求少下载，不提前下载
+    if offline::get_offline().is_offline() || power::get_power().policy() >= PowerPolicy::CachedOnly {
+        return;
+    }
+
+    let prefetcher = get_prefetcher();
+    let key = target.key();
+    if !prefetcher.needs_more(&key) || prefetcher.filling.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while prefetcher.needs_more(&key) {
+            match target.fetch(&app).await {
+                Ok(info) => {
+                    if !prefetcher.push(&key, info.clone()) {
+                        discard([info]);
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Prefetch failed for {}: {}", key, e);
+                    break;
+                }
+            }
+        }
+        prefetcher.filling.store(false, Ordering::SeqCst);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(path: &std::path::Path, id: &str) -> WallpaperInfo {
+        WallpaperInfo {
+            id: id.to_string(),
+            title: id.to_string(),
+            url: format!("https://example.com/{}.jpg", id),
+            source: WallpaperSource::Bing,
+            local_path: Some(path.to_path_buf()),
+            cached: true,
+            author: None,
+            attribution: None,
+        }
+    }
+
+    #[test]
+    fn test_queue_follows_target() {
+        let prefetcher = Prefetcher::new();
+        let file = std::env::temp_dir().join(format!("wallpaper-mate-prefetch-{}.jpg", std::process::id()));
+        std::fs::write(&file, b"").unwrap();
+        let missing = file.with_extension("missing.jpg");
+
+        assert!(prefetcher.take("bing").is_none());
+        assert!(prefetcher.needs_more("bing"));
+        assert!(prefetcher.push("bing", cached(&missing, "gone")));
+        assert!(prefetcher.push("bing", cached(&file, "a")));
+        assert!(!prefetcher.push("wallhaven", cached(&file, "b")));
+
+        // 跳过已删除的文件
+        assert_eq!(prefetcher.take("bing").unwrap().id, "a");
+
+        // 切换目标时释放被丢弃壁纸的选中记录
+        let dropped = cached(&file, "prefetch-dropped");
+        rotation::get_rotation().reserve(&dropped);
+        prefetcher.push("bing", dropped.clone());
+        assert!(prefetcher.take("wallhaven").is_none());
+        assert!(prefetcher.queued().is_empty());
+        let config = rotation::RotationConfig { no_repeat_days: 0, ..Default::default() };
+        assert!(rotation::get_rotation().pick(&[dropped], &config, chrono::Local::now()).is_some());
+
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let prefetcher = Prefetcher::new();
+        prefetcher.set_config(PrefetchConfig { enabled: true, size: 1 });
+        prefetcher.take("bing");
+        let file = std::env::temp_dir().join("wallpaper-mate-prefetch-size.jpg");
+
+        assert!(prefetcher.push("bing", cached(&file, "a")));
+        assert!(!prefetcher.push("bing", cached(&file, "b")));
+        assert!(!prefetcher.needs_more("bing"));
+
+        prefetcher.set_config(PrefetchConfig { enabled: false, size: 1 });
+        assert!(prefetcher.queued().is_empty());
+        assert!(!prefetcher.needs_more("bing"));
+    }
+}
@@ -0,0 +1,428 @@
+use crate::services::store;
+use crate::sources::provider::{fetch_page, ProviderError, SourceQuery};
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use chrono::{DateTime, Duration, Local};
+use rand::prelude::IndexedRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use std::time::Instant;
+use tauri::AppHandle;
+
+const ROTATION_FILE: &str = "rotation.json";
+const ROTATION_CONFIG_FILE: &str = "rotation_config.json";
+// 记忆条目上限，超出时丢弃最早的记录
+const MAX_MEMORY: usize = 5000;
+// 选中记录的有效期，选中后一直没有设置（如下载失败）的壁纸过期后可以再次选中
+const RESERVATION_TTL: std::time::Duration = std::time::Duration::from_secs(6 * 3600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationMode {
+    /// 按列表顺序依次显示
+    Sequential,
+    /// 在未显示过的壁纸中随机选择
+    Shuffle,
+    /// 越久没显示、排名越靠前的壁纸被选中的概率越高
+    Weighted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RotationConfig {
+    pub mode: RotationMode,
+    /// 多少天内不重复，为 0 时不限制
+    pub no_repeat_days: u32,
+    /// 找不到新壁纸时最多向后翻的页数
+    pub max_pages: u32,
+}
+
+impl Default for RotationConfig {
+    fn default() -> Self {
+        Self {
+            mode: RotationMode::Shuffle,
+            no_repeat_days: 7,
+            max_pages: 5,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RotationState {
+    /// "{source}:{id}" -> 最近一次显示时间，所有来源共用
+    shown: HashMap<String, DateTime<Local>>,
+    /// 每个来源（或播放列表条目）下次开始查找的页码
+    pages: HashMap<String, u32>,
+}
+
+pub struct RotationManager {
+    config: RwLock<RotationConfig>,
+    state: RwLock<RotationState>,
+    /// 已选出但还没设置的壁纸（如预取队列中的）及选中时间，只在内存中记录，避免重复选中
+    reserved: RwLock<HashMap<String, Instant>>,
+    path: RwLock<Option<PathBuf>>,
+    config_path: RwLock<Option<PathBuf>>,
+}
+
+fn memory_key(wallpaper: &WallpaperInfo) -> String {
+    format!("{}:{}", wallpaper.source.as_str(), wallpaper.id)
+}
+
+impl RotationManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(RotationConfig::default()),
+            state: RwLock::new(RotationState::default()),
+            reserved: RwLock::new(HashMap::new()),
+            path: RwLock::new(None),
+            config_path: RwLock::new(None),
+        }
+    }
+
+    pub fn set_config(&self, config: RotationConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.config_path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save rotation config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> RotationConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let config_path = store::data_file(app, ROTATION_CONFIG_FILE)?;
+        if let Some(config) = store::load_json(&config_path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.config_path.write().unwrap() = Some(config_path);
+
+        let path = store::data_file(app, ROTATION_FILE)?;
+        if let Some(state) = store::load_json(&path) {
+            *self.state.write().unwrap() = state;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    /**
+     * 记录壁纸已显示，由设置壁纸的流程调用
+     */
+    pub fn mark_shown(&self, wallpaper: &WallpaperInfo, now: DateTime<Local>) {
+        self.reserved.write().unwrap().remove(&memory_key(wallpaper));
+        {
+            let mut state = self.state.write().unwrap();
+            state.shown.insert(memory_key(wallpaper), now);
+
+            if state.shown.len() > MAX_MEMORY {
+                let mut times: Vec<DateTime<Local>> = state.shown.values().copied().collect();
+                times.sort();
+                let cutoff = times[state.shown.len() - MAX_MEMORY];
+                state.shown.retain(|_, shown_at| *shown_at >= cutoff);
+            }
+        }
+        self.save();
+    }
+
+    /**
+     * 记录壁纸已被选中，设置之前不会再被选中
+     */
+    pub fn reserve(&self, wallpaper: &WallpaperInfo) {
+        let now = Instant::now();
+        let mut reserved = self.reserved.write().unwrap();
+        reserved.retain(|_, at| now.duration_since(*at) < RESERVATION_TTL);
+        reserved.insert(memory_key(wallpaper), now);
+    }
+
+    /**
+     * 撤销选中记录，选中的壁纸最终没有设置时（如预取队列被清空）调用
+     */
+    pub fn release(&self, wallpaper: &WallpaperInfo) {
+        self.reserved.write().unwrap().remove(&memory_key(wallpaper));
+    }
+
+    fn is_reserved(&self, wallpaper: &WallpaperInfo) -> bool {
+        self.reserved
+            .read()
+            .unwrap()
+            .get(&memory_key(wallpaper))
+            .is_some_and(|at| at.elapsed() < RESERVATION_TTL)
+    }
+
+    pub fn last_shown(&self, wallpaper: &WallpaperInfo) -> Option<DateTime<Local>> {
+        self.state.read().unwrap().shown.get(&memory_key(wallpaper)).copied()
+    }
+
+    fn start_page(&self, key: &str) -> u32 {
+        self.state.read().unwrap().pages.get(key).copied().unwrap_or(1)
+    }
+
+    fn set_page(&self, key: &str, page: u32) {
+        self.state.write().unwrap().pages.insert(key.to_string(), page);
+    }
+
+    /**
+     * 清空显示记录和翻页位置
+     */
+    pub fn reset(&self) {
+        *self.state.write().unwrap() = RotationState::default();
+        self.reserved.write().unwrap().clear();
+        self.save();
+    }
+
+    /**
+     * 按当前模式从一页中选出一张，页内都在不重复窗口内或已被选中时返回 None
+     */
+    pub fn pick(
+        &self,
+        wallpapers: &[WallpaperInfo],
+        config: &RotationConfig,
+        now: DateTime<Local>,
+    ) -> Option<WallpaperInfo> {
+        let window = Duration::days(config.no_repeat_days as i64);
+        let fresh: Vec<(usize, Option<DateTime<Local>>)> = wallpapers
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| !self.is_reserved(w))
+            .map(|(i, w)| (i, self.last_shown(w)))
+            .filter(|(_, shown_at)| {
+                config.no_repeat_days == 0 || shown_at.map_or(true, |t| now - t >= window)
+            })
+            .collect();
+
+        let index = match config.mode {
+            RotationMode::Sequential => fresh.first().map(|(i, _)| *i),
+            RotationMode::Shuffle => fresh.choose(&mut rand::rng()).map(|(i, _)| *i),
+            RotationMode::Weighted => {
+                let count = wallpapers.len() as f64;
+                let weights: Vec<f64> = fresh
+                    .iter()
+                    .map(|(i, shown_at)| {
+                        // 从未显示过的权重最高，其余按距上次显示的时间增长
+                        let age = match shown_at {
+                            None => 2.0,
+                            Some(t) => {
+                                let days = (now - *t).num_minutes() as f64 / 1440.0;
+                                1.0 + (days / config.no_repeat_days.max(1) as f64).min(1.0)
+                            }
+                        };
+                        let rank = 1.0 + (count - *i as f64) / count;
+                        age * rank
+                    })
+                    .collect();
+                weighted_choice(&weights).map(|k| fresh[k].0)
+            }
+        };
+
+        index.map(|i| wallpapers[i].clone())
+    }
+
+    /**
+     * 都在窗口内时退而求其次，选最久没显示的
+     */
+    pub fn least_recent(&self, wallpapers: &[WallpaperInfo]) -> Option<WallpaperInfo> {
+        wallpapers
+            .iter()
+            .min_by_key(|w| self.last_shown(w))
+            .cloned()
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.read().unwrap().clone() else {
+            return;
+        };
+        if let Err(e) = store::save_json(&path, &*self.state.read().unwrap()) {
+            log::warn!("Failed to save rotation state: {}", e);
+        }
+    }
+}
+
+impl Default for RotationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static ROTATION: OnceLock<RotationManager> = OnceLock::new();
+
+pub fn get_rotation() -> &'static RotationManager {
+    ROTATION.get_or_init(RotationManager::new)
+}
+
+fn weighted_choice(weights: &[f64]) -> Option<usize> {
+    let total: f64 = weights.iter().sum();
+    if weights.is_empty() || total <= 0.0 {
+        return None;
+    }
+
+    let mut target = rand::rng().random::<f64>() * total;
+    for (i, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return Some(i);
+        }
+        target -= weight;
+    }
+    Some(weights.len() - 1)
+}
+
+/**
+ * 记录翻页位置用的键，每组搜索条件单独记录
+ */
+pub fn page_key(source: &WallpaperSource, query: &SourceQuery) -> String {
+    if query.is_empty() {
+        return source.as_str().to_string();
+    }
+    format!("{}?{}", source.as_str(), serde_json::to_string(query).unwrap_or_default())
+}
+
+/**
+ * 按搜索条件选出来源的下一张壁纸，条件为空时使用来源的默认列表
+ */
+pub async fn next(source: WallpaperSource, query: &SourceQuery) -> Result<WallpaperInfo, WallpaperError> {
+    next_in(&page_key(&source, query), |page| fetch_page(source.clone(), query, page)).await
+}
+
+/**
+ * 从分页列表中选出下一张壁纸，key 用于记录翻页位置
+ *
+ * 从上次的页码开始查找未在窗口内显示过的壁纸，当前页用完后继续向后翻页，
+ * 翻到最后一页回到第一页。都显示过时选择最久没显示的一张
+ */
+pub async fn next_in<F, Fut>(key: &str, mut fetch: F) -> Result<WallpaperInfo, WallpaperError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<(Vec<WallpaperInfo>, u32), ProviderError>>,
+{
+    let rotation = get_rotation();
+    let config = rotation.get_config();
+    let now = Local::now();
+
+    let start = rotation.start_page(key);
+    let mut page = start;
+    let mut first_page: Option<Vec<WallpaperInfo>> = None;
+
+    for _ in 0..config.max_pages.max(1) {
+        let (wallpapers, last_page) = match fetch(page).await {
+            Ok(result) => result,
+            // 保存的页码可能已经超出范围，从第一页重新开始
+            Err(e) if page != 1 => {
+                log::warn!("Failed to fetch page {} of {}: {}", page, key, e);
+                (Vec::new(), 1)
+            }
+            Err(e) => return Err(WallpaperError::ApiError(e.to_string())),
+        };
+
+        if let Some(selected) = rotation.pick(&wallpapers, &config, now) {
+            rotation.set_page(key, page);
+            rotation.reserve(&selected);
+            return Ok(selected);
+        }
+
+        if first_page.is_none() && !wallpapers.is_empty() {
+            first_page = Some(wallpapers);
+        }
+        page = if wallpapers_exhausted(page, last_page) { 1 } else { page + 1 };
+        if page == start {
+            break;
+        }
+    }
+
+    let fallback = first_page
+        .as_deref()
+        .and_then(|wallpapers| rotation.least_recent(wallpapers))
+        .ok_or_else(|| WallpaperError::ApiError("No wallpapers found".to_string()))?;
+    rotation.reserve(&fallback);
+    Ok(fallback)
+}
+
+fn wallpapers_exhausted(page: u32, last_page: u32) -> bool {
+    page >= last_page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallpapers(count: usize) -> Vec<WallpaperInfo> {
+        (0..count)
+            .map(|i| WallpaperInfo {
+                id: i.to_string(),
+                title: i.to_string(),
+                url: format!("https://example.com/{}.jpg", i),
+                source: WallpaperSource::Bing,
+                local_path: None,
+                cached: false,
+                author: None,
+                attribution: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pick_skips_recent() {
+        let rotation = RotationManager::new();
+        let page = wallpapers(4);
+        let now = Local::now();
+
+        for mode in [RotationMode::Sequential, RotationMode::Shuffle, RotationMode::Weighted] {
+            let rotation = RotationManager::new();
+            let config = RotationConfig { mode, ..Default::default() };
+            let mut picked = Vec::new();
+            while let Some(selected) = rotation.pick(&page, &config, now) {
+                rotation.mark_shown(&selected, now);
+                picked.push(selected.id);
+            }
+            picked.sort();
+            assert_eq!(picked, vec!["0", "1", "2", "3"], "{:?}", mode);
+        }
+
+        let config = RotationConfig { mode: RotationMode::Sequential, ..Default::default() };
+        rotation.mark_shown(&page[0], now - Duration::days(8));
+        rotation.mark_shown(&page[1], now - Duration::days(1));
+        assert_eq!(rotation.pick(&page, &config, now).unwrap().id, "0");
+    }
+
+    #[test]
+    fn test_no_window_allows_repeats() {
+        let rotation = RotationManager::new();
+        let page = wallpapers(2);
+        let now = Local::now();
+        rotation.mark_shown(&page[0], now);
+        rotation.mark_shown(&page[1], now);
+
+        let config = RotationConfig { no_repeat_days: 0, ..Default::default() };
+        assert!(rotation.pick(&page, &config, now).is_some());
+        assert_eq!(rotation.least_recent(&page).unwrap().id, "0");
+    }
+
+    #[test]
+    fn test_reserved_until_shown() {
+        let rotation = RotationManager::new();
+        let page = wallpapers(2);
+        let now = Local::now();
+        let config = RotationConfig { mode: RotationMode::Sequential, no_repeat_days: 0, ..Default::default() };
+
+        rotation.reserve(&page[0]);
+        assert_eq!(rotation.pick(&page, &config, now).unwrap().id, "1");
+        assert!(rotation.last_shown(&page[0]).is_none());
+
+        rotation.mark_shown(&page[0], now);
+        assert_eq!(rotation.pick(&page, &config, now).unwrap().id, "0");
+
+        rotation.reserve(&page[1]);
+        rotation.release(&page[1]);
+        assert!(!rotation.is_reserved(&page[1]));
+
+        // 过期的选中记录不再排除
+        if let Some(expired) = Instant::now().checked_sub(RESERVATION_TTL) {
+            rotation.reserved.write().unwrap().insert(memory_key(&page[1]), expired);
+            assert!(!rotation.is_reserved(&page[1]));
+        }
+    }
+}
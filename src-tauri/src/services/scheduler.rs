@@ -1,11 +1,32 @@
 use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
+use tauri::AppHandle;
+
+use crate::services::history::{self, ApplyTrigger};
+use crate::services::playlist::{self, PlaylistConfig};
+use crate::services::power::{self, PowerPolicy};
+use crate::services::prefetch;
+use crate::services::{apply, fetcher, offline, triggers};
+use crate::sources::provider::SourceQuery;
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+
+// 定时器检查间隔
+const TICK_SECONDS: u64 = 15;
+// 播放列表在计时表中的键
+pub const PLAYLIST_KEY: &str = "playlist";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AutoSwitchConfig {
     pub enabled: bool,
     pub interval_seconds: u64,
+    /// 切换时使用的关键词和筛选条件，为空时使用来源的默认列表
+    #[serde(default)]
+    pub query: SourceQuery,
 }
 
 impl Default for AutoSwitchConfig {
@@ -13,6 +34,7 @@ impl Default for AutoSwitchConfig {
         Self {
             enabled: false,
             interval_seconds: 3600,
+            query: SourceQuery::default(),
         }
     }
 }
@@ -21,19 +43,26 @@ type AutoSwitchState = Arc<RwLock<HashMap<String, AutoSwitchConfig>>>;
 
 pub struct WallpaperScheduler {
     state: AutoSwitchState,
+    last_run: Arc<RwLock<HashMap<String, Instant>>>,
+    paused: RwLock<bool>,
 }
 
 impl WallpaperScheduler {
     pub fn new() -> Self {
         Self {
             state: Arc::new(RwLock::new(HashMap::new())),
+            last_run: Arc::new(RwLock::new(HashMap::new())),
+            paused: RwLock::new(false),
         }
     }
 
     pub fn set_config(&self, source: &str, config: AutoSwitchConfig) {
         let key = format!("scheduler_{}", source);
         let mut state = self.state.write().unwrap();
-        state.insert(key, config);
+        state.insert(key.clone(), config);
+        // 修改配置后重新计时，预取的壁纸可能不符合新的搜索条件
+        self.last_run.write().unwrap().insert(key, Instant::now());
+        prefetch::get_prefetcher().invalidate(source);
     }
 
     pub fn get_config(&self, source: &str) -> Option<AutoSwitchConfig> {
@@ -41,6 +70,106 @@ impl WallpaperScheduler {
         let state = self.state.read().unwrap();
         state.get(&key).cloned()
     }
+
+    /**
+     * 来源的搜索条件，手动切换、托盘和备用来源也使用
+     */
+    pub fn query(&self, source: &WallpaperSource) -> SourceQuery {
+        self.get_config(source.as_str())
+            .map(|config| config.query)
+            .unwrap_or_default()
+    }
+
+    /**
+     * 暂停或恢复所有自动切换，恢复后重新计时
+     */
+    pub fn set_paused(&self, paused: bool) {
+        *self.paused.write().unwrap() = paused;
+        if !paused {
+            self.reset_timers(Instant::now());
+        }
+    }
+
+    /**
+     * 所有定时器从 now 重新计时，已启用但还没有计时的来源也一并计入
+     */
+    pub fn reset_timers(&self, now: Instant) {
+        let state = self.state.read().unwrap();
+        let mut last_run = self.last_run.write().unwrap();
+        for last in last_run.values_mut() {
+            *last = now;
+        }
+        for key in state.keys() {
+            last_run.insert(key.clone(), now);
+        }
+        last_run.insert(format!("scheduler_{}", PLAYLIST_KEY), now);
+    }
+
+    /**
+     * 当前生效的单个来源：第一个启用了定时切换的来源
+     */
+    pub fn active_source(&self) -> Option<(String, AutoSwitchConfig)> {
+        let state = self.state.read().unwrap();
+        let mut enabled: Vec<_> = state
+            .iter()
+            .filter(|(_, config)| config.enabled)
+            .map(|(key, config)| (key.trim_start_matches("scheduler_").to_string(), config.clone()))
+            .collect();
+        enabled.sort_by(|a, b| a.0.cmp(&b.0));
+        enabled.into_iter().next()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.read().unwrap()
+    }
+
+    /**
+     * 已到切换时间的来源及其配置
+     */
+    pub fn due_sources(&self, now: Instant) -> Vec<(String, AutoSwitchConfig)> {
+        if self.is_paused() {
+            return Vec::new();
+        }
+
+        let state = self.state.read().unwrap();
+        let last_run = self.last_run.read().unwrap();
+
+        state
+            .iter()
+            .filter(|(_, config)| config.enabled)
+            .filter(|(key, config)| {
+                last_run.get(*key).map_or(true, |last| {
+                    now.duration_since(*last) >= Duration::from_secs(config.interval_seconds)
+                })
+            })
+            .map(|(key, config)| (key.trim_start_matches("scheduler_").to_string(), config.clone()))
+            .collect()
+    }
+
+    /**
+     * 播放列表是否已到切换时间
+     */
+    pub fn playlist_due(&self, config: &PlaylistConfig, now: Instant) -> bool {
+        if self.is_paused() || !config.is_active() {
+            return false;
+        }
+
+        let key = format!("scheduler_{}", PLAYLIST_KEY);
+        self.last_run.read().unwrap().get(&key).map_or(true, |last| {
+            now.duration_since(*last) >= Duration::from_secs(config.interval_seconds)
+        })
+    }
+
+    pub fn mark_run(&self, source: &str, now: Instant) {
+        let key = format!("scheduler_{}", source);
+        self.last_run.write().unwrap().insert(key, now);
+    }
+}
+
+impl Default for WallpaperScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 static SCHEDULER: std::sync::OnceLock<WallpaperScheduler> = std::sync::OnceLock::new();
@@ -49,6 +178,120 @@ pub fn get_scheduler() -> &'static WallpaperScheduler {
     SCHEDULER.get_or_init(|| WallpaperScheduler::new())
 }
 
+/**
+ * 按电源和计费网络策略执行自动切换
+ *
+ * 策略要求跳过时返回 None；只用缓存时优先选同一来源的缓存，没有时选任意缓存。
+ * 离线模式下在线来源同样只用缓存
+ */
+async fn switch_with<Fut>(
+    app: &AppHandle,
+    cache_source: Option<WallpaperSource>,
+    trigger: ApplyTrigger,
+    fetch: Fut,
+) -> Result<Option<PathBuf>, WallpaperError>
+where
+    Fut: Future<Output = Result<WallpaperInfo, WallpaperError>>,
+{
+    let mut policy = power::get_power().policy();
+    if offline::get_offline().is_offline() && cache_source.as_ref().map_or(true, |s| s.is_remote()) {
+        policy = policy.max(PowerPolicy::CachedOnly);
+    }
+    let info = match policy {
+        PowerPolicy::Skip => {
+            log::info!("Auto switch skipped by power policy");
+            return Ok(None);
+        }
+        PowerPolicy::CachedOnly => fetcher::from_cache_or_any(app, cache_source.as_ref()).await?,
+        _ => fetch.await?,
+    };
+    apply::apply_wallpaper(app, info, trigger).await.map(Some)
+}
+
+async fn switch_once(app: &AppHandle, source: &str) -> Result<Option<PathBuf>, WallpaperError> {
+    let source = WallpaperSource::from_str(source)
+        .ok_or_else(|| WallpaperError::ApiError(format!("Invalid source: {}", source)))?;
+    let fetch = fetcher::fetch_next(app, source.clone());
+    switch_with(app, Some(source), ApplyTrigger::Schedule, fetch).await
+}
+
+async fn switch_playlist(
+    app: &AppHandle,
+    config: &PlaylistConfig,
+) -> Result<Option<PathBuf>, WallpaperError> {
+    let fetch = fetcher::fetch_from_playlist(app, config);
+    switch_with(app, None, ApplyTrigger::Schedule, fetch).await
+}
+
+/**
+ * 立即按当前的切换设置换一张壁纸，并重新计时
+ *
+ * 依次使用播放列表、启用了定时切换的来源、最近一张壁纸的来源（默认 Bing）。
+ * 启动、唤醒等触发器共用此流程
+ */
+pub async fn switch_now(
+    app: &AppHandle,
+    trigger: ApplyTrigger,
+) -> Result<Option<PathBuf>, WallpaperError> {
+    let scheduler = get_scheduler();
+    scheduler.reset_timers(Instant::now());
+
+    let config = playlist::get_playlist().get_config();
+    if config.is_active() {
+        return switch_with(app, None, trigger, fetcher::fetch_from_playlist(app, &config)).await;
+    }
+
+    let source = match scheduler.active_source() {
+        Some((source, _)) => WallpaperSource::from_str(&source)
+            .ok_or_else(|| WallpaperError::ApiError(format!("Invalid source: {}", source)))?,
+        None => history::get_history()
+            .page(1, 1)
+            .data
+            .into_iter()
+            .next()
+            .map(|entry| entry.wallpaper.source)
+            .unwrap_or(WallpaperSource::Bing),
+    };
+    let fetch = fetcher::fetch_next(app, source.clone());
+    switch_with(app, Some(source), trigger, fetch).await
+}
+
+/**
+ * 后台定时切换壁纸
+ *
+ * 启用播放列表后只按播放列表切换
+ */
+pub fn spawn_runner(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(TICK_SECONDS));
+        loop {
+            ticker.tick().await;
+
+            let config = playlist::get_playlist().get_config();
+            if config.is_active() {
+                if get_scheduler().playlist_due(&config, Instant::now()) {
+                    get_scheduler().mark_run(PLAYLIST_KEY, Instant::now());
+                    if let Err(e) = switch_playlist(&app, &config).await {
+                        log::warn!("Auto switch failed for playlist: {}", e);
+                        let interval = Duration::from_secs(config.interval_seconds);
+                        triggers::get_triggers().record_failure(&e, interval);
+                    }
+                }
+                continue;
+            }
+
+            for (source, config) in get_scheduler().due_sources(Instant::now()) {
+                get_scheduler().mark_run(&source, Instant::now());
+                if let Err(e) = switch_once(&app, &source).await {
+                    log::warn!("Auto switch failed for {}: {}", source, e);
+                    let interval = Duration::from_secs(config.interval_seconds);
+                    triggers::get_triggers().record_failure(&e, interval);
+                }
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +302,83 @@ mod tests {
         assert_eq!(config.enabled, false);
         assert_eq!(config.interval_seconds, 3600);
     }
+
+    #[test]
+    fn test_due_sources() {
+        let scheduler = WallpaperScheduler::new();
+        let config = AutoSwitchConfig {
+            enabled: true,
+            interval_seconds: 60,
+            ..Default::default()
+        };
+        scheduler.set_config("bing", config.clone());
+        scheduler.set_config("wallhaven", AutoSwitchConfig { enabled: false, ..config });
+
+        let now = Instant::now();
+        assert!(scheduler.due_sources(now).is_empty());
+
+        let due = scheduler.due_sources(now + Duration::from_secs(61));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, "bing");
+
+        scheduler.set_paused(true);
+        assert!(scheduler.due_sources(now + Duration::from_secs(61)).is_empty());
+    }
+
+    #[test]
+    fn test_source_query() {
+        let scheduler = WallpaperScheduler::new();
+        assert!(scheduler.query(&WallpaperSource::Wallhaven).is_empty());
+
+        let config: AutoSwitchConfig = serde_json::from_str(
+            r#"{"enabled": true, "intervalSeconds": 600, "query": {"query": "mountains", "filters": {"atleast": "2560x1440"}}}"#,
+        )
+        .unwrap();
+        scheduler.set_config("wallhaven", config);
+        let query = scheduler.query(&WallpaperSource::Wallhaven);
+        assert_eq!(query.query.as_deref(), Some("mountains"));
+        assert_eq!(query.filters.get("atleast").map(String::as_str), Some("2560x1440"));
+        assert!(scheduler.query(&WallpaperSource::Bing).is_empty());
+    }
+
+    #[test]
+    fn test_reset_timers() {
+        let scheduler = WallpaperScheduler::new();
+        let config = AutoSwitchConfig {
+            enabled: true,
+            interval_seconds: 60,
+            ..Default::default()
+        };
+        scheduler.set_config("wallhaven", config.clone());
+        scheduler.set_config("bing", config);
+        assert_eq!(scheduler.active_source().unwrap().0, "bing");
+
+        let later = Instant::now() + Duration::from_secs(61);
+        assert_eq!(scheduler.due_sources(later).len(), 2);
+        scheduler.reset_timers(later);
+        assert!(scheduler.due_sources(later + Duration::from_secs(30)).is_empty());
+    }
+
+    #[test]
+    fn test_playlist_due() {
+        let scheduler = WallpaperScheduler::new();
+        let mut config = PlaylistConfig {
+            enabled: true,
+            interval_seconds: 60,
+            entries: Vec::new(),
+        };
+        let now = Instant::now();
+        assert!(!scheduler.playlist_due(&config, now));
+
+        config.entries.push(playlist::PlaylistEntry {
+            target: playlist::PlaylistTarget::Source { source: WallpaperSource::Bing },
+            weight: 1.0,
+            enabled: true,
+        });
+        assert!(scheduler.playlist_due(&config, now));
+
+        scheduler.mark_run(PLAYLIST_KEY, now);
+        assert!(!scheduler.playlist_due(&config, now + Duration::from_secs(30)));
+        assert!(scheduler.playlist_due(&config, now + Duration::from_secs(61)));
+    }
 }
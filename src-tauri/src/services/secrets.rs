@@ -0,0 +1,435 @@
+use crate::services::store;
+use crate::types::{WallpaperError, WallpaperSource};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+const SECRETS_FILE: &str = "secrets.enc";
+const SECRETS_KEY_FILE: &str = "secrets.key";
+// 系统钥匙串中的服务名
+const KEYRING_SERVICE: &str = "wallpaper-mate";
+const NONCE_LEN: usize = 12;
+
+/**
+ * API Key 的存放位置
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretBackend {
+    /// Linux Secret Service（libsecret）或 macOS 钥匙串
+    Keyring,
+    /// 应用数据目录下的加密文件
+    File,
+    /// 环境变量 WALLPAPER_MATE_<SOURCE>_KEY，便于开发调试
+    Environment,
+}
+
+/**
+ * 前端看到的 API Key 状态，不包含完整的 Key
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyStatus {
+    pub source: WallpaperSource,
+    pub configured: bool,
+    pub backend: Option<SecretBackend>,
+    /// Key 的最后 4 位
+    pub hint: Option<String>,
+}
+
+fn env_var(source: &WallpaperSource) -> String {
+    format!("WALLPAPER_MATE_{}_KEY", source.as_str().to_uppercase())
+}
+
+/**
+ * 系统钥匙串通过命令行工具访问：Linux 使用 secret-tool，macOS 使用 security。
+ * 其他平台没有可用的工具，只使用加密文件
+ */
+fn keyring_supported() -> bool {
+    cfg!(any(target_os = "linux", target_os = "macos"))
+}
+
+fn run_tool(program: &str, args: &[&str], stdin: Option<&str>) -> Result<std::process::Output, WallpaperError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+fn tool_failed(program: &str, output: &std::process::Output) -> WallpaperError {
+    WallpaperError::WallpaperError(format!(
+        "{} exited with {}: {}",
+        program,
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/**
+ * 从钥匙串读取 Key，没有保存过时返回 None，钥匙串不可用时返回错误
+ */
+fn keyring_get(provider: &str) -> Result<Option<String>, WallpaperError> {
+    let (program, output) = if cfg!(target_os = "macos") {
+        let args = ["find-generic-password", "-s", KEYRING_SERVICE, "-a", provider, "-w"];
+        ("security", run_tool("security", &args, None)?)
+    } else {
+        let args = ["lookup", "application", KEYRING_SERVICE, "provider", provider];
+        ("secret-tool", run_tool("secret-tool", &args, None)?)
+    };
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !value.is_empty() {
+        return Ok(Some(value));
+    }
+    // 找不到条目时 secret-tool 只返回非零退出码，security 输出固定的提示
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.trim().is_empty() || stderr.contains("could not be found") {
+        return Ok(None);
+    }
+    Err(tool_failed(program, &output))
+}
+
+fn keyring_set(provider: &str, key: &str) -> Result<(), WallpaperError> {
+    let label = format!("Wallpaper Mate: {}", provider);
+    let (program, output) = if cfg!(target_os = "macos") {
+        // security 只能从参数读取密码
+        let args = ["add-generic-password", "-U", "-s", KEYRING_SERVICE, "-a", provider, "-l", &label, "-w", key];
+        ("security", run_tool("security", &args, None)?)
+    } else {
+        // secret-tool 从标准输入读取密码，不会出现在进程列表中
+        let label = format!("--label={}", label);
+        let args = ["store", &label, "application", KEYRING_SERVICE, "provider", provider];
+        ("secret-tool", run_tool("secret-tool", &args, Some(key))?)
+    };
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(tool_failed(program, &output))
+    }
+}
+
+fn keyring_delete(provider: &str) -> Result<(), WallpaperError> {
+    if cfg!(target_os = "macos") {
+        run_tool("security", &["delete-generic-password", "-s", KEYRING_SERVICE, "-a", provider], None)?;
+    } else {
+        run_tool("secret-tool", &["clear", "application", KEYRING_SERVICE, "provider", provider], None)?;
+    }
+    Ok(())
+}
+
+/**
+ * 加密文件：ChaCha20-Poly1305，密钥随机生成并单独保存（仅当前用户可读）
+ *
+ * 只能防止 Key 以明文出现在配置、备份或日志中，不能防御能读取用户文件的程序
+ */
+struct EncryptedFile {
+    path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl EncryptedFile {
+    fn load_cipher(&self) -> Result<Option<ChaCha20Poly1305>, WallpaperError> {
+        match fs::read(&self.key_path) {
+            Ok(key) if key.len() == 32 => Ok(Some(ChaCha20Poly1305::new(Key::from_slice(&key)))),
+            Ok(_) => Err(WallpaperError::WallpaperError("Corrupted secrets key".to_string())),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /**
+     * 第一次写入时生成密钥
+     */
+    fn cipher(&self) -> Result<ChaCha20Poly1305, WallpaperError> {
+        if let Some(cipher) = self.load_cipher()? {
+            return Ok(cipher);
+        }
+        let mut key = [0u8; 32];
+        rand::rng().fill(&mut key);
+        write_private(&self.key_path, &key)?;
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, String>, WallpaperError> {
+        let Ok(data) = fs::read(&self.path) else {
+            return Ok(BTreeMap::new());
+        };
+        let Some(cipher) = self.load_cipher()? else {
+            return Err(WallpaperError::WallpaperError("Secrets key is missing".to_string()));
+        };
+        if data.len() < NONCE_LEN {
+            return Err(WallpaperError::WallpaperError("Corrupted secrets file".to_string()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| WallpaperError::WallpaperError("Failed to decrypt secrets file".to_string()))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn write(&self, secrets: &BTreeMap<String, String>) -> Result<(), WallpaperError> {
+        if secrets.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+        let cipher = self.cipher()?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill(&mut nonce);
+        let plaintext = serde_json::to_vec(secrets)?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| WallpaperError::WallpaperError("Failed to encrypt secrets".to_string()))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        write_private(&self.path, &data)
+    }
+}
+
+/**
+ * 写入只有当前用户可读写的文件
+ */
+fn write_private(path: &Path, data: &[u8]) -> Result<(), WallpaperError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&tmp)?.write_all(data)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+pub struct SecretStore {
+    use_keyring: bool,
+    file: RwLock<Option<EncryptedFile>>,
+    /// 已读取过的 Key，避免每次请求都调用钥匙串
+    cache: RwLock<HashMap<String, Option<(String, SecretBackend)>>>,
+}
+
+impl SecretStore {
+    pub fn new(use_keyring: bool) -> Self {
+        Self {
+            use_keyring,
+            file: RwLock::new(None),
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, SECRETS_FILE)?;
+        let key_path = store::data_file(app, SECRETS_KEY_FILE)?;
+        self.set_files(path, key_path);
+        Ok(())
+    }
+
+    /**
+     * 加密文件和密钥文件的位置
+     */
+    pub fn set_files(&self, path: PathBuf, key_path: PathBuf) {
+        *self.file.write().unwrap() = Some(EncryptedFile { path, key_path });
+        self.cache.write().unwrap().clear();
+    }
+
+    fn read_file(&self) -> Result<BTreeMap<String, String>, WallpaperError> {
+        match self.file.read().unwrap().as_ref() {
+            Some(file) => file.read(),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    fn update_file(&self, update: impl FnOnce(&mut BTreeMap<String, String>)) -> Result<(), WallpaperError> {
+        let file = self.file.read().unwrap();
+        let Some(file) = file.as_ref() else {
+            return Err(WallpaperError::WallpaperError("Secret store is not initialized".to_string()));
+        };
+        let mut secrets = file.read()?;
+        update(&mut secrets);
+        file.write(&secrets)
+    }
+
+    fn lookup(&self, source: &WallpaperSource) -> Option<(String, SecretBackend)> {
+        let provider = source.as_str();
+        if self.use_keyring {
+            match keyring_get(provider) {
+                Ok(Some(key)) => return Some((key, SecretBackend::Keyring)),
+                Ok(None) => {}
+                Err(e) => log::warn!("Keyring unavailable, using encrypted file: {}", e),
+            }
+        }
+        match self.read_file() {
+            Ok(secrets) => {
+                if let Some(key) = secrets.get(provider) {
+                    return Some((key.clone(), SecretBackend::File));
+                }
+            }
+            Err(e) => log::warn!("Failed to read secrets file: {}", e),
+        }
+        std::env::var(env_var(source))
+            .ok()
+            .filter(|key| !key.trim().is_empty())
+            .map(|key| (key.trim().to_string(), SecretBackend::Environment))
+    }
+
+    fn entry(&self, source: &WallpaperSource) -> Option<(String, SecretBackend)> {
+        if let Some(entry) = self.cache.read().unwrap().get(source.as_str()) {
+            return entry.clone();
+        }
+        let entry = self.lookup(source);
+        self.cache
+            .write()
+            .unwrap()
+            .insert(source.as_str().to_string(), entry.clone());
+        entry
+    }
+
+    pub fn get(&self, source: &WallpaperSource) -> Option<String> {
+        self.entry(source).map(|(key, _)| key)
+    }
+
+    /**
+     * 保存 Key，优先写入系统钥匙串，不可用时写入加密文件
+     */
+    pub fn set(&self, source: &WallpaperSource, key: &str) -> Result<SecretBackend, WallpaperError> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(WallpaperError::ApiError("API key is empty".to_string()));
+        }
+        let provider = source.as_str();
+
+        let mut backend = SecretBackend::File;
+        if self.use_keyring {
+            match keyring_set(provider, key) {
+                Ok(()) => backend = SecretBackend::Keyring,
+                Err(e) => log::warn!("Keyring unavailable, using encrypted file: {}", e),
+            }
+        }
+        if backend == SecretBackend::Keyring {
+            // 去掉之前写入加密文件的旧 Key
+            if let Err(e) = self.update_file(|secrets| {
+                secrets.remove(provider);
+            }) {
+                log::warn!("Failed to update secrets file: {}", e);
+            }
+        } else {
+            self.update_file(|secrets| {
+                secrets.insert(provider.to_string(), key.to_string());
+            })?;
+        }
+
+        self.cache
+            .write()
+            .unwrap()
+            .insert(provider.to_string(), Some((key.to_string(), backend)));
+        Ok(backend)
+    }
+
+    /**
+     * 从钥匙串和加密文件中删除 Key，环境变量中的 Key 不受影响
+     */
+    pub fn clear(&self, source: &WallpaperSource) -> Result<(), WallpaperError> {
+        let provider = source.as_str();
+        if self.use_keyring {
+            if let Err(e) = keyring_delete(provider) {
+                log::warn!("Failed to clear keyring entry: {}", e);
+            }
+        }
+        self.update_file(|secrets| {
+            secrets.remove(provider);
+        })?;
+        self.cache.write().unwrap().remove(provider);
+        Ok(())
+    }
+
+    pub fn status(&self, source: &WallpaperSource) -> KeyStatus {
+        let entry = self.entry(source);
+        KeyStatus {
+            source: source.clone(),
+            configured: entry.is_some(),
+            backend: entry.as_ref().map(|(_, backend)| *backend),
+            hint: entry.map(|(key, _)| {
+                let chars: Vec<char> = key.chars().collect();
+                chars[chars.len().saturating_sub(4)..].iter().collect()
+            }),
+        }
+    }
+}
+
+impl Default for SecretStore {
+    fn default() -> Self {
+        Self::new(keyring_supported())
+    }
+}
+
+static SECRETS: OnceLock<SecretStore> = OnceLock::new();
+
+pub fn get_secrets() -> &'static SecretStore {
+    SECRETS.get_or_init(SecretStore::default)
+}
+
+/**
+ * 来源请求使用的 API Key，只从安全存储读取
+ */
+pub fn api_key(source: &WallpaperSource) -> Option<String> {
+    get_secrets().get(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_file_store() {
+        let dir = std::env::temp_dir().join(format!("wallpaper-mate-secrets-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let secrets = SecretStore::new(false);
+        secrets.set_files(dir.join(SECRETS_FILE), dir.join(SECRETS_KEY_FILE));
+        assert!(secrets.get(&WallpaperSource::Pexels).is_none());
+        assert!(secrets.set(&WallpaperSource::Pexels, "  ").is_err());
+
+        let backend = secrets.set(&WallpaperSource::Pexels, "pexels-secret-1234").unwrap();
+        assert_eq!(backend, SecretBackend::File);
+        secrets.set(&WallpaperSource::Unsplash, "unsplash-key").unwrap();
+
+        // 文件中没有明文
+        let raw = fs::read(dir.join(SECRETS_FILE)).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("pexels-secret"));
+
+        // 重新加载后仍能解密
+        let reloaded = SecretStore::new(false);
+        reloaded.set_files(dir.join(SECRETS_FILE), dir.join(SECRETS_KEY_FILE));
+        assert_eq!(reloaded.get(&WallpaperSource::Pexels).as_deref(), Some("pexels-secret-1234"));
+        let status = reloaded.status(&WallpaperSource::Pexels);
+        assert_eq!((status.configured, status.hint.as_deref()), (true, Some("1234")));
+
+        reloaded.clear(&WallpaperSource::Pexels).unwrap();
+        assert!(reloaded.get(&WallpaperSource::Pexels).is_none());
+        assert_eq!(reloaded.get(&WallpaperSource::Unsplash).as_deref(), Some("unsplash-key"));
+
+        reloaded.clear(&WallpaperSource::Unsplash).unwrap();
+        assert!(!dir.join(SECRETS_FILE).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
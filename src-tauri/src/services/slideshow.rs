@@ -0,0 +1,374 @@
+use crate::services::{wallpaper, watcher};
+use crate::sources::local::{self, LocalCollection};
+use crate::types::WallpaperError;
+use chrono::Local;
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlideshowDesktop {
+    Gnome,
+    Kde,
+}
+
+impl SlideshowDesktop {
+    /**
+     * 根据 XDG_CURRENT_DESKTOP 判断当前桌面，无法判断时默认 GNOME
+     */
+    pub fn detect() -> Self {
+        if wallpaper::is_kde(&wallpaper::current_desktop()) {
+            SlideshowDesktop::Kde
+        } else {
+            SlideshowDesktop::Gnome
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SlideshowOptions {
+    pub name: String,
+    /// 为空时按当前桌面环境选择
+    pub desktop: Option<SlideshowDesktop>,
+    /// 每张壁纸显示的秒数
+    pub duration_seconds: u64,
+    /// 切换过渡的秒数，为 0 时不过渡（仅 GNOME）
+    pub transition_seconds: f64,
+    /// 写入后设置为当前桌面的壁纸
+    pub register: bool,
+}
+
+impl Default for SlideshowOptions {
+    fn default() -> Self {
+        Self {
+            name: "wallpaper-mate".to_string(),
+            desktop: None,
+            duration_seconds: 1800,
+            transition_seconds: 2.0,
+            register: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlideshowExport {
+    pub desktop: SlideshowDesktop,
+    /// GNOME 为 XML 文件，KDE 为图片目录
+    pub path: PathBuf,
+    pub count: usize,
+    pub registered: bool,
+}
+
+/**
+ * 名称只保留字母数字、- 和 _，用作文件名
+ */
+fn file_name(name: &str) -> String {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    if cleaned.is_empty() {
+        SlideshowOptions::default().name
+    } else {
+        cleaned
+    }
+}
+
+/**
+ * 把一组缓存壁纸导出为桌面原生幻灯片
+ */
+pub fn export(
+    output_dir: &Path,
+    images: &[PathBuf],
+    options: &SlideshowOptions,
+) -> Result<SlideshowExport, WallpaperError> {
+    if images.is_empty() {
+        return Err(WallpaperError::WallpaperError("No wallpapers selected".to_string()));
+    }
+    if let Some(missing) = images.iter().find(|path| !path.is_file()) {
+        return Err(WallpaperError::WallpaperError(format!(
+            "Wallpaper not found: {}",
+            missing.display()
+        )));
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let name = file_name(&options.name);
+    let desktop = options.desktop.unwrap_or_else(SlideshowDesktop::detect);
+
+    let path = match desktop {
+        SlideshowDesktop::Gnome => {
+            let path = output_dir.join(format!("{}.xml", name));
+            let xml = gnome_xml(images, options.duration_seconds, options.transition_seconds);
+            fs::write(&path, xml)?;
+            if options.register {
+                register_gnome(&name, &path)?;
+            }
+            path
+        }
+        SlideshowDesktop::Kde => {
+            let path = output_dir.join(&name);
+            write_kde_folder(&path, images)?;
+            if options.register {
+                wallpaper::kde_eval(&kde_script(&path, options.duration_seconds))?;
+            }
+            path
+        }
+    };
+
+    Ok(SlideshowExport {
+        desktop,
+        path,
+        count: images.len(),
+        registered: options.register,
+    })
+}
+
+/**
+ * GNOME 定时背景 XML：每张图片一个 <static>，相邻图片之间一个 <transition>，最后一张过渡回第一张
+ */
+pub fn gnome_xml(images: &[PathBuf], duration_seconds: u64, transition_seconds: f64) -> String {
+    let now = Local::now();
+    let mut xml = String::from("<background>\n");
+    xml.push_str(&format!(
+        "  <starttime>\n    <year>{}</year>\n    <month>{}</month>\n    <day>{}</day>\n    <hour>0</hour>\n    <minute>00</minute>\n    <second>00</second>\n  </starttime>\n",
+        now.format("%Y"),
+        now.format("%-m"),
+        now.format("%-d")
+    ));
+
+    let file = |path: &PathBuf| escape(path.to_string_lossy().to_string()).to_string();
+    for (i, image) in images.iter().enumerate() {
+        xml.push_str(&format!(
+            "  <static>\n    <duration>{}.0</duration>\n    <file>{}</file>\n  </static>\n",
+            duration_seconds.max(1),
+            file(image)
+        ));
+
+        if transition_seconds > 0.0 && images.len() > 1 {
+            let next = &images[(i + 1) % images.len()];
+            xml.push_str(&format!(
+                "  <transition type=\"overlay\">\n    <duration>{:.1}</duration>\n    <from>{}</from>\n    <to>{}</to>\n  </transition>\n",
+                transition_seconds,
+                file(image),
+                file(next)
+            ));
+        }
+    }
+
+    xml.push_str("</background>\n");
+    xml
+}
+
+fn gnome_properties_xml(name: &str, slideshow: &Path) -> String {
+    format!(
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE wallpapers SYSTEM \"gnome-wp-list.dtd\">\n<wallpapers>\n  <wallpaper deleted=\"false\">\n    <name>{}</name>\n    <filename>{}</filename>\n    <options>zoom</options>\n  </wallpaper>\n</wallpapers>\n",
+        escape(name),
+        escape(slideshow.to_string_lossy().to_string())
+    )
+}
+
+/**
+ * 在 GNOME 设置的背景列表中登记幻灯片，并设置为当前背景
+ */
+fn register_gnome(name: &str, slideshow: &Path) -> Result<(), WallpaperError> {
+    if let Some(data_dir) = dirs::data_dir() {
+        let properties_dir = data_dir.join("gnome-background-properties");
+        fs::create_dir_all(&properties_dir)?;
+        fs::write(
+            properties_dir.join(format!("{}.xml", name)),
+            gnome_properties_xml(name, slideshow),
+        )?;
+    }
+
+    wallpaper::gnome_set_background("picture-uri", slideshow)?;
+    wallpaper::gnome_set_background("picture-uri-dark", slideshow)?;
+    // 应用自己设置的幻灯片不算外部修改
+    watcher::get_watcher().acknowledge(&slideshow.to_string_lossy());
+    Ok(())
+}
+
+/**
+ * KDE 幻灯片插件读取目录，这里用符号链接（非 Unix 平台复制）组成一个新目录
+ */
+fn write_kde_folder(folder: &Path, images: &[PathBuf]) -> Result<(), WallpaperError> {
+    if folder.exists() {
+        fs::remove_dir_all(folder)?;
+    }
+    fs::create_dir_all(folder)?;
+
+    for (i, image) in images.iter().enumerate() {
+        let extension = image.extension().and_then(|e| e.to_str()).unwrap_or("jpg");
+        // 序号前缀保持导出时的顺序
+        let link = folder.join(format!("{:04}.{}", i + 1, extension));
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(image, &link)?;
+        #[cfg(not(unix))]
+        fs::copy(image, &link).map(|_| ())?;
+    }
+
+    Ok(())
+}
+
+fn kde_script(folder: &Path, duration_seconds: u64) -> String {
+    format!(
+        r#"
+for (const desktop of desktops()) {{
+    desktop.wallpaperPlugin = "org.kde.slideshow";
+    desktop.currentConfigGroup = ["Wallpaper", "org.kde.slideshow", "General"];
+    desktop.writeConfig("SlidePaths", {});
+    desktop.writeConfig("SlideInterval", {});
+}}"#,
+        serde_json::to_string(&folder.to_string_lossy()).unwrap_or_else(|_| "\"\"".to_string()),
+        duration_seconds.max(1)
+    )
+}
+
+/**
+ * 解析 GNOME 定时背景 XML 中的图片路径，保持出现顺序并去重
+ *
+ * 支持 <file> 直接写路径，以及 <file><size ...>路径</size></file> 的多分辨率写法（取第一个）
+ */
+pub fn parse_gnome_xml(content: &str) -> Result<Vec<PathBuf>, WallpaperError> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut static_done = false;
+    let mut images: Vec<PathBuf> = Vec::new();
+    let mut transitions: Vec<PathBuf> = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| WallpaperError::WallpaperError(format!("Invalid slideshow XML: {}", e)))?;
+        match event {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if name == "static" {
+                    static_done = false;
+                }
+                stack.push(name);
+                text.clear();
+            }
+            Event::Text(e) => {
+                text.push_str(&e.decode().unwrap_or_default());
+            }
+            Event::GeneralRef(e) => {
+                let entity = format!("&{};", e.decode().unwrap_or_default());
+                text.push_str(&unescape(&entity).unwrap_or_default());
+            }
+            Event::End(_) => {
+                let name = stack.pop().unwrap_or_default();
+                let parent = stack.iter().rev().find(|n| n.as_str() != "file");
+                let value = text.trim();
+                if !value.is_empty() {
+                    match (name.as_str(), parent.map(String::as_str)) {
+                        ("file" | "size", Some("static")) if !static_done => {
+                            images.push(PathBuf::from(value));
+                            static_done = true;
+                        }
+                        ("from" | "to", Some("transition")) => transitions.push(PathBuf::from(value)),
+                        _ => {}
+                    }
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    // 只有过渡而没有静态图片时，从过渡中取图片
+    if images.is_empty() {
+        images = transitions;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    images.retain(|path| seen.insert(path.clone()));
+    Ok(images)
+}
+
+/**
+ * 导入 GNOME 定时背景 XML，作为本地来源中的一个合集
+ */
+pub fn import_gnome(path: &Path, name: Option<String>) -> Result<LocalCollection, WallpaperError> {
+    let content = fs::read_to_string(path)?;
+    let images = parse_gnome_xml(&content)?;
+    if images.is_empty() {
+        return Err(WallpaperError::WallpaperError(
+            "No images found in slideshow".to_string(),
+        ));
+    }
+
+    let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "gnome-slideshow".to_string())
+    });
+
+    let collection = LocalCollection {
+        name,
+        origin: Some(path.to_string_lossy().to_string()),
+        wallpapers: images.iter().map(|image| local::local_info(image)).collect(),
+        imported_at: Local::now(),
+    };
+    local::get_local_library().upsert(collection.clone());
+    Ok(collection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gnome_xml_roundtrip() {
+        let images = vec![PathBuf::from("/tmp/a&b.jpg"), PathBuf::from("/tmp/c.jpg")];
+        let xml = gnome_xml(&images, 600, 2.5);
+        assert!(xml.contains("<file>/tmp/a&amp;b.jpg</file>"));
+        assert!(xml.contains("<duration>600.0</duration>"));
+        assert_eq!(xml.matches("<transition").count(), 2);
+
+        assert_eq!(parse_gnome_xml(&xml).unwrap(), images);
+        assert!(!gnome_xml(&images, 600, 0.0).contains("<transition"));
+    }
+
+    #[test]
+    fn test_parse_gnome_xml_sizes() {
+        let xml = r#"<?xml version="1.0"?>
+<background>
+  <starttime><year>2011</year><month>11</month><day>24</day><hour>7</hour><minute>00</minute><second>00</second></starttime>
+  <static>
+    <duration>3600.0</duration>
+    <file>
+      <size width="1920" height="1080">/usr/share/backgrounds/day-1920.jpg</size>
+      <size width="3840" height="2160">/usr/share/backgrounds/day-3840.jpg</size>
+    </file>
+  </static>
+  <transition type="overlay">
+    <duration>60.0</duration>
+    <from>/usr/share/backgrounds/day-1920.jpg</from>
+    <to>/usr/share/backgrounds/night.jpg</to>
+  </transition>
+  <static>
+    <duration>3600.0</duration>
+    <file>/usr/share/backgrounds/night.jpg</file>
+  </static>
+</background>"#;
+        assert_eq!(
+            parse_gnome_xml(xml).unwrap(),
+            vec![
+                PathBuf::from("/usr/share/backgrounds/day-1920.jpg"),
+                PathBuf::from("/usr/share/backgrounds/night.jpg"),
+            ]
+        );
+    }
+}
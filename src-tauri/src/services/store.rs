@@ -0,0 +1,62 @@
+use crate::types::WallpaperError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/**
+ * 应用数据目录下的持久化文件路径
+ */
+pub fn data_file<R: tauri::Runtime>(app: &impl Manager<R>, name: &str) -> Result<PathBuf, WallpaperError> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| WallpaperError::ApiError(e.to_string()))?;
+
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir)?;
+    }
+
+    Ok(data_dir.join(name))
+}
+
+/**
+ * 读取 JSON 文件，文件不存在或损坏时返回 None
+ */
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("Ignoring corrupted {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/**
+ * 先写临时文件再重命名，避免写入中断导致文件损坏
+ */
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), WallpaperError> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_json() {
+        let path = std::env::temp_dir().join(format!("wm-store-{}.json", std::process::id()));
+        save_json(&path, &vec![1, 2, 3]).unwrap();
+        assert_eq!(load_json::<Vec<i32>>(&path), Some(vec![1, 2, 3]));
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(load_json::<Vec<i32>>(&path), None);
+        fs::remove_file(&path).unwrap();
+    }
+}
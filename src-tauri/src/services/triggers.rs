@@ -0,0 +1,389 @@
+use crate::services::history::ApplyTrigger;
+use crate::services::{offline, playlist, scheduler, store};
+use crate::types::WallpaperError;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+const TRIGGERS_FILE: &str = "triggers.json";
+// 检查时钟跳变和网络状态的间隔
+const CHECK_SECONDS: u64 = 30;
+// 启动后等待网络就绪和前端同步配置
+const STARTUP_DELAY_SECONDS: u64 = 5;
+// 唤醒后等待网络重新连接
+const WAKE_DELAY_SECONDS: u64 = 10;
+// 两种唤醒检测可能同时触发，间隔内只切换一次
+const WAKE_DEBOUNCE_SECONDS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TriggerConfig {
+    /// 应用启动时切换壁纸
+    pub on_startup: bool,
+    /// 从睡眠中唤醒后切换壁纸
+    pub on_wake: bool,
+    /// 定时切换因网络失败时，网络恢复后立即重试
+    pub retry_on_reconnect: bool,
+    /// 墙上时钟比单调时钟多走超过该秒数时视为唤醒
+    pub wake_threshold_seconds: u64,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self {
+            on_startup: false,
+            on_wake: false,
+            retry_on_reconnect: true,
+            wake_threshold_seconds: 120,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RetryState {
+    /// 有一次定时切换因网络失败
+    pending: bool,
+    /// 失败后检测到过断网
+    saw_offline: bool,
+    /// 超过一个切换间隔后不再重试，交给之后的定时切换
+    expires_at: Option<Instant>,
+}
+
+impl RetryState {
+    fn is_pending(&self, now: Instant) -> bool {
+        self.pending && self.expires_at.map_or(true, |at| now < at)
+    }
+}
+
+pub struct TriggerManager {
+    config: RwLock<TriggerConfig>,
+    retry: Mutex<RetryState>,
+    last_wake: Mutex<Option<SystemTime>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl TriggerManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(TriggerConfig::default()),
+            retry: Mutex::new(RetryState::default()),
+            last_wake: Mutex::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载触发器配置，启动时切换需要在前端同步配置之前就能读到
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, TRIGGERS_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: TriggerConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save trigger config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> TriggerConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 记录一次失败的定时切换，只有网络错误才会在网络恢复后重试
+     *
+     * interval 为切换间隔，超过一个间隔还没重试时放弃
+     */
+    pub fn record_failure(&self, error: &WallpaperError, interval: Duration) {
+        if is_network_error(error) {
+            *self.retry.lock().unwrap() = RetryState {
+                pending: true,
+                saw_offline: false,
+                expires_at: Some(Instant::now() + interval),
+            };
+        }
+    }
+
+    /**
+     * 任意一次切换成功后不再需要重试
+     */
+    pub fn record_success(&self) {
+        *self.retry.lock().unwrap() = RetryState::default();
+    }
+
+    /**
+     * 根据网络探测结果判断是否该重试：失败后经历过断网并且现在已恢复
+     */
+    fn observe_connectivity(&self, online: bool) -> bool {
+        let mut retry = self.retry.lock().unwrap();
+        if !retry.is_pending(Instant::now()) {
+            return false;
+        }
+        if !online {
+            retry.saw_offline = true;
+            return false;
+        }
+        if retry.saw_offline {
+            *retry = RetryState::default();
+            return true;
+        }
+        false
+    }
+
+    fn retry_pending(&self) -> bool {
+        let mut retry = self.retry.lock().unwrap();
+        if retry.pending && !retry.is_pending(Instant::now()) {
+            *retry = RetryState::default();
+        }
+        retry.pending
+    }
+
+    /**
+     * 唤醒去重，返回 true 表示这次唤醒需要处理
+     */
+    fn claim_wake(&self, now: SystemTime) -> bool {
+        let mut last_wake = self.last_wake.lock().unwrap();
+        let recent = last_wake.is_some_and(|last| {
+            now.duration_since(last)
+                .map_or(true, |elapsed| elapsed < Duration::from_secs(WAKE_DEBOUNCE_SECONDS))
+        });
+        if recent {
+            return false;
+        }
+        *last_wake = Some(now);
+        true
+    }
+}
+
+impl Default for TriggerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static TRIGGERS: OnceLock<TriggerManager> = OnceLock::new();
+
+pub fn get_triggers() -> &'static TriggerManager {
+    TRIGGERS.get_or_init(TriggerManager::new)
+}
+
+fn is_network_error(error: &WallpaperError) -> bool {
+    if let WallpaperError::NetworkError(_) = error {
+        return true;
+    }
+    let message = error.to_string().to_lowercase();
+    ["network error", "error sending request", "dns error", "connection refused", "timed out"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/**
+ * 睡眠期间单调时钟停止而墙上时钟继续走，两者差值超过阈值说明刚刚唤醒
+ */
+fn is_resume(wall_elapsed: Duration, mono_elapsed: Duration, threshold: Duration) -> bool {
+    wall_elapsed > mono_elapsed + threshold
+}
+
+/**
+ * 解析 gdbus monitor 输出中的 PrepareForSleep 信号，false 表示唤醒
+ */
+fn parse_prepare_for_sleep(line: &str) -> Option<bool> {
+    let (_, args) = line.split_once("PrepareForSleep")?;
+    if args.contains("true") {
+        Some(true)
+    } else if args.contains("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/**
+ * 当前生效的切换间隔：播放列表、启用了定时切换的来源，都没有时使用默认间隔
+ */
+fn switch_interval() -> Duration {
+    let config = playlist::get_playlist().get_config();
+    let seconds = if config.is_active() {
+        config.interval_seconds
+    } else {
+        scheduler::get_scheduler()
+            .active_source()
+            .map(|(_, config)| config.interval_seconds)
+            .unwrap_or_else(|| scheduler::AutoSwitchConfig::default().interval_seconds)
+    };
+    Duration::from_secs(seconds)
+}
+
+async fn run_trigger(app: &AppHandle, trigger: ApplyTrigger) {
+    log::info!("Switching wallpaper on {:?}", trigger);
+    let _ = app.emit("wallpaper-trigger", trigger);
+    if let Err(e) = scheduler::switch_now(app, trigger).await {
+        log::warn!("Triggered switch ({:?}) failed: {}", trigger, e);
+        get_triggers().record_failure(&e, switch_interval());
+    }
+}
+
+fn on_wake(app: &AppHandle) {
+    let triggers = get_triggers();
+    if !triggers.get_config().on_wake || scheduler::get_scheduler().is_paused() {
+        return;
+    }
+    if !triggers.claim_wake(SystemTime::now()) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(WAKE_DELAY_SECONDS)).await;
+        run_trigger(&app, ApplyTrigger::Wake).await;
+    });
+}
+
+/**
+ * 监听 logind 的 PrepareForSleep 信号，gdbus 不可用时只依赖时钟跳变检测
+ */
+fn spawn_logind_listener(app: AppHandle) {
+    std::thread::spawn(move || {
+        let child = Command::new("gdbus")
+            .args([
+                "monitor",
+                "--system",
+                "--dest",
+                "org.freedesktop.login1",
+                "--object-path",
+                "/org/freedesktop/login1",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                log::info!("logind sleep monitor unavailable: {}", e);
+                return;
+            }
+        };
+
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if parse_prepare_for_sleep(&line) == Some(false) {
+                    on_wake(&app);
+                }
+            }
+        }
+        let _ = child.wait();
+    });
+}
+
+/**
+ * 启动触发器：启动时切换、唤醒检测和网络恢复重试
+ */
+pub fn spawn_triggers(app: AppHandle) {
+    if get_triggers().get_config().on_startup {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECONDS)).await;
+            run_trigger(&app, ApplyTrigger::Startup).await;
+        });
+    }
+
+    if cfg!(target_os = "linux") {
+        spawn_logind_listener(app.clone());
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut wall = SystemTime::now();
+        let mut mono = Instant::now();
+        loop {
+            tokio::time::sleep(Duration::from_secs(CHECK_SECONDS)).await;
+
+            let config = get_triggers().get_config();
+            let wall_elapsed = SystemTime::now().duration_since(wall).unwrap_or_default();
+            let threshold = Duration::from_secs(config.wake_threshold_seconds);
+            if is_resume(wall_elapsed, mono.elapsed(), threshold) {
+                log::info!("Detected resume from sleep");
+                on_wake(&app);
+            }
+            wall = SystemTime::now();
+            mono = Instant::now();
+
+            if config.retry_on_reconnect && get_triggers().retry_pending() {
+                let online = offline::is_online().await;
+                if get_triggers().observe_connectivity(online) {
+                    run_trigger(&app, ApplyTrigger::Reconnect).await;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_detection() {
+        let threshold = Duration::from_secs(120);
+        assert!(!is_resume(Duration::from_secs(30), Duration::from_secs(30), threshold));
+        assert!(!is_resume(Duration::from_secs(100), Duration::from_secs(30), threshold));
+        assert!(is_resume(Duration::from_secs(3600), Duration::from_secs(30), threshold));
+
+        assert_eq!(
+            parse_prepare_for_sleep("/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)"),
+            Some(false)
+        );
+        assert_eq!(
+            parse_prepare_for_sleep("/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)"),
+            Some(true)
+        );
+        assert_eq!(parse_prepare_for_sleep("/org/freedesktop/login1: SessionNew ('3', '/x')"), None);
+
+        let triggers = TriggerManager::new();
+        let now = SystemTime::now();
+        assert!(triggers.claim_wake(now));
+        assert!(!triggers.claim_wake(now + Duration::from_secs(10)));
+        assert!(triggers.claim_wake(now + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_retry_after_reconnect() {
+        let triggers = TriggerManager::new();
+        assert!(!triggers.observe_connectivity(true));
+
+        let interval = Duration::from_secs(3600);
+        let network_error = WallpaperError::ApiError(
+            "API error: error sending request for url (https://wallhaven.cc/api/v1/search)".to_string(),
+        );
+        triggers.record_failure(&WallpaperError::ApiError("No wallpapers found".to_string()), interval);
+        assert!(!triggers.retry_pending());
+
+        triggers.record_failure(&network_error, interval);
+        // 失败后网络一直正常，交给下一次定时切换
+        assert!(!triggers.observe_connectivity(true));
+        assert!(!triggers.observe_connectivity(false));
+        assert!(triggers.observe_connectivity(true));
+        assert!(!triggers.retry_pending());
+
+        // 之后的切换成功时放弃重试
+        triggers.record_failure(&network_error, interval);
+        triggers.record_success();
+        assert!(!triggers.retry_pending());
+
+        // 超过一个间隔后过期
+        triggers.record_failure(&network_error, Duration::ZERO);
+        assert!(!triggers.observe_connectivity(false));
+        assert!(!triggers.retry_pending());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::types::WallpaperError;
 use std::path::Path;
+use std::process::Command;
 
 pub fn set_wallpaper(path: &Path) -> Result<(), WallpaperError> {
     wallpaper::set_from_path(path.to_str().unwrap())
@@ -11,6 +12,52 @@ pub fn get_current_wallpaper() -> Result<String, WallpaperError> {
         .map_err(|e| WallpaperError::WallpaperError(e.to_string()))
 }
 
+/**
+ * 执行系统命令并返回标准输出，非零退出码视为错误
+ */
+pub fn command_output(program: &str, args: &[&str]) -> Result<String, WallpaperError> {
+    let output = Command::new(program).args(args).output()?;
+    if !output.status.success() {
+        return Err(WallpaperError::WallpaperError(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn current_desktop() -> String {
+    std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default()
+}
+
+pub fn is_gnome(desktop: &str) -> bool {
+    desktop.contains("GNOME") || desktop == "Unity" || desktop == "Pantheon"
+}
+
+pub fn is_kde(desktop: &str) -> bool {
+    desktop.contains("KDE")
+}
+
+/**
+ * 在 Plasma 中执行桌面脚本
+ */
+pub fn kde_eval(script: &str) -> Result<String, WallpaperError> {
+    command_output(
+        "qdbus",
+        &["org.kde.plasmashell", "/PlasmaShell", "org.kde.PlasmaShell.evaluateScript", script],
+    )
+}
+
+/**
+ * 设置 GNOME 背景的 picture-uri / picture-uri-dark 等键
+ */
+pub fn gnome_set_background(key: &str, path: &Path) -> Result<(), WallpaperError> {
+    let uri = format!("'file://{}'", path.to_string_lossy().replace('\'', "\\'"));
+    command_output("gsettings", &["set", "org.gnome.desktop.background", key, &uri]).map(|_| ())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,230 @@
+use crate::services::history::{self, ApplyTrigger};
+use crate::services::{apply, cache, scheduler, store, wallpaper};
+use crate::sources::local;
+use crate::types::{WallpaperError, WallpaperInfo};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const WATCHER_FILE: &str = "watcher.json";
+// 渲染文件名末尾的 "-{signature:016x}"
+const RENDER_SUFFIX_LEN: usize = 17;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatcherConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    /// 检测到外部修改后暂停自动切换
+    pub pause_auto_switch: bool,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: 10,
+            pause_auto_switch: false,
+        }
+    }
+}
+
+pub struct WallpaperWatcher {
+    config: RwLock<WatcherConfig>,
+    /// 上次检测到的系统壁纸路径
+    last_seen: RwLock<Option<String>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl WallpaperWatcher {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(WatcherConfig::default()),
+            last_seen: RwLock::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载监视配置，需要在监视任务启动前调用
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, WATCHER_FILE)?;
+        if let Some(config) = store::load_json(&path) {
+            *self.config.write().unwrap() = config;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: WatcherConfig) {
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save watcher config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> WatcherConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 应用以其他方式（如恢复原始壁纸）修改壁纸后调用，避免被当作外部修改
+     */
+    pub fn acknowledge(&self, path: &str) {
+        *self.last_seen.write().unwrap() = Some(normalize_path(path));
+    }
+
+    /**
+     * 记录本次检测到的路径，返回是否为外部修改
+     *
+     * 首次检测只作为基准，与应用最近设置的路径相同时不算外部修改
+     */
+    fn observe(&self, path: &str, applied: Option<&Path>) -> bool {
+        let mut last_seen = self.last_seen.write().unwrap();
+        if last_seen.as_deref() == Some(path) {
+            return false;
+        }
+
+        let first = last_seen.is_none();
+        *last_seen = Some(path.to_string());
+        !first && applied.map_or(true, |applied| !same_path(applied, path))
+    }
+}
+
+impl Default for WallpaperWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static WATCHER: OnceLock<WallpaperWatcher> = OnceLock::new();
+
+pub fn get_watcher() -> &'static WallpaperWatcher {
+    WATCHER.get_or_init(WallpaperWatcher::new)
+}
+
+/**
+ * 系统返回的路径可能带有 file:// 前缀或引号
+ */
+pub fn normalize_path(raw: &str) -> String {
+    let path = raw.trim().trim_matches(|c| c == '\'' || c == '"');
+    path.strip_prefix("file://").unwrap_or(path).to_string()
+}
+
+fn same_path(a: &Path, b: &str) -> bool {
+    a == Path::new(b)
+}
+
+/**
+ * 把系统壁纸路径映射回缓存中的壁纸信息
+ *
+ * 依次匹配当前壁纸、叠加层渲染副本和缓存原图，不是由应用设置的壁纸返回 None
+ */
+pub fn resolve(app: &AppHandle, system_path: &str) -> Option<WallpaperInfo> {
+    let path = normalize_path(system_path);
+
+    if let (Some(info), Some(applied)) = (apply::current_wallpaper(), apply::applied_path()) {
+        if same_path(&applied, &path) || info.local_path.as_deref().is_some_and(|p| same_path(p, &path)) {
+            return Some(info);
+        }
+    }
+
+    let cache_dir = cache::get_cache_dir(app).ok()?;
+    let path = PathBuf::from(path);
+    let relative = path.strip_prefix(&cache_dir).ok()?;
+
+    if relative.starts_with("rendered") {
+        let stem = path.file_stem()?.to_string_lossy().to_string();
+        let id = stem.get(..stem.len().checked_sub(RENDER_SUFFIX_LEN)?)?;
+        return find_original(&cache_dir, id).and_then(|original| cache::load_metadata(&original));
+    }
+
+    cache::load_metadata(&path)
+}
+
+fn find_original(cache_dir: &Path, id: &str) -> Option<PathBuf> {
+    let file_name = format!("{}.jpg", id);
+    std::fs::read_dir(cache_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.exists())
+}
+
+/**
+ * 外部设置的壁纸，无法映射时作为本地文件记录
+ */
+fn external_info(app: &AppHandle, path: &str) -> WallpaperInfo {
+    resolve(app, path).unwrap_or_else(|| local::local_info(Path::new(&normalize_path(path))))
+}
+
+async fn read_system_wallpaper() -> Option<String> {
+    tauri::async_runtime::spawn_blocking(wallpaper::get_current_wallpaper)
+        .await
+        .ok()?
+        .ok()
+        .map(|path| normalize_path(&path))
+        .filter(|path| !path.is_empty())
+}
+
+/**
+ * 定时检测系统壁纸是否被外部修改
+ */
+pub fn spawn_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = get_watcher().get_config();
+            tokio::time::sleep(Duration::from_secs(config.interval_seconds.max(1))).await;
+            if !config.enabled {
+                continue;
+            }
+
+            let Some(path) = read_system_wallpaper().await else {
+                continue;
+            };
+            if !get_watcher().observe(&path, apply::applied_path().as_deref()) {
+                continue;
+            }
+
+            log::info!("Wallpaper changed externally: {}", path);
+            let info = external_info(&app, &path);
+            apply::clear_current();
+            let entry = history::get_history().record(&info, ApplyTrigger::External);
+            let _ = app.emit("wallpaper-changed-externally", &entry);
+
+            if config.pause_auto_switch && !scheduler::get_scheduler().is_paused() {
+                scheduler::get_scheduler().set_paused(true);
+                let _ = app.emit("auto-switch-paused", true);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("'file:///home/me/a.jpg'\n"), "/home/me/a.jpg");
+        assert_eq!(normalize_path("/Users/me/b.png"), "/Users/me/b.png");
+    }
+
+    #[test]
+    fn test_observe_external_change() {
+        let watcher = WallpaperWatcher::new();
+        let applied = PathBuf::from("/cache/bing/a.jpg");
+
+        // 首次检测只作为基准
+        assert!(!watcher.observe("/usr/share/default.jpg", None));
+        assert!(!watcher.observe("/cache/bing/a.jpg", Some(&applied)));
+        assert!(!watcher.observe("/cache/bing/a.jpg", Some(&applied)));
+        assert!(watcher.observe("/home/me/mine.jpg", Some(&applied)));
+        assert!(!watcher.observe("/home/me/mine.jpg", Some(&applied)));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::services::http;
+use crate::sources::ratelimit;
 use crate::types::{WallpaperInfo, WallpaperSource};
 use serde::Deserialize;
 
@@ -16,8 +18,9 @@ struct BingImage {
     copyright: String,
 }
 
-pub async fn fetch_wallpapers() -> Result<Vec<WallpaperInfo>, reqwest::Error> {
-    let response = reqwest::get(BING_API_URL).await?;
+pub async fn fetch_wallpapers() -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    let request = http::client().get(BING_API_URL);
+    let response = ratelimit::send(&WallpaperSource::Bing, request).await?;
     let bing_response: BingResponse = response.json().await?;
 
     let wallpapers = bing_response
@@ -32,6 +35,8 @@ pub async fn fetch_wallpapers() -> Result<Vec<WallpaperInfo>, reqwest::Error> {
                 source: WallpaperSource::Bing,
                 local_path: None,
                 cached: false,
+                author: None,
+                attribution: None,
             }
         })
         .collect();
@@ -39,7 +44,7 @@ pub async fn fetch_wallpapers() -> Result<Vec<WallpaperInfo>, reqwest::Error> {
     Ok(wallpapers)
 }
 
-pub async fn fetch_wallpapers_as_list(page: u32) -> Result<crate::types::PaginatedResponse<crate::types::WallpaperListItem>, reqwest::Error> {
+pub async fn fetch_wallpapers_as_list(page: u32) -> Result<crate::types::PaginatedResponse<crate::types::WallpaperListItem>, Box<dyn std::error::Error + Send + Sync>> {
     use crate::types::{PaginatedResponse, WallpaperListItem, WallpaperSource};
 
     // Bing API 只返回最近 8 天的壁纸，idx 参数表示偏移量
@@ -55,10 +60,12 @@ pub async fn fetch_wallpapers_as_list(page: u32) -> Result<crate::types::Paginat
             last_page: 1,
             per_page: 8,
             total: 0,
+            seed: None,
         });
     };
 
-    let response = reqwest::get(&url).await?;
+    let request = http::client().get(&url);
+    let response = ratelimit::send(&WallpaperSource::Bing, request).await?;
     let bing_response: BingResponse = response.json().await?;
 
     let wallpapers: Vec<WallpaperListItem> = bing_response
@@ -72,6 +79,8 @@ pub async fn fetch_wallpapers_as_list(page: u32) -> Result<crate::types::Paginat
                 url: full_url.clone(),
                 source: WallpaperSource::Bing,
                 thumb_url: full_url,
+                author: None,
+                attribution: None,
             }
         })
         .collect();
@@ -86,6 +95,7 @@ pub async fn fetch_wallpapers_as_list(page: u32) -> Result<crate::types::Paginat
         last_page,
         per_page: 8,
         total,
+        seed: None,
     })
 }
 
@@ -0,0 +1,913 @@
+use crate::services::overlay::parse_color;
+use crate::services::store;
+use crate::sources::local::stable_hash;
+use crate::types::{WallpaperError, WallpaperInfo, WallpaperSource};
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+const GENERATOR_FILE: &str = "generator.json";
+const URL_SCHEME: &str = "generated://";
+const FALLBACK_SIZE: (u32, u32) = (1920, 1080);
+// 单边的最大像素数，避免 url 或配置中的尺寸分配过大的画布
+const MAX_SIZE: u32 = 16384;
+// 总像素数上限，浮点画布每像素 12 字节，8192x8192 约 800 MB
+const MAX_PIXELS: u64 = 8192 * 8192;
+// 缓存目录中保留的生成图片数量，未固定种子时每次切换都会生成新文件
+const MAX_CACHED: usize = 32;
+// 每次随机获取时生成的候选数量
+const BATCH_SIZE: usize = 4;
+
+/**
+ * 内置调色板，名称对应配置中的 palette
+ */
+const PALETTES: &[(&str, &[&str])] = &[
+    ("sunset", &["#2D1B45", "#6B2D5C", "#F0545C", "#F79D5C", "#FCD581"]),
+    ("ocean", &["#03045E", "#0077B6", "#00B4D8", "#90E0EF", "#CAF0F8"]),
+    ("forest", &["#1B2F1E", "#2D4A2B", "#5B7F3A", "#A3B86C", "#E8E3B5"]),
+    ("pastel", &["#FFCAD4", "#F4ACB7", "#B5D6E0", "#C7CEEA", "#E2F0CB"]),
+    ("neon", &["#0D0221", "#261447", "#FF3864", "#2DE2E6", "#F6019D"]),
+    ("mono", &["#0F0F0F", "#3A3A3A", "#6E6E6E", "#A8A8A8", "#E4E4E4"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GeneratorStyle {
+    Gradient,
+    MeshGradient,
+    FlowField,
+    LowPoly,
+    Geometric,
+}
+
+impl GeneratorStyle {
+    pub const ALL: [GeneratorStyle; 5] = [
+        GeneratorStyle::Gradient,
+        GeneratorStyle::MeshGradient,
+        GeneratorStyle::FlowField,
+        GeneratorStyle::LowPoly,
+        GeneratorStyle::Geometric,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GeneratorStyle::Gradient => "gradient",
+            GeneratorStyle::MeshGradient => "mesh-gradient",
+            GeneratorStyle::FlowField => "flow-field",
+            GeneratorStyle::LowPoly => "low-poly",
+            GeneratorStyle::Geometric => "geometric",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|style| style.as_str() == s)
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            GeneratorStyle::Gradient => "Gradient",
+            GeneratorStyle::MeshGradient => "Mesh gradient",
+            GeneratorStyle::FlowField => "Flow field",
+            GeneratorStyle::LowPoly => "Low poly",
+            GeneratorStyle::Geometric => "Geometric",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GeneratorConfig {
+    /// 可选的风格，为空时使用全部风格
+    pub styles: Vec<GeneratorStyle>,
+    /// 内置调色板名称、"random" 或 "custom"
+    pub palette: String,
+    /// palette 为 "custom" 时使用的颜色
+    pub custom_colors: Vec<String>,
+    /// 为 0 时使用屏幕分辨率，单边最大 16384，总像素数超过 8192x8192 时等比缩小
+    pub width: u32,
+    pub height: u32,
+    /// 固定种子，相同的种子总是生成相同的图片
+    pub seed: Option<u64>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            styles: Vec::new(),
+            palette: "random".to_string(),
+            custom_colors: Vec::new(),
+            width: 0,
+            height: 0,
+            seed: None,
+        }
+    }
+}
+
+/**
+ * 限制单边和总像素数，超出时保持宽高比缩小
+ */
+fn clamp_size(width: u32, height: u32) -> (u32, u32) {
+    let (width, height) = (width.min(MAX_SIZE), height.min(MAX_SIZE));
+    let pixels = width as u64 * height as u64;
+    if pixels <= MAX_PIXELS {
+        return (width, height);
+    }
+    let factor = (MAX_PIXELS as f64 / pixels as f64).sqrt();
+    (
+        ((width as f64 * factor) as u32).max(1),
+        ((height as f64 * factor) as u32).max(1),
+    )
+}
+
+pub struct GeneratorManager {
+    config: RwLock<GeneratorConfig>,
+    screen_size: RwLock<Option<(u32, u32)>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl GeneratorManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(GeneratorConfig::default()),
+            screen_size: RwLock::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /**
+     * 加载生成器配置，定时切换在前端同步配置之前也能使用用户的风格和调色板
+     */
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, GENERATOR_FILE)?;
+        if let Some(config) = store::load_json::<GeneratorConfig>(&path) {
+            let (width, height) = clamp_size(config.width, config.height);
+            *self.config.write().unwrap() = GeneratorConfig { width, height, ..config };
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn set_config(&self, mut config: GeneratorConfig) {
+        (config.width, config.height) = clamp_size(config.width, config.height);
+        *self.config.write().unwrap() = config;
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            if let Err(e) = store::save_json(path, &*self.config.read().unwrap()) {
+                log::warn!("Failed to save generator config: {}", e);
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> GeneratorConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /**
+     * 启动时记录主显示器的物理分辨率
+     */
+    pub fn set_screen_size(&self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            *self.screen_size.write().unwrap() = Some((width, height));
+        }
+    }
+
+    fn resolution(&self, config: &GeneratorConfig) -> (u32, u32) {
+        let (width, height) = if config.width > 0 && config.height > 0 {
+            (config.width, config.height)
+        } else {
+            self.screen_size.read().unwrap().unwrap_or(FALLBACK_SIZE)
+        };
+        clamp_size(width, height)
+    }
+}
+
+impl Default for GeneratorManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static GENERATOR: OnceLock<GeneratorManager> = OnceLock::new();
+
+pub fn get_generator() -> &'static GeneratorManager {
+    GENERATOR.get_or_init(GeneratorManager::new)
+}
+
+/**
+ * 生成一张图片所需的全部参数，编码在 WallpaperInfo.url 中，缓存删除后可以原样重新生成
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorParams {
+    pub style: GeneratorStyle,
+    /// 调色板名称或以逗号分隔的十六进制颜色
+    pub palette: String,
+    pub seed: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl GeneratorParams {
+    pub fn to_url(&self) -> String {
+        format!(
+            "{}{}?palette={}&seed={}&width={}&height={}",
+            URL_SCHEME,
+            self.style.as_str(),
+            self.palette,
+            self.seed,
+            self.width,
+            self.height
+        )
+    }
+
+    pub fn from_url(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix(URL_SCHEME)?;
+        let (style, query) = rest.split_once('?')?;
+        let mut params = GeneratorParams {
+            style: GeneratorStyle::parse(style)?,
+            palette: "random".to_string(),
+            seed: 0,
+            width: FALLBACK_SIZE.0,
+            height: FALLBACK_SIZE.1,
+        };
+
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=')?;
+            match key {
+                "palette" => params.palette = value.to_string(),
+                "seed" => params.seed = value.parse().ok()?,
+                "width" => params.width = value.parse().ok()?,
+                "height" => params.height = value.parse().ok()?,
+                _ => {}
+            }
+        }
+
+        if params.width == 0 || params.height == 0 {
+            return None;
+        }
+        (params.width, params.height) = clamp_size(params.width, params.height);
+        Some(params)
+    }
+
+    fn id(&self) -> String {
+        format!(
+            "{}-{:016x}-{}x{}",
+            self.style.as_str(),
+            self.seed ^ stable_hash(&self.palette),
+            self.width,
+            self.height
+        )
+    }
+
+    fn title(&self) -> String {
+        let palette = if self.palette.contains(',') { "custom" } else { &self.palette };
+        format!("{} · {} #{:x}", self.style.label(), palette, self.seed)
+    }
+}
+
+/**
+ * 按配置生成候选壁纸，只生成描述信息，图片在缓存时才渲染
+ */
+pub async fn fetch_wallpapers(
+    config: Option<GeneratorConfig>,
+) -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    let config = config.unwrap_or_else(|| get_generator().get_config());
+    let (width, height) = get_generator().resolution(&config);
+
+    let palette = if config.palette == "custom" {
+        let colors = config
+            .custom_colors
+            .iter()
+            .map(|c| {
+                parse_color(c)
+                    .map(|rgba| format!("{:02x}{:02x}{:02x}", rgba[0], rgba[1], rgba[2]))
+                    .ok_or_else(|| format!("Invalid color: {}", c))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if colors.len() < 2 {
+            return Err("Custom palette needs at least two colors".into());
+        }
+        colors.join(",")
+    } else {
+        config.palette.clone()
+    };
+
+    let styles = if config.styles.is_empty() {
+        GeneratorStyle::ALL.to_vec()
+    } else {
+        config.styles.clone()
+    };
+
+    // 固定种子时只生成一张，保证结果可复现
+    let seeds: Vec<u64> = match config.seed {
+        Some(seed) => vec![seed],
+        None => (0..BATCH_SIZE).map(|_| rand::random::<u64>()).collect(),
+    };
+
+    let wallpapers = seeds
+        .into_iter()
+        .map(|seed| {
+            let params = GeneratorParams {
+                style: styles[(seed % styles.len() as u64) as usize],
+                palette: palette.clone(),
+                seed,
+                width,
+                height,
+            };
+            WallpaperInfo {
+                id: params.id(),
+                title: params.title(),
+                url: params.to_url(),
+                source: WallpaperSource::Generated,
+                local_path: None,
+                cached: false,
+                author: None,
+                attribution: None,
+            }
+        })
+        .collect();
+
+    Ok(wallpapers)
+}
+
+/**
+ * 按 url 中的参数渲染图片并保存
+ */
+pub fn render_to_file(url: &str, output: &Path) -> Result<(), WallpaperError> {
+    let params = GeneratorParams::from_url(url)
+        .ok_or_else(|| WallpaperError::ApiError(format!("Invalid generator url: {}", url)))?;
+    render(&params)?
+        .save_with_format(output, image::ImageFormat::Jpeg)
+        .map_err(|e| WallpaperError::ApiError(format!("Failed to encode image: {}", e)))
+}
+
+/**
+ * 只保留最近生成的 MAX_CACHED 张图片及其元数据，删除的图片需要时可按 url 重新生成
+ */
+pub fn prune_cache(dir: &Path, keep: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut images: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jpg") && path != keep)
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .collect();
+    // keep 本身占一个名额
+    if images.len() < MAX_CACHED {
+        return;
+    }
+    images.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in images.into_iter().skip(MAX_CACHED - 1) {
+        let _ = fs::remove_file(path.with_extension("json"));
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("Failed to remove generated image {}: {}", path.display(), e);
+        }
+    }
+}
+
+pub fn render(params: &GeneratorParams) -> Result<RgbImage, WallpaperError> {
+    let mut rng = SplitMix64::new(params.seed);
+    let palette = resolve_palette(&params.palette, &mut rng);
+    let mut canvas = Canvas::new(params.width, params.height)?;
+
+    match params.style {
+        GeneratorStyle::Gradient => draw_gradient(&mut canvas, &palette, &mut rng),
+        GeneratorStyle::MeshGradient => draw_mesh_gradient(&mut canvas, &palette, &mut rng),
+        GeneratorStyle::FlowField => draw_flow_field(&mut canvas, &palette, &mut rng),
+        GeneratorStyle::LowPoly => draw_low_poly(&mut canvas, &palette, &mut rng),
+        GeneratorStyle::Geometric => draw_geometric(&mut canvas, &palette, &mut rng),
+    }
+
+    Ok(canvas.into_image(&mut rng))
+}
+
+type Color = [f32; 3];
+
+/**
+ * 可复现的伪随机数生成器，不依赖 rand 版本的实现细节
+ */
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 区间的浮点数
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len.max(1) as u64) as usize
+    }
+}
+
+fn hex_to_color(hex: &str) -> Option<Color> {
+    let rgba = parse_color(hex)?;
+    Some([rgba[0] as f32, rgba[1] as f32, rgba[2] as f32])
+}
+
+fn hsl_to_color(h: f32, s: f32, l: f32) -> Color {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    [(r + m) * 255.0, (g + m) * 255.0, (b + m) * 255.0]
+}
+
+/**
+ * 解析调色板：内置名称、逗号分隔的十六进制颜色，其余情况按种子生成和谐配色
+ */
+fn resolve_palette(spec: &str, rng: &mut SplitMix64) -> Vec<Color> {
+    if let Some((_, colors)) = PALETTES.iter().find(|(name, _)| *name == spec) {
+        return colors.iter().filter_map(|c| hex_to_color(c)).collect();
+    }
+
+    let custom: Vec<Color> = spec.split(',').filter_map(hex_to_color).collect();
+    if custom.len() >= 2 {
+        return custom;
+    }
+
+    // 以随机色相为基准，取相邻和互补色相，亮度由暗到亮
+    let base = rng.range(0.0, 360.0);
+    let spread = rng.range(20.0, 60.0);
+    let saturation = rng.range(0.45, 0.85);
+    let offsets = [0.0, spread, -spread, 180.0, 180.0 + spread / 2.0];
+    let lightness = [0.18, 0.35, 0.5, 0.62, 0.78];
+    offsets
+        .iter()
+        .zip(lightness)
+        .map(|(offset, l)| hsl_to_color(base + offset, saturation, l))
+        .collect()
+}
+
+fn luminance(c: &Color) -> f32 {
+    0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2]
+}
+
+fn lerp(a: &Color, b: &Color, t: f32) -> Color {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+/**
+ * 在多个颜色之间按 t ∈ [0, 1] 均匀插值
+ */
+fn sample_stops(stops: &[Color], t: f32) -> Color {
+    if stops.len() == 1 {
+        return stops[0];
+    }
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (scaled as usize).min(stops.len() - 2);
+    lerp(&stops[index], &stops[index + 1], scaled - index as f32)
+}
+
+fn shuffled(palette: &[Color], rng: &mut SplitMix64) -> Vec<Color> {
+    let mut colors = palette.to_vec();
+    for i in (1..colors.len()).rev() {
+        colors.swap(i, rng.index(i + 1));
+    }
+    colors
+}
+
+/**
+ * 浮点画布，最后统一加抖动转换为 8 位，减少渐变色带
+ */
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Result<Self, WallpaperError> {
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|len| width <= MAX_SIZE && height <= MAX_SIZE && *len as u64 <= MAX_PIXELS)
+            .ok_or_else(|| WallpaperError::ApiError(format!("Image size too large: {}x{}", width, height)))?;
+        Ok(Self {
+            width,
+            height,
+            pixels: vec![[0.0; 3]; len],
+        })
+    }
+
+    /// 较短边相对 1080 像素的缩放系数，使图案在不同分辨率下比例一致
+    fn scale(&self) -> f32 {
+        self.width.min(self.height) as f32 / 1080.0
+    }
+
+    fn fill_with(&mut self, f: impl Fn(f32, f32) -> Color) {
+        let width = self.width as usize;
+        for (i, pixel) in self.pixels.iter_mut().enumerate() {
+            *pixel = f((i % width) as f32 + 0.5, (i / width) as f32 + 0.5);
+        }
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: &Color, alpha: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let pixel = &mut self.pixels[(y as u32 * self.width + x as u32) as usize];
+        *pixel = lerp(pixel, color, alpha);
+    }
+
+    fn stamp(&mut self, cx: f32, cy: f32, radius: f32, color: &Color, alpha: f32) {
+        let r = radius.ceil() as i64;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let d = ((dx * dx + dy * dy) as f32).sqrt();
+                if d <= radius {
+                    let falloff = (radius - d + 0.5).min(1.0);
+                    self.blend(cx as i64 + dx, cy as i64 + dy, color, alpha * falloff);
+                }
+            }
+        }
+    }
+
+    fn fill_triangle(&mut self, a: (f32, f32), b: (f32, f32), c: (f32, f32), color: &Color) {
+        let min_x = a.0.min(b.0).min(c.0).floor().max(0.0) as i64;
+        let max_x = a.0.max(b.0).max(c.0).ceil().min(self.width as f32) as i64;
+        let min_y = a.1.min(b.1).min(c.1).floor().max(0.0) as i64;
+        let max_y = a.1.max(b.1).max(c.1).ceil().min(self.height as f32) as i64;
+        let edge = |p: (f32, f32), q: (f32, f32), x: f32, y: f32| {
+            (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0)
+        };
+        let area = edge(a, b, c.0, c.1);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(b, c, px, py) / area;
+                let w1 = edge(c, a, px, py) / area;
+                let w2 = edge(a, b, px, py) / area;
+                // 共享边上的像素两侧都会填充，避免出现缝隙
+                if w0 >= -1e-4 && w1 >= -1e-4 && w2 >= -1e-4 {
+                    self.pixels[(y as u32 * self.width + x as u32) as usize] = *color;
+                }
+            }
+        }
+    }
+
+    fn into_image(self, rng: &mut SplitMix64) -> RgbImage {
+        let mut img = RgbImage::new(self.width, self.height);
+        for (pixel, color) in img.pixels_mut().zip(self.pixels) {
+            let dither = rng.next_f32() - 0.5;
+            *pixel = Rgb(color.map(|c| (c + dither).round().clamp(0.0, 255.0) as u8));
+        }
+        img
+    }
+}
+
+fn draw_gradient(canvas: &mut Canvas, palette: &[Color], rng: &mut SplitMix64) {
+    let stops: Vec<Color> = shuffled(palette, rng).into_iter().take(2 + rng.index(2)).collect();
+    let angle = rng.range(0.0, TAU);
+    let (dx, dy) = (angle.cos(), angle.sin());
+    let (w, h) = (canvas.width as f32, canvas.height as f32);
+    let half_extent = (w * dx.abs() + h * dy.abs()) / 2.0;
+
+    canvas.fill_with(|x, y| {
+        let t = ((x - w / 2.0) * dx + (y - h / 2.0) * dy) / half_extent;
+        // smoothstep 让过渡更柔和
+        let t = (t + 1.0) / 2.0;
+        sample_stops(&stops, t * t * (3.0 - 2.0 * t))
+    });
+}
+
+struct MeshPoint {
+    x: f32,
+    y: f32,
+    color: Color,
+}
+
+fn mesh_points(palette: &[Color], rng: &mut SplitMix64, count: usize) -> Vec<MeshPoint> {
+    (0..count)
+        .map(|i| MeshPoint {
+            x: rng.range(-0.1, 1.1),
+            y: rng.range(-0.1, 1.1),
+            color: palette[(i + rng.index(palette.len())) % palette.len()],
+        })
+        .collect()
+}
+
+/**
+ * 反距离加权插值，坐标为 0..1 归一化值
+ */
+fn sample_mesh(points: &[MeshPoint], x: f32, y: f32) -> Color {
+    let mut total = 0.0;
+    let mut color = [0.0; 3];
+    for point in points {
+        let d2 = (x - point.x).powi(2) + (y - point.y).powi(2);
+        let weight = 1.0 / (d2 * d2 + 1e-4);
+        total += weight;
+        for (c, p) in color.iter_mut().zip(point.color) {
+            *c += p * weight;
+        }
+    }
+    color.map(|c| c / total)
+}
+
+fn draw_mesh_gradient(canvas: &mut Canvas, palette: &[Color], rng: &mut SplitMix64) {
+    let count = 4 + rng.index(3);
+    let points = mesh_points(palette, rng, count);
+    let (w, h) = (canvas.width as f32, canvas.height as f32);
+    let warp = ValueNoise::new(rng.next_u64());
+    let strength = rng.range(0.03, 0.08);
+
+    canvas.fill_with(|x, y| {
+        let (u, v) = (x / w, y / h);
+        // 用低频噪声扭曲采样坐标，让色块边缘更自然
+        let du = warp.fbm(u * 2.0, v * 2.0, 2) - 0.5;
+        let dv = warp.fbm(u * 2.0 + 7.3, v * 2.0 + 3.1, 2) - 0.5;
+        sample_mesh(&points, u + du * strength * 2.0, v + dv * strength * 2.0)
+    });
+}
+
+/**
+ * 二维值噪声，按格点哈希得到随机值后平滑插值
+ */
+struct ValueNoise {
+    seed: u64,
+}
+
+impl ValueNoise {
+    fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    fn lattice(&self, x: i64, y: i64) -> f32 {
+        let mut rng = SplitMix64::new(
+            self.seed ^ (x as u64).wrapping_mul(0x9E37_79B9) ^ ((y as u64).wrapping_mul(0x85EB_CA6B) << 1),
+        );
+        rng.next_f32()
+    }
+
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (sx, sy) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+        let (ix, iy) = (x0 as i64, y0 as i64);
+
+        let top = self.lattice(ix, iy) + (self.lattice(ix + 1, iy) - self.lattice(ix, iy)) * sx;
+        let bottom =
+            self.lattice(ix, iy + 1) + (self.lattice(ix + 1, iy + 1) - self.lattice(ix, iy + 1)) * sx;
+        top + (bottom - top) * sy
+    }
+
+    /// 多个倍频叠加，结果仍在 0..1
+    fn fbm(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let (mut value, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..octaves {
+            value += self.sample(x * frequency, y * frequency) * amplitude;
+            total += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        value / total
+    }
+}
+
+fn draw_flow_field(canvas: &mut Canvas, palette: &[Color], rng: &mut SplitMix64) {
+    let mut colors = palette.to_vec();
+    colors.sort_by(|a, b| luminance(a).total_cmp(&luminance(b)));
+    let background = colors.remove(0);
+    let background_light = lerp(&background, &colors[0], 0.25);
+    let h = canvas.height as f32;
+    canvas.fill_with(|_, y| lerp(&background_light, &background, y / h));
+
+    let noise = ValueNoise::new(rng.next_u64());
+    let scale = canvas.scale();
+    let frequency = rng.range(1.5, 3.5) / (1080.0 * scale);
+    let turns = rng.range(1.0, 2.5);
+    let radius = 1.2 * scale.max(0.5);
+    let step = 2.0 * scale.max(0.5);
+    let particles = (canvas.width * canvas.height / 1200) as usize;
+    let steps = 140;
+
+    for _ in 0..particles {
+        let mut x = rng.range(0.0, canvas.width as f32);
+        let mut y = rng.range(0.0, canvas.height as f32);
+        let color = colors[rng.index(colors.len())];
+        let alpha = rng.range(0.12, 0.35);
+
+        for _ in 0..steps {
+            let angle = noise.fbm(x * frequency, y * frequency, 3) * TAU * turns;
+            x += angle.cos() * step;
+            y += angle.sin() * step;
+            if x < 0.0 || y < 0.0 || x >= canvas.width as f32 || y >= canvas.height as f32 {
+                break;
+            }
+            canvas.stamp(x, y, radius, &color, alpha);
+        }
+    }
+}
+
+fn draw_low_poly(canvas: &mut Canvas, palette: &[Color], rng: &mut SplitMix64) {
+    let count = 4 + rng.index(3);
+    let points = mesh_points(palette, rng, count);
+    let (w, h) = (canvas.width as f32, canvas.height as f32);
+    let cell = w.max(h) / rng.range(10.0, 18.0);
+    let cols = (w / cell).ceil() as usize + 1;
+    let rows = (h / cell).ceil() as usize + 1;
+    let jitter = cell * 0.4;
+
+    // 网格顶点随机偏移，边界上的顶点只沿边移动
+    let mut grid = Vec::with_capacity(cols * rows);
+    for row in 0..rows {
+        for col in 0..cols {
+            let mut x = (col as f32 * cell).min(w);
+            let mut y = (row as f32 * cell).min(h);
+            if col > 0 && col < cols - 1 {
+                x += rng.range(-jitter, jitter);
+            }
+            if row > 0 && row < rows - 1 {
+                y += rng.range(-jitter, jitter);
+            }
+            grid.push((x, y));
+        }
+    }
+
+    for row in 0..rows - 1 {
+        for col in 0..cols - 1 {
+            let a = grid[row * cols + col];
+            let b = grid[row * cols + col + 1];
+            let c = grid[(row + 1) * cols + col];
+            let d = grid[(row + 1) * cols + col + 1];
+            let triangles = if rng.next_f32() < 0.5 {
+                [(a, b, d), (a, d, c)]
+            } else {
+                [(a, b, c), (b, d, c)]
+            };
+
+            for (p, q, r) in triangles {
+                let cx = (p.0 + q.0 + r.0) / 3.0 / w;
+                let cy = (p.1 + q.1 + r.1) / 3.0 / h;
+                let shade = rng.range(0.92, 1.08);
+                let color = sample_mesh(&points, cx, cy).map(|c| c * shade);
+                canvas.fill_triangle(p, q, r, &color);
+            }
+        }
+    }
+}
+
+/**
+ * Truchet 风格的拼贴：每个格子随机放置四分之一圆或对角三角形
+ */
+fn draw_geometric(canvas: &mut Canvas, palette: &[Color], rng: &mut SplitMix64) {
+    let colors = shuffled(palette, rng);
+    let background = colors[0];
+    canvas.fill_with(|_, _| background);
+
+    let (w, h) = (canvas.width as f32, canvas.height as f32);
+    let cell = w.min(h) / rng.range(5.0, 10.0);
+    let cols = (w / cell).ceil() as usize;
+    let rows = (h / cell).ceil() as usize;
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let (x0, y0) = (col as f32 * cell, row as f32 * cell);
+            let fill = colors[1 + rng.index(colors.len() - 1)];
+            let accent = colors[rng.index(colors.len())];
+            let corner = rng.index(4);
+            let (cx, cy) = match corner {
+                0 => (x0, y0),
+                1 => (x0 + cell, y0),
+                2 => (x0 + cell, y0 + cell),
+                _ => (x0, y0 + cell),
+            };
+
+            if rng.next_f32() < 0.6 {
+                draw_quarter_circle(canvas, (x0, y0), cell, (cx, cy), &fill);
+                if rng.next_f32() < 0.3 {
+                    canvas.stamp(x0 + cell / 2.0, y0 + cell / 2.0, cell * 0.12, &accent, 1.0);
+                }
+            } else {
+                let corners = [(x0, y0), (x0 + cell, y0), (x0 + cell, y0 + cell), (x0, y0 + cell)];
+                canvas.fill_triangle(corners[corner], corners[(corner + 1) % 4], corners[(corner + 3) % 4], &fill);
+            }
+        }
+    }
+}
+
+fn draw_quarter_circle(canvas: &mut Canvas, origin: (f32, f32), cell: f32, center: (f32, f32), color: &Color) {
+    let x_end = (origin.0 + cell).min(canvas.width as f32) as i64;
+    let y_end = (origin.1 + cell).min(canvas.height as f32) as i64;
+    for y in origin.1 as i64..y_end {
+        for x in origin.0 as i64..x_end {
+            let d = ((x as f32 + 0.5 - center.0).powi(2) + (y as f32 + 0.5 - center.1).powi(2)).sqrt();
+            let coverage = (cell - d + 0.5).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                canvas.blend(x, y, color, coverage);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(style: GeneratorStyle) -> GeneratorParams {
+        GeneratorParams {
+            style,
+            palette: "ocean".to_string(),
+            seed: 42,
+            width: 96,
+            height: 54,
+        }
+    }
+
+    #[test]
+    fn test_url_roundtrip() {
+        let params = GeneratorParams {
+            palette: "ff0000,00ff00".to_string(),
+            ..params(GeneratorStyle::LowPoly)
+        };
+        assert_eq!(GeneratorParams::from_url(&params.to_url()), Some(params));
+        assert_eq!(GeneratorParams::from_url("https://example.com/a.jpg"), None);
+
+        let huge = GeneratorParams::from_url("generated://gradient?seed=1&width=4000000000&height=100").unwrap();
+        assert_eq!((huge.width, huge.height), (MAX_SIZE, 100));
+        assert!(Canvas::new(MAX_SIZE + 1, 1).is_err());
+
+        let square = GeneratorParams::from_url("generated://gradient?seed=1&width=16384&height=16384").unwrap();
+        assert_eq!((square.width, square.height), (8192, 8192));
+        assert!(Canvas::new(MAX_SIZE, MAX_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_render_is_reproducible() {
+        for style in GeneratorStyle::ALL {
+            let first = render(&params(style)).unwrap();
+            assert_eq!(first.dimensions(), (96, 54));
+            assert_eq!(first, render(&params(style)).unwrap(), "{:?}", style);
+            assert_ne!(first, render(&GeneratorParams { seed: 43, ..params(style) }).unwrap(), "{:?}", style);
+        }
+    }
+
+    #[test]
+    fn test_prune_cache() {
+        let dir = std::env::temp_dir().join(format!("wallpaper-mate-generated-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for i in 0..MAX_CACHED + 3 {
+            fs::write(dir.join(format!("{}.jpg", i)), b"").unwrap();
+            fs::write(dir.join(format!("{}.json", i)), b"{}").unwrap();
+        }
+        let keep = dir.join("0.jpg");
+        prune_cache(&dir, &keep);
+
+        let images = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jpg"))
+            .count();
+        assert_eq!(images, MAX_CACHED);
+        assert!(keep.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_resolve_palette() {
+        let mut rng = SplitMix64::new(1);
+        assert_eq!(resolve_palette("mono", &mut rng).len(), 5);
+        assert_eq!(
+            resolve_palette("ff0000,0000ff", &mut rng),
+            vec![[255.0, 0.0, 0.0], [0.0, 0.0, 255.0]]
+        );
+        assert_eq!(resolve_palette("random", &mut rng).len(), 5);
+    }
+}
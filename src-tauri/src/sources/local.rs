@@ -0,0 +1,255 @@
+use crate::services::store;
+use crate::types::{PaginatedResponse, WallpaperError, WallpaperInfo, WallpaperListItem, WallpaperSource};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+const LIBRARY_FILE: &str = "local_library.json";
+const PER_PAGE: u32 = 24;
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp"];
+// 扫描文件夹时的最大递归深度
+const MAX_FOLDER_DEPTH: usize = 4;
+
+/**
+ * 一组本地壁纸，例如从 GNOME 幻灯片 XML 导入的图片
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalCollection {
+    pub name: String,
+    /// 导入来源文件
+    pub origin: Option<String>,
+    pub wallpapers: Vec<WallpaperInfo>,
+    pub imported_at: DateTime<Local>,
+}
+
+pub struct LocalLibrary {
+    collections: RwLock<Vec<LocalCollection>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl LocalLibrary {
+    pub fn new() -> Self {
+        Self {
+            collections: RwLock::new(Vec::new()),
+            path: RwLock::new(None),
+        }
+    }
+
+    pub fn init(&self, app: &AppHandle) -> Result<(), WallpaperError> {
+        let path = store::data_file(app, LIBRARY_FILE)?;
+        if let Some(collections) = store::load_json(&path) {
+            *self.collections.write().unwrap() = collections;
+        }
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<LocalCollection> {
+        self.collections.read().unwrap().clone()
+    }
+
+    /**
+     * 添加合集，同名合集会被替换
+     */
+    pub fn upsert(&self, collection: LocalCollection) {
+        {
+            let mut collections = self.collections.write().unwrap();
+            collections.retain(|c| c.name != collection.name);
+            collections.push(collection);
+        }
+        self.save();
+    }
+
+    pub fn remove(&self, name: &str) -> bool {
+        let removed = {
+            let mut collections = self.collections.write().unwrap();
+            let before = collections.len();
+            collections.retain(|c| c.name != name);
+            collections.len() != before
+        };
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /**
+     * 所有合集中仍然存在的图片，按路径去重
+     */
+    pub fn wallpapers(&self) -> Vec<WallpaperInfo> {
+        let mut seen = std::collections::HashSet::new();
+        self.collections
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|c| c.wallpapers.iter())
+            .filter(|w| w.local_path.as_deref().is_some_and(Path::exists))
+            .filter(|w| seen.insert(w.id.clone()))
+            .cloned()
+            .collect()
+    }
+
+    /**
+     * 指定合集中仍然存在的图片
+     */
+    pub fn collection(&self, name: &str) -> Option<Vec<WallpaperInfo>> {
+        self.collections
+            .read()
+            .unwrap()
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| {
+                c.wallpapers
+                    .iter()
+                    .filter(|w| w.local_path.as_deref().is_some_and(Path::exists))
+                    .cloned()
+                    .collect()
+            })
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.read().unwrap().clone() else {
+            return;
+        };
+        if let Err(e) = store::save_json(&path, &*self.collections.read().unwrap()) {
+            log::warn!("Failed to save local library: {}", e);
+        }
+    }
+}
+
+impl Default for LocalLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static LIBRARY: OnceLock<LocalLibrary> = OnceLock::new();
+
+pub fn get_local_library() -> &'static LocalLibrary {
+    LIBRARY.get_or_init(LocalLibrary::new)
+}
+
+/**
+ * 与平台和 Rust 版本无关的字符串哈希（FNV-1a），用于生成稳定的 ID
+ */
+pub(crate) fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/**
+ * 本地图片文件对应的壁纸信息，ID 由路径哈希得到，可以安全地用作文件名
+ */
+pub fn local_info(path: &Path) -> WallpaperInfo {
+    let title = path
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    WallpaperInfo {
+        id: format!("{:016x}", stable_hash(&path.to_string_lossy())),
+        title,
+        url: format!("file://{}", path.display()),
+        source: WallpaperSource::Local,
+        local_path: Some(path.to_path_buf()),
+        cached: false,
+        author: None,
+        attribution: None,
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn scan_folder(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < MAX_FOLDER_DEPTH {
+                scan_folder(&path, depth + 1, found);
+            }
+        } else if is_image(&path) {
+            found.push(path);
+        }
+    }
+}
+
+/**
+ * 文件夹（包括子文件夹）中的所有图片，按路径排序
+ */
+pub fn folder_wallpapers(dir: &Path) -> Vec<WallpaperInfo> {
+    let mut found = Vec::new();
+    scan_folder(dir, 0, &mut found);
+    found.sort();
+    found.iter().map(|path| local_info(path)).collect()
+}
+
+pub async fn fetch_wallpapers() -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    let wallpapers = get_local_library().wallpapers();
+    if wallpapers.is_empty() {
+        return Err("No local wallpapers imported".into());
+    }
+    Ok(wallpapers)
+}
+
+pub async fn fetch_wallpapers_paginated(
+    page: u32,
+) -> Result<PaginatedResponse<WallpaperListItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let wallpapers = get_local_library().wallpapers();
+    let page = page.max(1);
+    let total = wallpapers.len() as u32;
+
+    let data = wallpapers
+        .into_iter()
+        .skip((page - 1).saturating_mul(PER_PAGE) as usize)
+        .take(PER_PAGE as usize)
+        .map(|w| WallpaperListItem {
+            thumb_url: w.url.clone(),
+            author: w.author,
+            attribution: w.attribution,
+            id: w.id,
+            title: w.title,
+            url: w.url,
+            source: w.source,
+        })
+        .collect();
+
+    Ok(PaginatedResponse {
+        data,
+        current_page: page,
+        last_page: total.div_ceil(PER_PAGE).max(1),
+        per_page: PER_PAGE,
+        total,
+        seed: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folder_wallpapers() {
+        let dir = std::env::temp_dir().join(format!("wallpaper-mate-folder-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["b.JPG", "a.png", "notes.txt", "nested/c.webp"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let wallpapers = folder_wallpapers(&dir);
+        let titles: Vec<&str> = wallpapers.iter().map(|w| w.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "b", "c"]);
+        assert!(wallpapers.iter().all(|w| w.source == WallpaperSource::Local));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,6 +3,10 @@ pub mod wallhaven;
 pub mod unsplash;
 pub mod pixabay;
 pub mod pexels;
+pub mod generated;
+pub mod local;
 pub mod provider;
+pub mod ratelimit;
+pub mod validation;
 
 pub use provider::{fetch_paginated, fetch_random, list_sources, ProviderError};
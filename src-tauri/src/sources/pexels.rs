@@ -1,7 +1,10 @@
+use crate::services::{http, secrets};
+use crate::sources::ratelimit;
 use crate::types::{WallpaperInfo, WallpaperListItem, WallpaperSource, PaginatedResponse};
 use serde::Deserialize;
 
 const PEXELS_API_URL: &str = "https://api.pexels.com/v1/curated";
+const PEXELS_SEARCH_URL: &str = "https://api.pexels.com/v1/search";
 
 #[derive(Debug, Deserialize)]
 struct PexelsResponse {
@@ -29,35 +32,57 @@ struct PexelsSources {
 
 pub struct PexelsConfig {
     pub api_key: Option<String>,
+    /// 为空时使用精选列表，否则搜索关键词
+    pub query: Option<String>,
 }
 
 impl Default for PexelsConfig {
     fn default() -> Self {
         Self {
             api_key: None,
+            query: None,
         }
     }
 }
 
+fn endpoint(config: &PexelsConfig) -> Result<reqwest::Url, Box<dyn std::error::Error + Send + Sync>> {
+    match config.query.as_deref().filter(|q| !q.is_empty()) {
+        Some(query) => {
+            let mut url = reqwest::Url::parse(PEXELS_SEARCH_URL)?;
+            url.query_pairs_mut().append_pair("query", query);
+            Ok(url)
+        }
+        None => Ok(reqwest::Url::parse(PEXELS_API_URL)?),
+    }
+}
+
+/**
+ * 验证 API Key 的请求：只取一张精选照片
+ */
+pub fn key_check_request(api_key: &str) -> reqwest::RequestBuilder {
+    http::client()
+        .get(PEXELS_API_URL)
+        .query(&[("per_page", "1")])
+        .header("Authorization", api_key)
+}
+
 pub async fn fetch_wallpapers(
     config: Option<PexelsConfig>,
 ) -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
     let config = config.unwrap_or_default();
-    let client = reqwest::Client::new();
+    let client = http::client();
 
-    let mut url = reqwest::Url::parse(PEXELS_API_URL)?;
+    let mut url = endpoint(&config)?;
     url.query_pairs_mut()
         .append_pair("per_page", "20")
         .append_pair("orientation", "landscape");
 
     let mut request = client.get(url);
-    if let Some(key) = &config.api_key {
-        if !key.is_empty() {
-            request = request.header("Authorization", key);
-        }
+    if let Some(key) = config.api_key.clone().or_else(|| secrets::api_key(&WallpaperSource::Pexels)) {
+        request = request.header("Authorization", key);
     }
 
-    let response = request.send().await?;
+    let response = ratelimit::send(&WallpaperSource::Pexels, request).await?;
     let pexels_response: PexelsResponse = response.json().await?;
 
     let wallpapers = pexels_response
@@ -77,6 +102,8 @@ pub async fn fetch_wallpapers(
                 source: WallpaperSource::Pexels,
                 local_path: None,
                 cached: false,
+                author: Some(photo.photographer.clone()),
+                attribution: None,
             }
         })
         .collect();
@@ -89,22 +116,20 @@ pub async fn fetch_wallpapers_paginated(
     page: u32,
 ) -> Result<PaginatedResponse<WallpaperListItem>, Box<dyn std::error::Error + Send + Sync>> {
     let config = config.unwrap_or_default();
-    let client = reqwest::Client::new();
+    let client = http::client();
 
-    let mut url = reqwest::Url::parse(PEXELS_API_URL)?;
+    let mut url = endpoint(&config)?;
     url.query_pairs_mut()
         .append_pair("per_page", "20")
         .append_pair("page", &page.to_string())
         .append_pair("orientation", "landscape");
 
     let mut request = client.get(url);
-    if let Some(key) = &config.api_key {
-        if !key.is_empty() {
-            request = request.header("Authorization", key);
-        }
+    if let Some(key) = config.api_key.clone().or_else(|| secrets::api_key(&WallpaperSource::Pexels)) {
+        request = request.header("Authorization", key);
     }
 
-    let response = request.send().await?;
+    let response = ratelimit::send(&WallpaperSource::Pexels, request).await?;
     let pexels_response: PexelsResponse = response.json().await?;
 
     let wallpapers: Vec<WallpaperListItem> = pexels_response
@@ -123,6 +148,8 @@ pub async fn fetch_wallpapers_paginated(
                 url: photo.src.original.clone(),
                 source: WallpaperSource::Pexels,
                 thumb_url: photo.src.large2x.clone(),
+                author: Some(photo.photographer.clone()),
+                attribution: None,
             }
         })
         .collect();
@@ -135,6 +162,7 @@ pub async fn fetch_wallpapers_paginated(
         last_page: total_pages,
         per_page: pexels_response.per_page,
         total: pexels_response.total_results,
+        seed: None,
     })
 }
 
@@ -1,8 +1,8 @@
+use crate::services::{http, secrets};
+use crate::sources::ratelimit;
 use crate::types::{WallpaperInfo, WallpaperListItem, WallpaperSource};
 use serde::Deserialize;
 
-use crate::config::PIXABAY_API_KEY;
-
 const PIXABAY_API_URL: &str = "https://pixabay.com/api/";
 
 #[derive(Debug, Deserialize)]
@@ -40,15 +40,28 @@ impl Default for PixabayConfig {
     }
 }
 
+/**
+ * 验证 API Key 的请求，per_page 最小为 3
+ */
+pub fn key_check_request(api_key: &str) -> reqwest::RequestBuilder {
+    http::client()
+        .get(PIXABAY_API_URL)
+        .query(&[("key", api_key), ("q", "wallpaper"), ("per_page", "3")])
+}
+
+fn api_key() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    secrets::api_key(&WallpaperSource::Pixabay).ok_or_else(|| "Pixabay API key is not set".into())
+}
+
 pub async fn fetch_wallpapers(
     config: Option<PixabayConfig>,
 ) -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
     let config = config.unwrap_or_default();
-    let client = reqwest::Client::new();
+    let client = http::client();
 
     let mut url = reqwest::Url::parse(PIXABAY_API_URL)?;
     url.query_pairs_mut()
-        .append_pair("key", PIXABAY_API_KEY)
+        .append_pair("key", &api_key()?)
         .append_pair("q", config.query.as_deref().unwrap_or("wallpaper"))
         .append_pair("image_type", config.image_type.as_deref().unwrap_or("photo"))
         .append_pair("per_page", "20")
@@ -56,7 +69,11 @@ pub async fn fetch_wallpapers(
         .append_pair("min_width", "1920")
         .append_pair("min_height", "1080");
 
-    let response = client.get(url).send().await?;
+    if let Some(category) = &config.category {
+        url.query_pairs_mut().append_pair("category", category);
+    }
+
+    let response = ratelimit::send(&WallpaperSource::Pixabay, client.get(url)).await?;
     let pixabay_response: PixabayResponse = response.json().await?;
 
     let wallpapers = pixabay_response
@@ -70,6 +87,8 @@ pub async fn fetch_wallpapers(
                 source: WallpaperSource::Pixabay,
                 local_path: None,
                 cached: false,
+                author: Some(photo.user.clone()),
+                attribution: None,
             }
         })
         .collect();
@@ -84,20 +103,24 @@ pub async fn fetch_wallpapers_paginated(
     use crate::types::{PaginatedResponse, WallpaperListItem};
 
     let config = config.unwrap_or_default();
-    let client = reqwest::Client::new();
+    let client = http::client();
 
     let mut url = reqwest::Url::parse(PIXABAY_API_URL)?;
     url.query_pairs_mut()
-        .append_pair("key", PIXABAY_API_KEY)
+        .append_pair("key", &api_key()?)
         .append_pair("q", config.query.as_deref().unwrap_or("wallpaper"))
         .append_pair("image_type", config.image_type.as_deref().unwrap_or("photo"))
         .append_pair("per_page", "20")
         .append_pair("page", &page.to_string())
-        .append_pair("safesearch", "true")
+        .append_pair("safesearch", &config.safesearch.unwrap_or(true).to_string())
         .append_pair("min_width", "1920")
         .append_pair("min_height", "1080");
 
-    let response = client.get(url).send().await?;
+    if let Some(category) = &config.category {
+        url.query_pairs_mut().append_pair("category", category);
+    }
+
+    let response = ratelimit::send(&WallpaperSource::Pixabay, client.get(url)).await?;
     let pixabay_response: PixabayResponse = response.json().await?;
 
     let total = pixabay_response.total_hits;
@@ -114,6 +137,8 @@ pub async fn fetch_wallpapers_paginated(
                 url: photo.full_hd_url.clone(),
                 source: WallpaperSource::Pixabay,
                 thumb_url: photo.webformat_url.clone(),
+                author: Some(photo.user.clone()),
+                attribution: None,
             }
         })
         .collect();
@@ -124,6 +149,7 @@ pub async fn fetch_wallpapers_paginated(
         last_page,
         per_page,
         total,
+        seed: None,
     })
 }
 
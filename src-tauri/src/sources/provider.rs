@@ -1,4 +1,8 @@
+use crate::services::offline::Offline;
+use crate::sources::ratelimit::RateLimited;
 use crate::types::{PaginatedResponse, WallpaperInfo, WallpaperListItem, WallpaperSource};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /**
  * 统一错误类型
@@ -22,50 +26,73 @@ pub enum ProviderError {
 
     #[error("Unknown source: {0}")]
     UnknownSource(String),
+
+    #[error("{0}")]
+    RateLimited(#[from] RateLimited),
+}
+
+/**
+ * 按来源模块返回的错误分类：限流、离线和网络错误，其余都视为 API 错误
+ */
+impl From<Box<dyn std::error::Error + Send + Sync>> for ProviderError {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        let err = match err.downcast::<RateLimited>() {
+            Ok(limited) => return ProviderError::RateLimited(*limited),
+            Err(err) => err,
+        };
+        if err.is::<Offline>() {
+            return ProviderError::Network(err.to_string());
+        }
+        match err.downcast_ref::<reqwest::Error>() {
+            Some(e) if e.is_connect() || e.is_timeout() => ProviderError::Network(e.to_string()),
+            _ => ProviderError::Api(err.to_string()),
+        }
+    }
 }
 
 /**
  * 通过来源类型获取随机壁纸
  *
- * api_key 为可选参数，仅 Wallhaven/Pexels 等需要
+ * 需要 API Key 的来源从安全存储读取
  */
-pub async fn fetch_random(
-    source: WallpaperSource,
-    api_key: Option<String>,
-) -> Result<Vec<WallpaperInfo>, ProviderError> {
+pub async fn fetch_random(source: WallpaperSource) -> Result<Vec<WallpaperInfo>, ProviderError> {
     match source {
         WallpaperSource::Bing => {
             crate::sources::bing::fetch_wallpapers()
                 .await
-                .map_err(|e| ProviderError::Network(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Wallhaven => {
-            let config = crate::sources::wallhaven::WallhavenConfig {
-                api_key,
-                ..Default::default()
-            };
+            let config = crate::sources::wallhaven::account_config().await;
             crate::sources::wallhaven::search_wallpapers(Some(config))
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Unsplash => {
-            crate::sources::unsplash::fetch_wallpapers(None)
+            let config = crate::sources::unsplash::get_unsplash().get_config();
+            crate::sources::unsplash::fetch_wallpapers(Some(config))
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Pixabay => {
             crate::sources::pixabay::fetch_wallpapers(None)
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Pexels => {
-            let config = crate::sources::pexels::PexelsConfig {
-                api_key,
-                ..Default::default()
-            };
-            crate::sources::pexels::fetch_wallpapers(Some(config))
+            crate::sources::pexels::fetch_wallpapers(None)
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
+        }
+        WallpaperSource::Generated => {
+            crate::sources::generated::fetch_wallpapers(None)
+                .await
+                .map_err(|e| ProviderError::InvalidConfig(e.to_string()))
+        }
+        WallpaperSource::Local => {
+            crate::sources::local::fetch_wallpapers()
+                .await
+                .map_err(|e| ProviderError::InvalidConfig(e.to_string()))
         }
         _ => Err(ProviderError::UnknownSource(format!("{:?}", source))),
     }
@@ -76,47 +103,305 @@ pub async fn fetch_random(
  */
 pub async fn fetch_paginated(
     source: WallpaperSource,
-    api_key: Option<String>,
     page: u32,
 ) -> Result<PaginatedResponse<WallpaperListItem>, ProviderError> {
     match source {
         WallpaperSource::Bing => {
             crate::sources::bing::fetch_wallpapers_as_list(page)
                 .await
-                .map_err(|e| ProviderError::Network(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Wallhaven => {
-            let config = crate::sources::wallhaven::WallhavenConfig {
-                api_key,
-                ..Default::default()
-            };
+            let config = crate::sources::wallhaven::account_config().await;
             crate::sources::wallhaven::search_wallpapers_paginated(Some(config), page)
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Unsplash => {
-            crate::sources::unsplash::fetch_wallpapers_paginated(None, page)
+            let config = crate::sources::unsplash::get_unsplash().get_config();
+            crate::sources::unsplash::fetch_wallpapers_paginated(Some(config), page)
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Pixabay => {
             crate::sources::pixabay::fetch_wallpapers_paginated(None, page)
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
+        }
+        WallpaperSource::Pexels => {
+            crate::sources::pexels::fetch_wallpapers_paginated(None, page)
+                .await
+                .map_err(ProviderError::from)
+        }
+        WallpaperSource::Local => {
+            crate::sources::local::fetch_wallpapers_paginated(page)
+                .await
+                .map_err(|e| ProviderError::InvalidConfig(e.to_string()))
+        }
+        _ => Err(ProviderError::UnknownSource(format!("{:?}", source))),
+    }
+}
+
+/**
+ * 搜索关键词和筛选条件
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SourceQuery {
+    pub query: Option<String>,
+    /// 来源特有的筛选条件，键名与各来源 API 参数一致
+    pub filters: BTreeMap<String, String>,
+}
+
+impl SourceQuery {
+    pub fn is_empty(&self) -> bool {
+        self.query.as_deref().map_or(true, str::is_empty) && self.filters.is_empty()
+    }
+
+    fn filter(&self, key: &str) -> Option<String> {
+        self.filters.get(key).cloned()
+    }
+
+    /**
+     * 检查筛选条件是否都是该来源支持的
+     */
+    pub fn validate(&self, source: &WallpaperSource) -> Result<(), ProviderError> {
+        let allowed: &[&str] = match source {
+            WallpaperSource::Wallhaven => &[
+                "categories",
+                "purity",
+                "sorting",
+                "atleast",
+                "resolutions",
+                "ratios",
+                "colors",
+                "topRange",
+                "order",
+                "seed",
+                "collection",
+            ],
+            WallpaperSource::Unsplash => &["orientation", "topic", "collection", "content_filter"],
+            WallpaperSource::Pixabay => &["category", "image_type", "safesearch"],
+            WallpaperSource::Pexels => &[],
+            _ if self.is_empty() => return Ok(()),
+            _ => {
+                return Err(ProviderError::InvalidConfig(format!(
+                    "{} does not support search",
+                    source.as_str()
+                )))
+            }
+        };
+
+        if let Some(key) = self.filters.keys().find(|key| !allowed.contains(&key.as_str())) {
+            return Err(ProviderError::InvalidConfig(format!(
+                "Unsupported filter for {}: {}",
+                source.as_str(),
+                key
+            )));
+        }
+        if *source == WallpaperSource::Wallhaven {
+            match self.filters.get("collection") {
+                Some(collection) => self.validate_collection(collection)?,
+                None => self
+                    .wallhaven_config(Default::default())
+                    .validate()
+                    .map_err(ProviderError::InvalidConfig)?,
+            }
+        }
+        if *source == WallpaperSource::Unsplash {
+            self.unsplash_config()
+                .validate()
+                .map_err(ProviderError::InvalidConfig)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * 浏览 Wallhaven 收藏夹时只能再按 purity 筛选
+     */
+    fn validate_collection(&self, collection: &str) -> Result<(), ProviderError> {
+        crate::sources::wallhaven::parse_collection(collection).map_err(ProviderError::InvalidConfig)?;
+        let combined = self.query.as_deref().is_some_and(|query| !query.trim().is_empty())
+            || self.filters.keys().any(|key| key != "collection" && key != "purity");
+        if combined {
+            return Err(ProviderError::InvalidConfig(
+                "collection can only be combined with purity".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /**
+     * 多个值的筛选条件用逗号分隔，与 API 参数一致
+     */
+    fn filter_list(&self, key: &str) -> Vec<String> {
+        self.filters
+            .get(key)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /**
+     * 用筛选条件覆盖当前的 Unsplash 配置
+     *
+     * 指定了关键词时不再沿用配置中的话题和合集，否则关键词会被忽略
+     */
+    fn unsplash_config(&self) -> crate::sources::unsplash::UnsplashConfig {
+        let defaults = crate::sources::unsplash::get_unsplash().get_config();
+        let query = self.query.clone().filter(|query| !query.trim().is_empty());
+        let (topic, collection) = if query.is_some() {
+            (None, None)
+        } else {
+            (defaults.topic, defaults.collection)
+        };
+        crate::sources::unsplash::UnsplashConfig {
+            query: query.or(defaults.query),
+            orientation: self.filter("orientation").or(defaults.orientation),
+            topic: self.filter("topic").or(topic),
+            collection: self.filter("collection").or(collection),
+            content_filter: self.filter("content_filter").or(defaults.content_filter),
+            count: defaults.count,
+            access_key: defaults.access_key,
+        }
+    }
+
+    /**
+     * 用筛选条件覆盖默认条件，默认条件可能来自账号设置
+     */
+    fn wallhaven_config(
+        &self,
+        defaults: crate::sources::wallhaven::WallhavenConfig,
+    ) -> crate::sources::wallhaven::WallhavenConfig {
+        let sorting = self.filter("sorting").unwrap_or(defaults.sorting);
+        // 账号的排行榜范围只在按排行榜排序时使用
+        let top_range = self
+            .filter("topRange")
+            .or(defaults.top_range.filter(|_| sorting == "toplist"));
+        let list = |key: &str, default: Vec<String>| {
+            if self.filters.contains_key(key) {
+                self.filter_list(key)
+            } else {
+                default
+            }
+        };
+        crate::sources::wallhaven::WallhavenConfig {
+            categories: self.filter("categories").unwrap_or(defaults.categories),
+            purity: self.filter("purity").unwrap_or(defaults.purity),
+            api_key: defaults.api_key,
+            query: self.query.clone().filter(|query| !query.trim().is_empty()),
+            atleast: self.filter("atleast").or(defaults.atleast),
+            resolutions: list("resolutions", defaults.resolutions),
+            ratios: list("ratios", defaults.ratios),
+            colors: self.filter("colors").or(defaults.colors),
+            order: self.filter("order").or(defaults.order),
+            seed: self.filter("seed").or(defaults.seed),
+            sorting,
+            top_range,
+        }
+    }
+}
+
+/**
+ * 按关键词和筛选条件分页搜索
+ */
+pub async fn search_paginated(
+    source: WallpaperSource,
+    query: &SourceQuery,
+    page: u32,
+) -> Result<PaginatedResponse<WallpaperListItem>, ProviderError> {
+    query.validate(&source)?;
+    if query.is_empty() {
+        return fetch_paginated(source, page).await;
+    }
+
+    match source {
+        WallpaperSource::Wallhaven => {
+            if let Some(collection) = query.filters.get("collection") {
+                let purity = query.filters.get("purity").map(String::as_str);
+                return crate::sources::wallhaven::collection_paginated(collection, purity, page)
+                    .await
+                    .map_err(ProviderError::from);
+            }
+            let defaults = crate::sources::wallhaven::account_config().await;
+            let config = query.wallhaven_config(defaults);
+            crate::sources::wallhaven::search_wallpapers_paginated(Some(config), page)
+                .await
+                .map_err(ProviderError::from)
+        }
+        WallpaperSource::Unsplash => {
+            let config = query.unsplash_config();
+            crate::sources::unsplash::fetch_wallpapers_paginated(Some(config), page)
+                .await
+                .map_err(ProviderError::from)
+        }
+        WallpaperSource::Pixabay => {
+            let defaults = crate::sources::pixabay::PixabayConfig::default();
+            let safesearch = match query.filter("safesearch") {
+                Some(value) => Some(value.parse().map_err(|_| {
+                    ProviderError::InvalidConfig(format!("Invalid safesearch: {}", value))
+                })?),
+                None => defaults.safesearch,
+            };
+            let config = crate::sources::pixabay::PixabayConfig {
+                query: query.query.clone().or(defaults.query),
+                category: query.filter("category"),
+                image_type: query.filter("image_type").or(defaults.image_type),
+                safesearch,
+            };
+            crate::sources::pixabay::fetch_wallpapers_paginated(Some(config), page)
+                .await
+                .map_err(ProviderError::from)
         }
         WallpaperSource::Pexels => {
             let config = crate::sources::pexels::PexelsConfig {
-                api_key,
+                query: query.query.clone(),
                 ..Default::default()
             };
             crate::sources::pexels::fetch_wallpapers_paginated(Some(config), page)
                 .await
-                .map_err(|e| ProviderError::Api(e.to_string()))
+                .map_err(ProviderError::from)
         }
         _ => Err(ProviderError::UnknownSource(format!("{:?}", source))),
     }
 }
 
+/**
+ * 按页获取完整的壁纸信息，返回当前页和总页数
+ *
+ * 程序化生成的来源没有分页，每次都是新的一批
+ */
+pub async fn fetch_page(
+    source: WallpaperSource,
+    query: &SourceQuery,
+    page: u32,
+) -> Result<(Vec<WallpaperInfo>, u32), ProviderError> {
+    if source == WallpaperSource::Generated {
+        query.validate(&source)?;
+        return fetch_random(source).await.map(|wallpapers| (wallpapers, 1));
+    }
+
+    // Unsplash 自动切换使用 /photos/random，每次也是新的一批
+    if source == WallpaperSource::Unsplash {
+        query.validate(&source)?;
+        let config = query.unsplash_config();
+        return crate::sources::unsplash::fetch_wallpapers(Some(config))
+            .await
+            .map(|wallpapers| (wallpapers, 1))
+            .map_err(ProviderError::from);
+    }
+
+    let response = search_paginated(source, query, page).await?;
+    let wallpapers = response.data.into_iter().map(WallpaperInfo::from).collect();
+    Ok((wallpapers, response.last_page.max(1)))
+}
+
 /**
  * 获取所有支持的来源 ID 列表
  */
@@ -127,5 +412,7 @@ pub fn list_sources() -> Vec<String> {
         "unsplash".to_string(),
         "pixabay".to_string(),
         "pexels".to_string(),
+        "generated".to_string(),
+        "local".to_string(),
     ]
 }
@@ -0,0 +1,371 @@
+use crate::services::http;
+use crate::services::offline::{self, Offline};
+use crate::types::WallpaperSource;
+use chrono::{DateTime, Local, TimeZone};
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+// 429/5xx 的最大重试次数
+const MAX_RETRIES: u32 = 3;
+const BACKOFF_BASE_MS: u64 = 1000;
+const BACKOFF_MAX_MS: u64 = 30_000;
+// 服务端要求等待超过该秒数时不在请求内等待，直接进入冷却
+const MAX_INLINE_WAIT_SECONDS: u64 = 10;
+
+/**
+ * 来源限流，请求在冷却结束前不会发出
+ */
+#[derive(Debug, thiserror::Error)]
+#[error("Rate limited by {provider}, retry in {retry_after}s")]
+pub struct RateLimited {
+    pub provider: String,
+    pub retry_after: u64,
+}
+
+/**
+ * 来源的配额和冷却状态
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStatus {
+    pub source: String,
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    /// 配额重置时间
+    pub reset_at: Option<DateTime<Local>>,
+    /// 在此之前不再请求该来源
+    pub cooldown_until: Option<DateTime<Local>>,
+    pub last_status: Option<u16>,
+    pub requests: u64,
+}
+
+/**
+ * 响应头中的配额信息
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    pub reset_at: Option<DateTime<Local>>,
+}
+
+impl Quota {
+    pub fn from_headers(headers: &HeaderMap, now: DateTime<Local>) -> Self {
+        Self {
+            limit: header_u64(headers, "x-ratelimit-limit").map(|v| v as u32),
+            remaining: header_u64(headers, "x-ratelimit-remaining").map(|v| v as u32),
+            reset_at: header_u64(headers, "x-ratelimit-reset").map(|v| reset_time(v, now)),
+        }
+    }
+}
+
+impl ProviderStatus {
+    /**
+     * 需要等待的秒数，配额耗尽或处于冷却时返回 Some
+     */
+    fn wait_seconds(&self, now: DateTime<Local>) -> Option<u64> {
+        let exhausted_until = match (self.remaining, self.reset_at) {
+            (Some(0), Some(reset_at)) => Some(reset_at),
+            _ => None,
+        };
+        [self.cooldown_until, exhausted_until]
+            .into_iter()
+            .flatten()
+            .filter(|until| *until > now)
+            .max()
+            .map(|until| (until - now).num_seconds().max(1) as u64)
+    }
+}
+
+/**
+ * 没有 reset 头时假定的配额窗口：Unsplash、Pexels 按小时，其余按分钟
+ */
+fn default_window(source: &WallpaperSource) -> Duration {
+    match source {
+        WallpaperSource::Unsplash | WallpaperSource::Pexels => Duration::from_secs(3600),
+        _ => Duration::from_secs(60),
+    }
+}
+
+pub struct RateLimiter {
+    providers: RwLock<HashMap<String, ProviderStatus>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            providers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn status(&self, source: &WallpaperSource) -> ProviderStatus {
+        self.providers
+            .read()
+            .unwrap()
+            .get(source.as_str())
+            .cloned()
+            .unwrap_or_else(|| ProviderStatus {
+                source: source.as_str().to_string(),
+                ..Default::default()
+            })
+    }
+
+    /**
+     * 请求前检查，配额耗尽或冷却中时返回错误
+     */
+    pub fn check(&self, source: &WallpaperSource, now: DateTime<Local>) -> Result<(), RateLimited> {
+        match self.status(source).wait_seconds(now) {
+            Some(retry_after) => Err(RateLimited {
+                provider: source.as_str().to_string(),
+                retry_after,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * 记录响应状态码和限流相关的响应头
+     */
+    pub fn record(&self, source: &WallpaperSource, status: StatusCode, headers: &HeaderMap, now: DateTime<Local>) {
+        let mut providers = self.providers.write().unwrap();
+        let entry = providers
+            .entry(source.as_str().to_string())
+            .or_insert_with(|| ProviderStatus {
+                source: source.as_str().to_string(),
+                ..Default::default()
+            });
+
+        entry.requests += 1;
+        entry.last_status = Some(status.as_u16());
+        let quota = Quota::from_headers(headers, now);
+        if quota.limit.is_some() {
+            entry.limit = quota.limit;
+        }
+        if quota.remaining.is_some() {
+            entry.remaining = quota.remaining;
+        }
+        if quota.reset_at.is_some() {
+            entry.reset_at = quota.reset_at;
+        } else if entry.remaining == Some(0) && entry.reset_at.map_or(true, |reset| reset <= now) {
+            entry.reset_at = chrono::Duration::from_std(default_window(source)).ok().map(|w| now + w);
+        }
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let wait = retry_after(headers, now).unwrap_or_else(|| default_window(source));
+            entry.cooldown_until = chrono::Duration::from_std(wait).ok().map(|w| now + w);
+        } else if status.is_success() {
+            entry.cooldown_until = None;
+        }
+    }
+
+    /**
+     * 在请求内等待后会自动解除的短冷却
+     */
+    fn cool_down(&self, source: &WallpaperSource, wait: Duration, now: DateTime<Local>) {
+        let mut providers = self.providers.write().unwrap();
+        if let Some(entry) = providers.get_mut(source.as_str()) {
+            entry.cooldown_until = chrono::Duration::from_std(wait).ok().map(|w| now + w);
+        }
+    }
+
+    pub fn all(&self) -> Vec<ProviderStatus> {
+        let mut statuses: Vec<ProviderStatus> = self.providers.read().unwrap().values().cloned().collect();
+        statuses.sort_by(|a, b| a.source.cmp(&b.source));
+        statuses
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static RATE_LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+pub fn get_rate_limiter() -> &'static RateLimiter {
+    RATE_LIMITER.get_or_init(RateLimiter::new)
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/**
+ * reset 头有的是 Unix 时间戳（Pexels），有的是剩余秒数（Pixabay）
+ */
+fn reset_time(value: u64, now: DateTime<Local>) -> DateTime<Local> {
+    if value > 1_000_000_000 {
+        Local.timestamp_opt(value as i64, 0).single().unwrap_or(now)
+    } else {
+        now + chrono::Duration::seconds(value as i64)
+    }
+}
+
+/**
+ * Retry-After 可以是秒数或 HTTP 日期
+ */
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Local>) -> Option<Duration> {
+    let value = headers.get("retry-after")?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Local) - now).to_std().ok()
+}
+
+/**
+ * 第 attempt 次重试前的等待时间：指数退避加上 0~50% 的随机抖动
+ */
+fn backoff_delay(attempt: u32, jitter: f64) -> Duration {
+    let base = BACKOFF_BASE_MS.saturating_mul(1 << attempt.min(16)).min(BACKOFF_MAX_MS);
+    Duration::from_millis(base + (base as f64 * jitter.clamp(0.0, 1.0) * 0.5) as u64)
+}
+
+fn should_retry(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/**
+ * 发送来源 API 请求
+ *
+ * 配额耗尽或冷却中时不发出请求；429/5xx 按指数退避重试，
+ * 服务端要求等待太久时直接返回 RateLimited。整体超时取网络配置的连接加读取超时。
+ * 离线模式下不发出请求，连接失败时自动进入离线模式
+ */
+pub async fn send(
+    source: &WallpaperSource,
+    request: RequestBuilder,
+) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    let network = offline::get_offline();
+    if network.is_offline() {
+        return Err(Box::new(Offline));
+    }
+    let limiter = get_rate_limiter();
+    limiter.check(source, Local::now())?;
+
+    let request = request.timeout(http::get_http().get_config().request_timeout());
+    let mut attempt = 0;
+    loop {
+        let current = match request.try_clone() {
+            Some(current) => current,
+            None => return Ok(track(request.send().await).await?),
+        };
+        let response = track(current.send().await).await?;
+        let status = response.status();
+        let now = Local::now();
+        limiter.record(source, status, response.headers(), now);
+
+        if !should_retry(status) {
+            return Ok(response);
+        }
+
+        let wait = retry_after(response.headers(), now)
+            .unwrap_or_else(|| backoff_delay(attempt, rand::rng().random()));
+        if attempt >= MAX_RETRIES || wait > Duration::from_secs(MAX_INLINE_WAIT_SECONDS) {
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                limiter.check(source, now)?;
+            }
+            return Err(format!("{} returned {}", source.as_str(), status).into());
+        }
+
+        log::info!(
+            "{} returned {}, retrying in {}ms",
+            source.as_str(),
+            status,
+            wait.as_millis()
+        );
+        limiter.cool_down(source, wait, now);
+        tokio::time::sleep(wait).await;
+        attempt += 1;
+    }
+}
+
+/**
+ * 根据请求结果更新离线状态
+ */
+async fn track(result: reqwest::Result<Response>) -> reqwest::Result<Response> {
+    match &result {
+        Ok(_) => offline::get_offline().record_online(),
+        Err(e) => offline::get_offline().record_error(e).await,
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_quota_tracking() {
+        let limiter = RateLimiter::new();
+        let source = WallpaperSource::Unsplash;
+        let now = Local::now();
+
+        limiter.record(
+            &source,
+            StatusCode::OK,
+            &headers(&[("X-Ratelimit-Limit", "50"), ("X-Ratelimit-Remaining", "1")]),
+            now,
+        );
+        let status = limiter.status(&source);
+        assert_eq!((status.limit, status.remaining, status.requests), (Some(50), Some(1), 1));
+        assert!(limiter.check(&source, now).is_ok());
+
+        // 配额用完后在窗口结束前预先限流
+        limiter.record(&source, StatusCode::OK, &headers(&[("X-Ratelimit-Remaining", "0")]), now);
+        let err = limiter.check(&source, now).unwrap_err();
+        assert!(err.retry_after > 3500);
+        assert!(limiter.check(&source, now + chrono::Duration::hours(2)).is_ok());
+
+        // Pexels 的 reset 是时间戳
+        let reset = (now + chrono::Duration::minutes(5)).timestamp().to_string();
+        limiter.record(
+            &WallpaperSource::Pexels,
+            StatusCode::OK,
+            &headers(&[("X-Ratelimit-Remaining", "0"), ("X-Ratelimit-Reset", &reset)]),
+            now,
+        );
+        let err = limiter.check(&WallpaperSource::Pexels, now).unwrap_err();
+        assert!((290..=300).contains(&err.retry_after));
+    }
+
+    #[test]
+    fn test_retry_after_cooldown() {
+        let limiter = RateLimiter::new();
+        let source = WallpaperSource::Wallhaven;
+        let now = Local::now();
+
+        limiter.record(&source, StatusCode::TOO_MANY_REQUESTS, &headers(&[("Retry-After", "30")]), now);
+        assert_eq!(limiter.check(&source, now).unwrap_err().retry_after, 30);
+        assert!(limiter.check(&source, now + chrono::Duration::seconds(31)).is_ok());
+
+        limiter.record(&source, StatusCode::OK, &HeaderMap::new(), now);
+        assert!(limiter.check(&source, now).is_ok());
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0, 0.0), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(2, 0.0), Duration::from_millis(4000));
+        assert_eq!(backoff_delay(2, 1.0), Duration::from_millis(6000));
+        assert_eq!(backoff_delay(10, 0.0), Duration::from_millis(BACKOFF_MAX_MS));
+        assert!(should_retry(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!should_retry(StatusCode::UNAUTHORIZED));
+    }
+}
@@ -1,7 +1,8 @@
-use crate::types::{WallpaperInfo, WallpaperListItem, WallpaperSource};
-use serde::Deserialize;
-
-use crate::config::UNSPLASH_ACCESS_KEY;
+use crate::services::{http, secrets};
+use crate::sources::ratelimit;
+use crate::types::{Attribution, WallpaperInfo, WallpaperListItem, WallpaperSource};
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock};
 
 const UNSPLASH_API_URL: &str = "https://api.unsplash.com";
 
@@ -11,6 +12,7 @@ struct UnsplashPhoto {
     width: u32,
     height: u32,
     urls: UnsplashUrls,
+    links: UnsplashPhotoLinks,
     user: UnsplashUser,
 }
 
@@ -20,9 +22,60 @@ struct UnsplashUrls {
     small: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct UnsplashPhotoLinks {
+    html: String,
+    download_location: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct UnsplashUser {
     name: String,
+    username: String,
+    links: UnsplashUserLinks,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnsplashUserLinks {
+    html: String,
+}
+
+// API 指南要求署名链接带上应用名称作为 utm_source
+const UTM_SOURCE: &str = "wallpaper_mate";
+
+fn with_utm(link: &str) -> String {
+    match reqwest::Url::parse(link) {
+        Ok(mut url) => {
+            url.query_pairs_mut()
+                .append_pair("utm_source", UTM_SOURCE)
+                .append_pair("utm_medium", "referral");
+            url.to_string()
+        }
+        Err(_) => link.to_string(),
+    }
+}
+
+impl UnsplashPhoto {
+    /**
+     * 长边至少 1920、短边至少 1080，竖图和方图也能通过，方向由 orientation 参数决定
+     */
+    fn large_enough(&self) -> bool {
+        self.width.max(self.height) >= 1920 && self.width.min(self.height) >= 1080
+    }
+
+    fn attribution(&self) -> Attribution {
+        let profile = if self.user.links.html.is_empty() {
+            format!("https://unsplash.com/@{}", self.user.username)
+        } else {
+            self.user.links.html.clone()
+        };
+        Attribution {
+            name: self.user.name.clone(),
+            profile_url: with_utm(&profile),
+            photo_url: with_utm(&self.links.html),
+            download_location: Some(self.links.download_location.clone()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,9 +86,25 @@ struct UnsplashResponse {
     total_pages: u32,
 }
 
+const PER_PAGE: u32 = 20;
+// /photos/random 单次最多返回 30 张
+const MAX_RANDOM_COUNT: u32 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct UnsplashConfig {
     pub query: Option<String>,
+    /// landscape、portrait 或 squarish
     pub orientation: Option<String>,
+    /// 话题的 slug 或 ID，例如官方的 wallpapers 话题
+    pub topic: Option<String>,
+    /// 合集 ID
+    pub collection: Option<String>,
+    /// low 或 high，API 默认为 low
+    pub content_filter: Option<String>,
+    /// /photos/random 每次返回的数量
+    pub count: u32,
+    #[serde(skip)]
     pub access_key: Option<String>,
 }
 
@@ -44,37 +113,203 @@ impl Default for UnsplashConfig {
         Self {
             query: Some("wallpaper".to_string()),
             orientation: Some("landscape".to_string()),
+            topic: None,
+            collection: None,
+            content_filter: None,
+            count: 20,
             access_key: None,
         }
     }
 }
 
+/**
+ * 浏览的范围：合集优先于话题，话题优先于搜索关键词
+ */
+enum Listing<'a> {
+    Search(&'a str),
+    Topic(&'a str),
+    Collection(&'a str),
+}
+
+fn is_slug(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|value| !value.is_empty())
+}
+
+impl UnsplashConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(orientation) = non_empty(&self.orientation) {
+            if !matches!(orientation, "landscape" | "portrait" | "squarish") {
+                return Err(format!("Invalid orientation: {}", orientation));
+            }
+        }
+        if let Some(filter) = non_empty(&self.content_filter) {
+            if !matches!(filter, "low" | "high") {
+                return Err(format!("Invalid content filter: {}", filter));
+            }
+        }
+        if let Some(topic) = non_empty(&self.topic).filter(|topic| !is_slug(topic)) {
+            return Err(format!("Invalid topic: {}", topic));
+        }
+        if let Some(collection) = non_empty(&self.collection).filter(|collection| !is_slug(collection)) {
+            return Err(format!("Invalid collection: {}", collection));
+        }
+        if !(1..=MAX_RANDOM_COUNT).contains(&self.count) {
+            return Err(format!("Count must be between 1 and {}", MAX_RANDOM_COUNT));
+        }
+        Ok(())
+    }
+
+    fn listing(&self) -> Listing<'_> {
+        if let Some(collection) = non_empty(&self.collection) {
+            Listing::Collection(collection)
+        } else if let Some(topic) = non_empty(&self.topic) {
+            Listing::Topic(topic)
+        } else {
+            Listing::Search(non_empty(&self.query).unwrap_or("wallpaper"))
+        }
+    }
+}
+
+pub struct UnsplashManager {
+    config: RwLock<UnsplashConfig>,
+}
+
+impl UnsplashManager {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(UnsplashConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, config: UnsplashConfig) -> Result<(), String> {
+        config.validate()?;
+        *self.config.write().unwrap() = config;
+        Ok(())
+    }
+
+    pub fn get_config(&self) -> UnsplashConfig {
+        self.config.read().unwrap().clone()
+    }
+}
+
+impl Default for UnsplashManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static UNSPLASH: OnceLock<UnsplashManager> = OnceLock::new();
+
+pub fn get_unsplash() -> &'static UnsplashManager {
+    UNSPLASH.get_or_init(UnsplashManager::new)
+}
+
+/**
+ * 验证 Access Key 的请求。/me 需要用户授权，Client-ID 只能用最小的搜索请求
+ */
+pub fn key_check_request(access_key: &str) -> reqwest::RequestBuilder {
+    http::client()
+        .get(format!("{}/search/photos", UNSPLASH_API_URL))
+        .query(&[("query", "wallpaper"), ("per_page", "1")])
+        .header("Authorization", format!("Client-ID {}", access_key))
+}
+
+fn access_key(config: &UnsplashConfig) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    config
+        .access_key
+        .clone()
+        .or_else(|| secrets::api_key(&WallpaperSource::Unsplash))
+        .ok_or_else(|| "Unsplash access key is not set".into())
+}
+
+fn random_url(config: &UnsplashConfig) -> Result<reqwest::Url, Box<dyn std::error::Error + Send + Sync>> {
+    let mut url = reqwest::Url::parse(&format!("{}/photos/random", UNSPLASH_API_URL))?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("count", &config.count.clamp(1, MAX_RANDOM_COUNT).to_string());
+        // query 不能和 topics、collections 同时使用
+        match config.listing() {
+            Listing::Collection(collection) => pairs.append_pair("collections", collection),
+            Listing::Topic(topic) => pairs.append_pair("topics", topic),
+            Listing::Search(query) => pairs.append_pair("query", query),
+        };
+        if let Some(orientation) = non_empty(&config.orientation) {
+            pairs.append_pair("orientation", orientation);
+        }
+        if let Some(filter) = non_empty(&config.content_filter) {
+            pairs.append_pair("content_filter", filter);
+        }
+    }
+    Ok(url)
+}
+
+fn page_url(config: &UnsplashConfig, page: u32) -> Result<reqwest::Url, Box<dyn std::error::Error + Send + Sync>> {
+    let mut url = match config.listing() {
+        Listing::Collection(collection) => {
+            reqwest::Url::parse(&format!("{}/collections/{}/photos", UNSPLASH_API_URL, collection))?
+        }
+        Listing::Topic(topic) => reqwest::Url::parse(&format!("{}/topics/{}/photos", UNSPLASH_API_URL, topic))?,
+        Listing::Search(query) => {
+            let mut url = reqwest::Url::parse(&format!("{}/search/photos", UNSPLASH_API_URL))?;
+            url.query_pairs_mut().append_pair("query", query);
+            if let Some(filter) = non_empty(&config.content_filter) {
+                url.query_pairs_mut().append_pair("content_filter", filter);
+            }
+            url
+        }
+    };
+    {
+        let mut pairs = url.query_pairs_mut();
+        if let Some(orientation) = non_empty(&config.orientation) {
+            pairs.append_pair("orientation", orientation);
+        }
+        pairs
+            .append_pair("per_page", &PER_PAGE.to_string())
+            .append_pair("page", &page.to_string());
+    }
+    Ok(url)
+}
+
+/**
+ * 按 API 指南在下载图片时请求 download_location，只用于统计，不返回图片
+ */
+pub async fn track_download(download_location: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // 记录可能来自前端，只把 Access Key 发给 Unsplash API
+    if !download_location.starts_with(&format!("{}/", UNSPLASH_API_URL)) {
+        return Err(format!("Unexpected download location: {}", download_location).into());
+    }
+    let config = get_unsplash().get_config();
+    let auth_header = format!("Client-ID {}", access_key(&config)?);
+    let request = http::client().get(download_location).header("Authorization", auth_header);
+    ratelimit::send(&WallpaperSource::Unsplash, request).await?;
+    Ok(())
+}
+
+/**
+ * 通过 /photos/random 随机获取一批壁纸
+ */
 pub async fn fetch_wallpapers(
     config: Option<UnsplashConfig>,
 ) -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
-    let config = config.unwrap_or_default();
-    let client = reqwest::Client::new();
+    let config = config.unwrap_or_else(|| get_unsplash().get_config());
+    config.validate()?;
+    let client = http::client();
 
-    let mut url = reqwest::Url::parse(&format!("{}/search/photos", UNSPLASH_API_URL))?;
-    url.query_pairs_mut()
-        .append_pair("query", config.query.as_deref().unwrap_or("wallpaper"))
-        .append_pair("orientation", config.orientation.as_deref().unwrap_or("landscape"))
-        .append_pair("per_page", "20");
+    let url = random_url(&config)?;
+    let auth_header = format!("Client-ID {}", access_key(&config)?);
 
-    let auth_header = format!("Client-ID {}", UNSPLASH_ACCESS_KEY);
+    let request = client.get(url).header("Authorization", auth_header);
+    let response = ratelimit::send(&WallpaperSource::Unsplash, request).await?;
 
-    let response = client
-        .get(url)
-        .header("Authorization", auth_header)
-        .send()
-        .await?;
+    let photos: Vec<UnsplashPhoto> = response.json().await?;
 
-    let unsplash_response: UnsplashResponse = response.json().await?;
-
-    let wallpapers = unsplash_response
-        .results
+    let wallpapers = photos
         .into_iter()
-        .filter(|photo| photo.width >= 1920 && photo.height >= 1080)
+        .filter(UnsplashPhoto::large_enough)
         .map(|photo| {
             WallpaperInfo {
                 id: photo.id.clone(),
@@ -83,6 +318,8 @@ pub async fn fetch_wallpapers(
                 source: WallpaperSource::Unsplash,
                 local_path: None,
                 cached: false,
+                author: Some(photo.user.name.clone()),
+                attribution: Some(photo.attribution()),
             }
         })
         .collect();
@@ -90,42 +327,46 @@ pub async fn fetch_wallpapers(
     Ok(wallpapers)
 }
 
+/**
+ * 分页浏览搜索结果、话题或合集
+ */
 pub async fn fetch_wallpapers_paginated(
     config: Option<UnsplashConfig>,
     page: u32,
 ) -> Result<crate::types::PaginatedResponse<WallpaperListItem>, Box<dyn std::error::Error + Send + Sync>> {
     use crate::types::{PaginatedResponse, WallpaperListItem};
 
-    let config = config.unwrap_or_default();
-    let client = reqwest::Client::new();
+    let config = config.unwrap_or_else(|| get_unsplash().get_config());
+    config.validate()?;
+    let client = http::client();
 
-    let mut url = reqwest::Url::parse(&format!("{}/search/photos", UNSPLASH_API_URL))?;
-    url.query_pairs_mut()
-        .append_pair("query", config.query.as_deref().unwrap_or("wallpaper"))
-        .append_pair("orientation", config.orientation.as_deref().unwrap_or("landscape"))
-        .append_pair("per_page", "20")
-        .append_pair("page", &page.to_string());
+    let url = page_url(&config, page)?;
+    let auth_header = format!("Client-ID {}", access_key(&config)?);
 
-    let auth_header = config
-        .access_key
-        .as_ref()
-        .map(|k| format!("Client-ID {}", k))
-        .unwrap_or_else(|| {
-            format!("Client-ID {}", UNSPLASH_ACCESS_KEY)
-        });
-
-    let response = client
-        .get(url)
-        .header("Authorization", auth_header)
-        .send()
-        .await?;
-
-    let unsplash_response: UnsplashResponse = response.json().await?;
-
-    let wallpapers: Vec<WallpaperListItem> = unsplash_response
-        .results
+    let request = client.get(url).header("Authorization", auth_header);
+    let response = ratelimit::send(&WallpaperSource::Unsplash, request).await?;
+
+    // 话题和合集直接返回数组，总数在 X-Total 响应头中
+    let (photos, total, last_page) = match config.listing() {
+        Listing::Search(_) => {
+            let unsplash_response: UnsplashResponse = response.json().await?;
+            (unsplash_response.results, unsplash_response.total, unsplash_response.total_pages)
+        }
+        _ => {
+            let total = response
+                .headers()
+                .get("x-total")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u32>().ok());
+            let photos: Vec<UnsplashPhoto> = response.json().await?;
+            let total = total.unwrap_or(photos.len() as u32);
+            (photos, total, total.div_ceil(PER_PAGE).max(1))
+        }
+    };
+
+    let wallpapers: Vec<WallpaperListItem> = photos
         .into_iter()
-        .filter(|photo| photo.width >= 1920 && photo.height >= 1080)
+        .filter(UnsplashPhoto::large_enough)
         .map(|photo| {
             WallpaperListItem {
                 id: photo.id.clone(),
@@ -133,6 +374,8 @@ pub async fn fetch_wallpapers_paginated(
                 url: photo.urls.regular.clone(),
                 source: WallpaperSource::Unsplash,
                 thumb_url: photo.urls.small.clone(),
+                author: Some(photo.user.name.clone()),
+                attribution: Some(photo.attribution()),
             }
         })
         .collect();
@@ -140,9 +383,10 @@ pub async fn fetch_wallpapers_paginated(
     Ok(PaginatedResponse {
         data: wallpapers,
         current_page: page,
-        last_page: unsplash_response.total_pages,
-        per_page: 20,
-        total: unsplash_response.total,
+        last_page,
+        per_page: PER_PAGE,
+        total,
+        seed: None,
     })
 }
 
@@ -156,4 +400,76 @@ mod tests {
         assert_eq!(config.query, Some("wallpaper".to_string()));
         assert_eq!(config.orientation, Some("landscape".to_string()));
     }
+
+    #[test]
+    fn test_attribution() {
+        let photo: UnsplashPhoto = serde_json::from_str(
+            r#"{"id": "Dwu85P9SOIk", "width": 6000, "height": 4000,
+                "urls": {"regular": "https://images.unsplash.com/photo-1?w=1080", "small": "https://images.unsplash.com/photo-1?w=400"},
+                "links": {"html": "https://unsplash.com/photos/Dwu85P9SOIk",
+                    "download_location": "https://api.unsplash.com/photos/Dwu85P9SOIk/download?ixid=abc"},
+                "user": {"name": "Jane Doe", "username": "janedoe", "links": {"html": "https://unsplash.com/@janedoe"}}}"#,
+        )
+        .unwrap();
+        let attribution = photo.attribution();
+        assert_eq!(attribution.name, "Jane Doe");
+        assert_eq!(
+            attribution.profile_url,
+            "https://unsplash.com/@janedoe?utm_source=wallpaper_mate&utm_medium=referral"
+        );
+        assert_eq!(
+            attribution.photo_url,
+            "https://unsplash.com/photos/Dwu85P9SOIk?utm_source=wallpaper_mate&utm_medium=referral"
+        );
+        assert!(attribution.download_location.unwrap().starts_with(UNSPLASH_API_URL));
+        assert!(photo.large_enough());
+
+        let portrait = UnsplashPhoto { width: 3000, height: 4500, ..photo };
+        assert!(portrait.large_enough());
+        let small = UnsplashPhoto { width: 1600, height: 1600, ..portrait };
+        assert!(!small.large_enough());
+    }
+
+    #[tokio::test]
+    async fn test_track_download_rejects_other_hosts() {
+        assert!(track_download("https://example.com/photos/1/download").await.is_err());
+    }
+
+    #[test]
+    fn test_request_urls() {
+        let config = UnsplashConfig {
+            topic: Some("wallpapers".to_string()),
+            content_filter: Some("high".to_string()),
+            count: 10,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let random = random_url(&config).unwrap();
+        assert_eq!(random.path(), "/photos/random");
+        assert_eq!(
+            random.query(),
+            Some("count=10&topics=wallpapers&orientation=landscape&content_filter=high")
+        );
+
+        let page = page_url(&config, 3).unwrap();
+        assert_eq!(page.path(), "/topics/wallpapers/photos");
+        assert!(page.query().unwrap().ends_with("per_page=20&page=3"));
+
+        let collection = UnsplashConfig { collection: Some("1065976".to_string()), ..config };
+        assert_eq!(page_url(&collection, 1).unwrap().path(), "/collections/1065976/photos");
+
+        let search = page_url(&UnsplashConfig::default(), 1).unwrap();
+        assert!(search.query().unwrap().starts_with("query=wallpaper&orientation=landscape"));
+
+        let invalid = [
+            UnsplashConfig { orientation: Some("wide".to_string()), ..Default::default() },
+            UnsplashConfig { content_filter: Some("none".to_string()), ..Default::default() },
+            UnsplashConfig { topic: Some("../me".to_string()), ..Default::default() },
+            UnsplashConfig { count: 31, ..Default::default() },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err());
+        }
+    }
 }
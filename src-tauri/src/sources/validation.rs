@@ -0,0 +1,149 @@
+use crate::services::{http, offline};
+use crate::sources::ratelimit::{self, Quota};
+use crate::sources::{pexels, pixabay, unsplash, wallhaven};
+use crate::types::WallpaperSource;
+use chrono::{DateTime, Local};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+/**
+ * API Key 的验证结果
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum KeyValidation {
+    Valid,
+    /// Key 错误、被吊销或没有权限
+    Invalid { message: String },
+    /// Key 可能有效，但配额已用完
+    #[serde(rename_all = "camelCase")]
+    RateLimited { retry_after: Option<u64> },
+    /// 无法连接或服务端出错，不能判断 Key 是否有效
+    NetworkFailure { message: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyCheck {
+    pub source: WallpaperSource,
+    pub result: KeyValidation,
+    pub http_status: Option<u16>,
+    pub quota: Quota,
+}
+
+/**
+ * 按响应状态码和内容判断结果
+ *
+ * Pixabay 对错误的 Key 返回 400 和 "[ERROR 400] Invalid or missing API key"
+ */
+fn classify(status: StatusCode, headers: &HeaderMap, body: &str, now: DateTime<Local>) -> KeyValidation {
+    if status.is_success() {
+        return KeyValidation::Valid;
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return KeyValidation::RateLimited {
+            retry_after: ratelimit::retry_after(headers, now).map(|wait| wait.as_secs()),
+        };
+    }
+    if status.is_server_error() {
+        return KeyValidation::NetworkFailure {
+            message: format!("Provider returned {}", status),
+        };
+    }
+
+    let detail = body.trim().chars().take(200).collect::<String>();
+    KeyValidation::Invalid {
+        message: if detail.is_empty() { status.to_string() } else { format!("{}: {}", status, detail) },
+    }
+}
+
+/**
+ * 用对应来源最便宜的需要认证的请求验证 API Key
+ *
+ * 不经过限流器，也不会重试：验证的 Key 还没有保存，不应影响当前 Key 的配额记录
+ */
+pub async fn validate_api_key(source: WallpaperSource, key: &str) -> Result<KeyCheck, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("API key is empty".to_string());
+    }
+    let request = match source {
+        WallpaperSource::Wallhaven => wallhaven::key_check_request(key),
+        WallpaperSource::Unsplash => unsplash::key_check_request(key),
+        WallpaperSource::Pexels => pexels::key_check_request(key),
+        WallpaperSource::Pixabay => pixabay::key_check_request(key),
+        other => return Err(format!("{} does not use an API key", other.as_str())),
+    };
+
+    let network_failure = |message: String| KeyCheck {
+        source: source.clone(),
+        result: KeyValidation::NetworkFailure { message },
+        http_status: None,
+        quota: Quota::default(),
+    };
+    if offline::get_offline().is_offline() {
+        return Ok(network_failure(offline::Offline.to_string()));
+    }
+
+    let timeout = http::get_http().get_config().request_timeout();
+    let response = match request.timeout(timeout).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            offline::get_offline().record_error(&e).await;
+            return Ok(network_failure(e.to_string()));
+        }
+    };
+
+    let now = Local::now();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = if status.is_success() {
+        String::new()
+    } else {
+        response.text().await.unwrap_or_default()
+    };
+
+    Ok(KeyCheck {
+        result: classify(status, &headers, &body, now),
+        http_status: Some(status.as_u16()),
+        quota: Quota::from_headers(&headers, now),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_classify() {
+        let now = Local::now();
+        let mut headers = HeaderMap::new();
+        assert_eq!(classify(StatusCode::OK, &headers, "", now), KeyValidation::Valid);
+        assert!(matches!(
+            classify(StatusCode::UNAUTHORIZED, &headers, "", now),
+            KeyValidation::Invalid { .. }
+        ));
+        assert!(matches!(
+            classify(StatusCode::BAD_REQUEST, &headers, "[ERROR 400] Invalid or missing API key", now),
+            KeyValidation::Invalid { message } if message.contains("Invalid or missing")
+        ));
+        assert!(matches!(
+            classify(StatusCode::BAD_GATEWAY, &headers, "", now),
+            KeyValidation::NetworkFailure { .. }
+        ));
+
+        headers.insert("retry-after", HeaderValue::from_static("120"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        assert_eq!(
+            classify(StatusCode::TOO_MANY_REQUESTS, &headers, "", now),
+            KeyValidation::RateLimited { retry_after: Some(120) }
+        );
+        assert_eq!(Quota::from_headers(&headers, now).remaining, Some(0));
+
+        let json = serde_json::to_value(KeyValidation::RateLimited { retry_after: Some(5) }).unwrap();
+        assert_eq!(json, serde_json::json!({"status": "rateLimited", "retryAfter": 5}));
+    }
+}
@@ -1,5 +1,9 @@
+use crate::services::{http, secrets};
+use crate::sources::ratelimit;
 use crate::types::{WallpaperInfo, WallpaperSource};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 const WALLHAVEN_API_URL: &str = "https://wallhaven.cc/api/v1/search";
 
@@ -39,6 +43,9 @@ struct WallhavenMeta {
     last_page: u32,
     per_page: u32,
     total: u32,
+    /// 随机排序时返回，后续页带上才能得到同一次随机的结果
+    #[serde(default)]
+    seed: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,11 +63,38 @@ struct WallhavenTag {
     name: String,
 }
 
+// API 支持的排序方式
+const SORTINGS: &[&str] = &["date_added", "relevance", "random", "views", "favorites", "toplist"];
+const TOP_RANGES: &[&str] = &["1d", "3d", "1w", "1M", "3M", "6M", "1y"];
+// colors 只接受 Wallhaven 调色板中的颜色
+const COLORS: &[&str] = &[
+    "660000", "990000", "cc0000", "cc3333", "ea4c88", "993399", "663399", "333399", "0066cc",
+    "0099cc", "66cccc", "77cc33", "669900", "336600", "666600", "999900", "cccc33", "ffff00",
+    "ffcc33", "ff9900", "ff6600", "cc6633", "996633", "663300", "000000", "999999", "cccccc",
+    "ffffff", "424153",
+];
+
 pub struct WallhavenConfig {
     pub categories: String,
     pub purity: String,
     pub sorting: String,
     pub api_key: Option<String>,
+    /// 搜索关键词，对应 API 的 q 参数，支持 +tag、-tag、@user、id:、like:、type:
+    pub query: Option<String>,
+    /// 最小分辨率，例如 1920x1080
+    pub atleast: Option<String>,
+    /// 精确分辨率
+    pub resolutions: Vec<String>,
+    /// 宽高比，例如 16x9，也可以是 landscape 或 portrait
+    pub ratios: Vec<String>,
+    /// 调色板中的颜色，例如 0066cc
+    pub colors: Option<String>,
+    /// 排行榜的时间范围，仅 sorting 为 toplist 时有效
+    pub top_range: Option<String>,
+    /// desc 或 asc
+    pub order: Option<String>,
+    /// random 排序的种子，仅 sorting 为 random 时有效
+    pub seed: Option<String>,
 }
 
 impl Default for WallhavenConfig {
@@ -68,30 +102,178 @@ impl Default for WallhavenConfig {
         Self {
             categories: "010".to_string(),
             purity: "100".to_string(),
-            // 使用 toplist 而非 random，确保分页结果稳定
-            // random 模式下每次请求都会返回不同的随机结果
-            sorting: "toplist".to_string(),
+            // random 的分页通过第一页返回的 seed 保持稳定
+            sorting: "random".to_string(),
             api_key: None,
+            query: None,
+            atleast: None,
+            resolutions: Vec::new(),
+            ratios: Vec::new(),
+            colors: None,
+            top_range: None,
+            order: None,
+            seed: None,
         }
     }
 }
 
-pub async fn get_wallpaper_details(id: &str, api_key: Option<String>) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!("{}/{}", WALLHAVEN_API_URL.replace("/search", "/w"), id);
-    let client = if let Some(key) = api_key {
-        if !key.is_empty() {
-            reqwest::Client::builder()
-                .default_headers(create_auth_header(&key))
-                .build()?
+fn is_flags(value: &str) -> bool {
+    value.len() == 3 && value.chars().all(|c| c == '0' || c == '1')
+}
+
+/**
+ * 形如 1920x1080 的尺寸
+ */
+fn is_dimension(value: &str) -> bool {
+    match value.split_once('x') {
+        Some((width, height)) => {
+            width.parse::<u32>().is_ok_and(|w| w > 0) && height.parse::<u32>().is_ok_and(|h| h > 0)
+        }
+        None => false,
+    }
+}
+
+fn is_alphanumeric(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_username(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+}
+
+/**
+ * 检查 q 参数的标签语法
+ *
+ * id: 是精确的标签搜索，不能和其他条件组合
+ */
+fn validate_query(query: &str) -> Result<(), String> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    for term in &terms {
+        let valid = if let Some(id) = term.strip_prefix("id:") {
+            if terms.len() > 1 {
+                return Err("id: cannot be combined with other search terms".to_string());
+            }
+            id.parse::<u64>().is_ok()
+        } else if let Some(id) = term.strip_prefix("like:") {
+            is_alphanumeric(id)
+        } else if let Some(kind) = term.strip_prefix("type:") {
+            matches!(kind, "png" | "jpg")
+        } else if let Some(user) = term.strip_prefix('@') {
+            is_username(user)
+        } else if let Some(tag) = term.strip_prefix('+').or_else(|| term.strip_prefix('-')) {
+            !tag.is_empty() && !tag.starts_with(['+', '-'])
         } else {
-            reqwest::Client::new()
+            true
+        };
+        if !valid {
+            return Err(format!("Invalid search term: {}", term));
         }
-    } else {
-        reqwest::Client::new()
-    };
+    }
+    Ok(())
+}
 
-    let response = client.get(url).send().await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+impl WallhavenConfig {
+    /**
+     * 在发出请求前检查所有搜索参数
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_flags(&self.categories) {
+            return Err(format!("Invalid categories: {}", self.categories));
+        }
+        if !is_flags(&self.purity) {
+            return Err(format!("Invalid purity: {}", self.purity));
+        }
+        if !SORTINGS.contains(&self.sorting.as_str()) {
+            return Err(format!("Invalid sorting: {}", self.sorting));
+        }
+        if let Some(query) = &self.query {
+            validate_query(query)?;
+        }
+        if let Some(atleast) = self.atleast.as_deref().filter(|value| !is_dimension(value)) {
+            return Err(format!("Invalid atleast: {}", atleast));
+        }
+        if let Some(resolution) = self.resolutions.iter().find(|value| !is_dimension(value)) {
+            return Err(format!("Invalid resolution: {}", resolution));
+        }
+        if let Some(ratio) = self
+            .ratios
+            .iter()
+            .find(|value| !matches!(value.as_str(), "landscape" | "portrait") && !is_dimension(value))
+        {
+            return Err(format!("Invalid ratio: {}", ratio));
+        }
+        if let Some(color) = self.colors.as_deref() {
+            let hex = color.trim_start_matches('#').to_ascii_lowercase();
+            if !COLORS.contains(&hex.as_str()) {
+                return Err(format!("Unsupported color: {}", color));
+            }
+        }
+        if let Some(range) = &self.top_range {
+            if !TOP_RANGES.contains(&range.as_str()) {
+                return Err(format!("Invalid topRange: {}", range));
+            }
+            if self.sorting != "toplist" {
+                return Err("topRange requires sorting=toplist".to_string());
+            }
+        }
+        if let Some(order) = self.order.as_deref().filter(|order| !matches!(*order, "desc" | "asc")) {
+            return Err(format!("Invalid order: {}", order));
+        }
+        if let Some(seed) = &self.seed {
+            if seed.len() != 6 || !is_alphanumeric(seed) {
+                return Err(format!("Invalid seed: {}", seed));
+            }
+            if self.sorting != "random" {
+                return Err("seed requires sorting=random".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 生成搜索地址，NSFW 内容需要 API Key
+     */
+    fn search_url(&self, page: u32, api_key: Option<&str>) -> Result<reqwest::Url, Box<dyn std::error::Error + Send + Sync>> {
+        self.validate()?;
+        if self.purity.ends_with('1') && api_key.map_or(true, str::is_empty) {
+            return Err("NSFW purity requires a Wallhaven API key".into());
+        }
+
+        let mut url = reqwest::Url::parse(WALLHAVEN_API_URL)?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs
+                .append_pair("categories", &self.categories)
+                .append_pair("purity", &self.purity)
+                .append_pair("sorting", &self.sorting)
+                .append_pair("page", &page.to_string());
+
+            let optional = [
+                ("q", self.query.clone()),
+                ("atleast", self.atleast.clone()),
+                ("resolutions", Some(self.resolutions.join(","))),
+                ("ratios", Some(self.ratios.join(","))),
+                ("colors", self.colors.as_deref().map(|color| color.trim_start_matches('#').to_ascii_lowercase())),
+                ("topRange", self.top_range.clone()),
+                ("order", self.order.clone()),
+                ("seed", self.seed.clone()),
+                ("apikey", api_key.map(str::to_string)),
+            ];
+            for (key, value) in optional {
+                if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+                    pairs.append_pair(key, value.trim());
+                }
+            }
+        }
+        Ok(url)
+    }
+}
+
+pub async fn get_wallpaper_details(id: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("{}/{}", WALLHAVEN_API_URL.replace("/search", "/w"), id);
+    let api_key = secrets::api_key(&WallpaperSource::Wallhaven);
+    let request = authorized(http::client().get(url), api_key.as_deref());
+    let response = ratelimit::send(&WallpaperSource::Wallhaven, request).await?;
     let details: WallhavenImageDetails = response.json().await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
@@ -100,33 +282,11 @@ pub async fn get_wallpaper_details(id: &str, api_key: Option<String>) -> Result<
 
 pub async fn search_wallpapers(config: Option<WallhavenConfig>) -> Result<Vec<WallpaperInfo>, Box<dyn std::error::Error + Send + Sync>> {
     let config = config.unwrap_or_default();
-    let mut url = reqwest::Url::parse(WALLHAVEN_API_URL)?;
-    url.query_pairs_mut()
-        .append_pair("categories", &config.categories)
-        .append_pair("purity", &config.purity)
-        .append_pair("sorting", &config.sorting)
-        .append_pair("page", "1");
-
-    if let Some(key) = &config.api_key {
-        if !key.is_empty() {
-            url.query_pairs_mut().append_pair("apikey", key);
-        }
-    }
-
-    let client = if let Some(key) = &config.api_key {
-        if !key.is_empty() {
-            reqwest::Client::builder()
-                .default_headers(create_auth_header(key))
-                .build()?
-        } else {
-            reqwest::Client::new()
-        }
-    } else {
-        reqwest::Client::new()
-    };
+    let api_key = config.api_key.clone().or_else(|| secrets::api_key(&WallpaperSource::Wallhaven));
+    let url = config.search_url(1, api_key.as_deref())?;
 
-    let response = client.get(url).send().await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    let request = authorized(http::client().get(url), api_key.as_deref());
+    let response = ratelimit::send(&WallpaperSource::Wallhaven, request).await?;
     let wallhaven_response: WallhavenResponse = response.json().await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
@@ -142,6 +302,8 @@ pub async fn search_wallpapers(config: Option<WallhavenConfig>) -> Result<Vec<Wa
                 source: WallpaperSource::Wallhaven,
                 local_path: None,
                 cached: false,
+                author: None,
+                attribution: None,
             }
         })
         .collect();
@@ -153,44 +315,30 @@ pub async fn search_wallpapers_paginated(
     config: Option<WallhavenConfig>,
     page: u32,
 ) -> Result<crate::types::PaginatedResponse<crate::types::WallpaperListItem>, Box<dyn std::error::Error + Send + Sync>> {
-    use crate::types::{PaginatedResponse, WallpaperListItem, WallpaperSource};
-
     let config = config.unwrap_or_default();
-    let mut url = reqwest::Url::parse(WALLHAVEN_API_URL)?;
-    url.query_pairs_mut()
-        .append_pair("categories", &config.categories)
-        .append_pair("purity", &config.purity)
-        .append_pair("sorting", &config.sorting)
-        .append_pair("page", &page.to_string());
-
-    if let Some(key) = &config.api_key {
-        if !key.is_empty() {
-            url.query_pairs_mut().append_pair("apikey", key);
-        }
-    }
+    let api_key = config.api_key.clone().or_else(|| secrets::api_key(&WallpaperSource::Wallhaven));
+    let url = config.search_url(page, api_key.as_deref())?;
 
-    let client = if let Some(key) = &config.api_key {
-        if !key.is_empty() {
-            reqwest::Client::builder()
-                .default_headers(create_auth_header(key))
-                .build()?
-        } else {
-            reqwest::Client::new()
-        }
-    } else {
-        reqwest::Client::new()
-    };
-
-    let response = client.get(url).send().await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    let request = authorized(http::client().get(url), api_key.as_deref());
+    let response = ratelimit::send(&WallpaperSource::Wallhaven, request).await?;
     let wallhaven_response: WallhavenResponse = response.json().await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
+    Ok(to_page(wallhaven_response, page))
+}
+
+fn to_page(
+    wallhaven_response: WallhavenResponse,
+    page: u32,
+) -> crate::types::PaginatedResponse<crate::types::WallpaperListItem> {
+    use crate::types::{PaginatedResponse, WallpaperListItem, WallpaperSource};
+
     let meta = wallhaven_response.meta.unwrap_or_else(|| WallhavenMeta {
         current_page: page,
         last_page: 1,
         per_page: 24,
         total: wallhaven_response.data.len() as u32,
+        seed: None,
     });
 
     let wallpapers = wallhaven_response
@@ -204,26 +352,269 @@ pub async fn search_wallpapers_paginated(
                 url: img.path.clone(),
                 source: WallpaperSource::Wallhaven,
                 thumb_url: img.thumbs.small.clone(),
+                author: None,
+                attribution: None,
             }
         })
         .collect();
 
-    Ok(PaginatedResponse {
+    PaginatedResponse {
         data: wallpapers,
         current_page: meta.current_page,
         last_page: meta.last_page,
         per_page: meta.per_page,
         total: meta.total,
-    })
+        seed: meta.seed,
+    }
 }
 
-fn create_auth_header(api_key: &str) -> reqwest::header::HeaderMap {
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "X-API-Key",
-        reqwest::header::HeaderValue::from_str(api_key).unwrap(),
-    );
-    headers
+/**
+ * 账号设置中的默认搜索条件
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSettings {
+    pub categories: String,
+    pub purity: String,
+    pub resolutions: Vec<String>,
+    pub ratios: Vec<String>,
+    pub toplist_range: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WallhavenSettingsResponse {
+    data: WallhavenSettingsData,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct WallhavenSettingsData {
+    categories: Vec<String>,
+    purity: Vec<String>,
+    resolutions: Vec<String>,
+    aspect_ratios: Vec<String>,
+    toplist_range: Option<String>,
+}
+
+/**
+ * 把名称列表转换成 API 使用的 "110" 形式，列表为空时返回 None
+ */
+fn to_flags(values: &[String], names: [&str; 3]) -> Option<String> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(
+        names
+            .iter()
+            .map(|name| if values.iter().any(|value| value == name) { '1' } else { '0' })
+            .collect(),
+    )
+}
+
+impl From<WallhavenSettingsData> for AccountSettings {
+    fn from(data: WallhavenSettingsData) -> Self {
+        let defaults = WallhavenConfig::default();
+        Self {
+            categories: to_flags(&data.categories, ["general", "anime", "people"]).unwrap_or(defaults.categories),
+            purity: to_flags(&data.purity, ["sfw", "sketchy", "nsfw"]).unwrap_or(defaults.purity),
+            resolutions: data.resolutions,
+            ratios: data.aspect_ratios,
+            toplist_range: data.toplist_range.filter(|range| TOP_RANGES.contains(&range.as_str())),
+        }
+    }
+}
+
+struct CachedSettings {
+    api_key: String,
+    settings: AccountSettings,
+    fetched_at: Instant,
+}
+
+// 账号设置很少变化，缓存一段时间避免每次搜索都多一次请求
+const SETTINGS_TTL: Duration = Duration::from_secs(3600);
+
+static ACCOUNT_SETTINGS: RwLock<Option<CachedSettings>> = RwLock::new(None);
+
+/**
+ * 获取 API Key 对应账号的设置
+ */
+pub async fn fetch_account_settings(api_key: &str) -> Result<AccountSettings, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(cached) = ACCOUNT_SETTINGS.read().unwrap().as_ref() {
+        if cached.api_key == api_key && cached.fetched_at.elapsed() < SETTINGS_TTL {
+            return Ok(cached.settings.clone());
+        }
+    }
+
+    let url = WALLHAVEN_API_URL.replace("/search", "/settings");
+    let request = authorized(http::client().get(url), Some(api_key)).query(&[("apikey", api_key)]);
+    let response = ratelimit::send(&WallpaperSource::Wallhaven, request).await?;
+    let settings: WallhavenSettingsResponse = response.json().await
+        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    let settings = AccountSettings::from(settings.data);
+
+    *ACCOUNT_SETTINGS.write().unwrap() = Some(CachedSettings {
+        api_key: api_key.to_string(),
+        settings: settings.clone(),
+        fetched_at: Instant::now(),
+    });
+    Ok(settings)
+}
+
+/**
+ * 搜索的默认条件：配置了 API Key 时使用账号设置，获取失败时使用内置默认值
+ */
+pub async fn account_config() -> WallhavenConfig {
+    let Some(key) = secrets::api_key(&WallpaperSource::Wallhaven) else {
+        return WallhavenConfig::default();
+    };
+
+    let defaults = WallhavenConfig::default();
+    match fetch_account_settings(&key).await {
+        Ok(settings) => WallhavenConfig {
+            categories: settings.categories,
+            purity: settings.purity,
+            resolutions: settings.resolutions,
+            ratios: settings.ratios,
+            top_range: settings.toplist_range.filter(|_| defaults.sorting == "toplist"),
+            api_key: Some(key),
+            ..defaults
+        },
+        Err(e) => {
+            log::warn!("Failed to load Wallhaven account settings: {}", e);
+            WallhavenConfig { api_key: Some(key), ..defaults }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WallhavenCollectionsResponse {
+    data: Vec<WallhavenCollectionData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WallhavenCollectionData {
+    id: u64,
+    label: String,
+    #[serde(default)]
+    views: u64,
+    #[serde(default)]
+    public: u8,
+    #[serde(default)]
+    count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WallhavenCollection {
+    pub id: u64,
+    pub label: String,
+    pub views: u64,
+    pub public: bool,
+    pub count: u64,
+}
+
+/**
+ * 列出收藏夹
+ *
+ * 不指定用户名时返回 API Key 对应账号的全部收藏夹，指定时只返回该用户公开的收藏夹
+ */
+pub async fn list_collections(
+    username: Option<&str>,
+) -> Result<Vec<WallhavenCollection>, Box<dyn std::error::Error + Send + Sync>> {
+    let api_key = secrets::api_key(&WallpaperSource::Wallhaven);
+    let base = WALLHAVEN_API_URL.replace("/search", "/collections");
+    let url = match username.map(str::trim).filter(|user| !user.is_empty()) {
+        Some(user) if is_username(user) => format!("{}/{}", base, user),
+        Some(user) => return Err(format!("Invalid username: {}", user).into()),
+        None if api_key.is_none() => return Err("Listing your collections requires a Wallhaven API key".into()),
+        None => base,
+    };
+
+    let mut request = authorized(http::client().get(url), api_key.as_deref());
+    if let Some(key) = &api_key {
+        request = request.query(&[("apikey", key)]);
+    }
+    let response = ratelimit::send(&WallpaperSource::Wallhaven, request).await?;
+    let collections: WallhavenCollectionsResponse = response.json().await
+        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+    Ok(collections
+        .data
+        .into_iter()
+        .map(|collection| WallhavenCollection {
+            id: collection.id,
+            label: collection.label,
+            views: collection.views,
+            public: collection.public != 0,
+            count: collection.count,
+        })
+        .collect())
+}
+
+/**
+ * 解析 "用户名/收藏夹 ID" 形式的收藏夹引用
+ */
+pub fn parse_collection(value: &str) -> Result<(String, u64), String> {
+    let parsed = value
+        .trim()
+        .split_once('/')
+        .filter(|(user, _)| is_username(user))
+        .and_then(|(user, id)| id.parse::<u64>().ok().map(|id| (user.to_string(), id)));
+    parsed.ok_or_else(|| format!("Invalid collection: {} (expected username/id)", value))
+}
+
+/**
+ * 分页获取收藏夹中的壁纸，私有收藏夹需要所有者的 API Key
+ */
+pub async fn collection_paginated(
+    collection: &str,
+    purity: Option<&str>,
+    page: u32,
+) -> Result<crate::types::PaginatedResponse<crate::types::WallpaperListItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let (username, id) = parse_collection(collection)?;
+    if let Some(purity) = purity.filter(|purity| !is_flags(purity)) {
+        return Err(format!("Invalid purity: {}", purity).into());
+    }
+    let api_key = secrets::api_key(&WallpaperSource::Wallhaven);
+
+    let mut url = reqwest::Url::parse(&format!(
+        "{}/{}/{}",
+        WALLHAVEN_API_URL.replace("/search", "/collections"),
+        username,
+        id
+    ))?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("page", &page.to_string());
+        if let Some(purity) = purity {
+            pairs.append_pair("purity", purity);
+        }
+        if let Some(key) = &api_key {
+            pairs.append_pair("apikey", key);
+        }
+    }
+
+    let request = authorized(http::client().get(url), api_key.as_deref());
+    let response = ratelimit::send(&WallpaperSource::Wallhaven, request).await?;
+    let wallhaven_response: WallhavenResponse = response.json().await
+        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+    Ok(to_page(wallhaven_response, page))
+}
+
+/**
+ * 验证 API Key 的请求，/settings 只有 Key 有效时才返回 200
+ */
+pub fn key_check_request(api_key: &str) -> reqwest::RequestBuilder {
+    let url = WALLHAVEN_API_URL.replace("/search", "/settings");
+    authorized(http::client().get(url), Some(api_key)).query(&[("apikey", api_key)])
+}
+
+fn authorized(request: reqwest::RequestBuilder, api_key: Option<&str>) -> reqwest::RequestBuilder {
+    match api_key.filter(|key| !key.is_empty()) {
+        Some(key) => request.header("X-API-Key", key),
+        None => request,
+    }
 }
 
 #[cfg(test)]
@@ -238,6 +629,92 @@ mod tests {
         assert_eq!(config.sorting, "random");
     }
 
+    #[test]
+    fn test_validate_config() {
+        let config = WallhavenConfig {
+            query: Some("+nature -people @artist like:94x38z".to_string()),
+            atleast: Some("2560x1440".to_string()),
+            ratios: vec!["16x9".to_string(), "landscape".to_string()],
+            colors: Some("#0066CC".to_string()),
+            sorting: "toplist".to_string(),
+            top_range: Some("1M".to_string()),
+            order: Some("asc".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let url = config.search_url(2, Some("key")).unwrap();
+        let query = url.query().unwrap();
+        assert!(query.contains("atleast=2560x1440"));
+        assert!(query.contains("ratios=16x9%2Clandscape"));
+        assert!(query.contains("colors=0066cc"));
+        assert!(query.contains("topRange=1M"));
+        assert!(query.contains("page=2"));
+        assert!(!query.contains("resolutions"));
+
+        let invalid = [
+            WallhavenConfig { purity: "12".to_string(), ..Default::default() },
+            WallhavenConfig { sorting: "newest".to_string(), ..Default::default() },
+            WallhavenConfig { query: Some("id:12 +cats".to_string()), ..Default::default() },
+            WallhavenConfig { query: Some("id:abc".to_string()), ..Default::default() },
+            WallhavenConfig { query: Some("@".to_string()), ..Default::default() },
+            WallhavenConfig { atleast: Some("1920".to_string()), ..Default::default() },
+            WallhavenConfig { colors: Some("123456".to_string()), ..Default::default() },
+            WallhavenConfig { top_range: Some("2w".to_string()), ..Default::default() },
+            WallhavenConfig { seed: Some("abc12".to_string()), ..Default::default() },
+            WallhavenConfig { sorting: "views".to_string(), seed: Some("abc123".to_string()), ..Default::default() },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err());
+        }
+
+        let nsfw = WallhavenConfig { purity: "001".to_string(), ..Default::default() };
+        assert!(nsfw.search_url(1, None).is_err());
+    }
+
+    #[test]
+    fn test_random_seed() {
+        let response: WallhavenResponse = serde_json::from_str(
+            r#"{"data": [{"id": "94x38z", "path": "https://w.wallhaven.cc/full/94/wallhaven-94x38z.jpg",
+                "category": "general", "purity": "sfw", "dimension_x": 1920, "dimension_y": 1080,
+                "thumbs": {"original": "o", "large": "l", "small": "s"}}],
+                "meta": {"current_page": 1, "last_page": 40, "per_page": 24, "total": 960, "seed": "Ab12Cd"}}"#,
+        )
+        .unwrap();
+        let page = to_page(response, 1);
+        assert_eq!(page.seed.as_deref(), Some("Ab12Cd"));
+
+        let next = WallhavenConfig { seed: page.seed, ..Default::default() };
+        let url = next.search_url(2, None).unwrap();
+        assert!(url.query().unwrap().contains("sorting=random&page=2&seed=Ab12Cd"));
+    }
+
+    #[test]
+    fn test_account_settings() {
+        let response: WallhavenSettingsResponse = serde_json::from_str(
+            r#"{"data": {"thumb_size": "orig", "per_page": "24", "purity": ["sfw", "sketchy"],
+                "categories": ["general", "people"], "resolutions": ["2560x1440"],
+                "aspect_ratios": ["16x9"], "toplist_range": "6M", "tag_blacklist": []}}"#,
+        )
+        .unwrap();
+        let settings = AccountSettings::from(response.data);
+        assert_eq!(settings.categories, "101");
+        assert_eq!(settings.purity, "110");
+        assert_eq!(settings.ratios, vec!["16x9".to_string()]);
+        assert_eq!(settings.toplist_range.as_deref(), Some("6M"));
+
+        let empty = AccountSettings::from(WallhavenSettingsData::default());
+        assert_eq!((empty.categories.as_str(), empty.purity.as_str()), ("010", "100"));
+    }
+
+    #[test]
+    fn test_parse_collection() {
+        assert_eq!(parse_collection("some_user/15"), Ok(("some_user".to_string(), 15)));
+        assert!(parse_collection("some_user").is_err());
+        assert!(parse_collection("some user/15").is_err());
+        assert!(parse_collection("some_user/abc").is_err());
+    }
+
     #[tokio::test]
     async fn test_search_wallpapers() {
         let result = search_wallpapers(None).await;
@@ -10,6 +10,12 @@ pub struct WallpaperInfo {
     pub source: WallpaperSource,
     pub local_path: Option<PathBuf>,
     pub cached: bool,
+    /// 作者或摄影师
+    #[serde(default)]
+    pub author: Option<String>,
+    /// 来源要求展示的署名信息
+    #[serde(default)]
+    pub attribution: Option<Attribution>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +26,39 @@ pub struct WallpaperListItem {
     pub source: WallpaperSource,
     #[serde(rename = "thumbUrl")]
     pub thumb_url: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub attribution: Option<Attribution>,
+}
+
+/**
+ * 摄影师署名，链接已带上来源要求的 UTM 参数
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attribution {
+    pub name: String,
+    pub profile_url: String,
+    pub photo_url: String,
+    /// 下载图片时需要请求的统计地址
+    #[serde(default)]
+    pub download_location: Option<String>,
+}
+
+impl From<WallpaperListItem> for WallpaperInfo {
+    fn from(item: WallpaperListItem) -> Self {
+        WallpaperInfo {
+            id: item.id,
+            title: item.title,
+            url: item.url,
+            source: item.source,
+            local_path: None,
+            cached: false,
+            author: item.author,
+            attribution: item.attribution,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +69,9 @@ pub struct PaginatedResponse<T> {
     pub last_page: u32,
     pub per_page: u32,
     pub total: u32,
+    /// 随机排序的种子，请求后续页时传回以保持顺序一致
+    #[serde(default)]
+    pub seed: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -41,6 +83,10 @@ pub enum WallpaperSource {
     Pixabay,
     Pexels,
     Reddit,
+    /// 本地程序化生成
+    Generated,
+    /// 非在线来源的本地文件
+    Local,
 }
 
 #[derive(Debug, thiserror::Error)]
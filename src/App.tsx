@@ -1,3 +1,4 @@
+import { useEffect } from 'react';
 import { BrowserRouter, Routes, Route } from 'react-router-dom';
 import Layout from './components/Layout';
 import RandomWallpaper from './pages/RandomWallpaper';
@@ -6,8 +7,13 @@ import Favorites from './pages/Favorites';
 import Downloads from './pages/Downloads';
 import AutoSwitch from './pages/AutoSwitch';
 import Settings from './pages/Settings';
+import { migrateLegacyApiKeys } from './utils/apiKeys';
 
 export default function App() {
+  useEffect(() => {
+    migrateLegacyApiKeys();
+  }, []);
+
   return (
     <BrowserRouter>
       <Routes>
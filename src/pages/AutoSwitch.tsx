@@ -2,13 +2,8 @@ import { useState, useEffect } from 'react';
 import PageHeader from '../components/PageHeader';
 import { Play, Pause, Clock } from 'lucide-react';
 import { cn } from '../lib/utils';
-import type { WallpaperSource } from '../types';
-import {
-  setAutoSwitchConfig,
-  getAutoSwitchConfig,
-  fetchNextWallpaper,
-} from '../services/tauri';
-import { getApiKeyForSource } from '../utils/apiKeys';
+import type { WallpaperSource, SourceQuery } from '../types';
+import { setAutoSwitchConfig, getAutoSwitchConfig } from '../services/tauri';
 
 const SOURCES = [
   {
@@ -31,6 +26,11 @@ const SOURCES = [
     label: 'Pixabay',
     color: 'from-green-500 to-teal-500',
   },
+  {
+    id: 'generated',
+    label: '本地生成',
+    color: 'from-amber-400 to-fuchsia-500',
+  },
 ];
 
 const INTERVALS = [
@@ -44,9 +44,38 @@ const INTERVALS = [
   { label: '3天', value: 259200 },
 ];
 
+// 支持按关键词搜索的来源
+const SEARCHABLE_SOURCES = ['wallhaven', 'unsplash', 'pixabay'];
+
+// Wallhaven 的筛选条件，键名与 API 参数一致
+const WALLHAVEN_FILTERS = [
+  {
+    key: 'atleast',
+    label: '最低分辨率',
+    options: [
+      { label: '不限', value: '' },
+      { label: '1920×1080', value: '1920x1080' },
+      { label: '2560×1440', value: '2560x1440' },
+      { label: '3840×2160', value: '3840x2160' },
+    ],
+  },
+  {
+    key: 'ratios',
+    label: '比例',
+    options: [
+      { label: '不限', value: '' },
+      { label: '16:9', value: '16x9' },
+      { label: '16:10', value: '16x10' },
+      { label: '21:9', value: '21x9' },
+    ],
+  },
+];
+
 export default function AutoSwitch() {
   const [source, setSource] = useState<WallpaperSource>('bing');
   const [interval, setIntervalValue] = useState<number>(3600);
+  const [keyword, setKeyword] = useState('');
+  const [filters, setFilters] = useState<Record<string, string>>({});
   const [isRunning, setIsRunning] = useState(false);
   const [loading, setLoading] = useState(false);
 
@@ -58,11 +87,25 @@ export default function AutoSwitch() {
     setIntervalValue(newInterval);
   };
 
+  const handleFilterChange = (key: string, value: string) => {
+    setFilters((prev) => ({ ...prev, [key]: value }));
+  };
+
+  const buildQuery = (): SourceQuery | null => {
+    if (!SEARCHABLE_SOURCES.includes(source)) return null;
+    return {
+      query: keyword.trim() || null,
+      filters: Object.fromEntries(
+        Object.entries(filters).filter(([, value]) => value)
+      ),
+    };
+  };
+
   const handleToggle = async () => {
     setLoading(true);
     try {
       const newEnabled = !isRunning;
-      await setAutoSwitchConfig(source, newEnabled, interval);
+      await setAutoSwitchConfig(source, newEnabled, interval, buildQuery());
       setIsRunning(newEnabled);
     } catch (error) {
       console.error('Failed to toggle auto switch:', error);
@@ -75,10 +118,12 @@ export default function AutoSwitch() {
     const checkStatus = async () => {
       try {
         const config = await getAutoSwitchConfig(source);
+        setIsRunning(config?.enabled ?? false);
         if (config) {
-          setIsRunning(config.enabled);
           setIntervalValue(config.intervalSeconds);
         }
+        setKeyword(config?.query?.query ?? '');
+        setFilters(config?.query?.filters ?? {});
       } catch (error) {
         console.error('Failed to check auto switch status:', error);
       }
@@ -87,21 +132,6 @@ export default function AutoSwitch() {
     checkStatus();
   }, [source]);
 
-  useEffect(() => {
-    if (!isRunning) return;
-
-    const timer = setInterval(async () => {
-      try {
-        const apiKey = getApiKeyForSource(source);
-        await fetchNextWallpaper(source, apiKey);
-      } catch (error) {
-        console.error('Auto switch failed:', error);
-      }
-    }, interval * 1000);
-
-    return () => clearInterval(timer);
-  }, [isRunning, interval, source]);
-
   return (
     <div className="flex flex-col h-full bg-gradient-to-br from-zinc-950 via-zinc-900 to-zinc-950">
       <PageHeader
@@ -143,6 +173,48 @@ export default function AutoSwitch() {
               </div>
 
               <div className="w-full space-y-4">
+                {SEARCHABLE_SOURCES.includes(source) && (
+                  <div>
+                    <label className="block text-sm font-medium text-zinc-300 mb-3">
+                      关键词
+                    </label>
+                    <input
+                      type="text"
+                      value={keyword}
+                      onChange={(e) => setKeyword(e.target.value)}
+                      disabled={loading || isRunning}
+                      placeholder="留空时使用来源的默认列表"
+                      className="w-full bg-zinc-800/60 border border-white/5 rounded-lg px-4 py-2.5 text-sm text-white placeholder:text-zinc-600 focus:outline-none focus:border-indigo-500/50 focus:ring-1 focus:ring-indigo-500/30 transition-colors disabled:opacity-50"
+                    />
+                  </div>
+                )}
+
+                {source === 'wallhaven' && (
+                  <div className="grid grid-cols-2 gap-4">
+                    {WALLHAVEN_FILTERS.map((filter) => (
+                      <div key={filter.key}>
+                        <label className="block text-sm font-medium text-zinc-300 mb-3">
+                          {filter.label}
+                        </label>
+                        <select
+                          value={filters[filter.key] ?? ''}
+                          onChange={(e) =>
+                            handleFilterChange(filter.key, e.target.value)
+                          }
+                          disabled={loading || isRunning}
+                          className="w-full bg-zinc-800/60 border border-white/5 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:border-indigo-500/50 disabled:opacity-50"
+                        >
+                          {filter.options.map((option) => (
+                            <option key={option.value} value={option.value}>
+                              {option.label}
+                            </option>
+                          ))}
+                        </select>
+                      </div>
+                    ))}
+                  </div>
+                )}
+
                 <div>
                   <label className="block text-sm font-medium text-zinc-300 mb-3">
                     切换间隔
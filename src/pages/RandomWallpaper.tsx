@@ -12,7 +12,6 @@ import { cn } from '../lib/utils';
 import PageHeader from '../components/PageHeader';
 import type { WallpaperInfo } from '../types';
 import { useAppStore } from '../store/appStore';
-import { getApiKeyForSource } from '../utils/apiKeys';
 
 const SOURCES = [
   {
@@ -40,6 +39,11 @@ const SOURCES = [
     label: 'Pexels',
     color: 'from-cyan-500 to-blue-600',
   },
+  {
+    id: 'generated',
+    label: '本地生成',
+    color: 'from-amber-400 to-fuchsia-500',
+  },
 ];
 
 export default function RandomWallpaper() {
@@ -113,11 +117,7 @@ export default function RandomWallpaper() {
   const fetchNextWallpaper = async (targetSource: string) => {
     setLoading(true);
     try {
-      const apiKey = getApiKeyForSource(targetSource);
-      await invoke('fetch_next_wallpaper', {
-        source: targetSource,
-        api_key: apiKey,
-      });
+      await invoke('fetch_next_wallpaper', { source: targetSource });
     } catch (error) {
       console.error('Failed to fetch wallpaper:', error);
       setToast({ message: `获取失败: ${error}`, type: 'error' });
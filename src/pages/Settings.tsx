@@ -1,44 +1,41 @@
 import { useState, useEffect } from 'react';
-import { Settings as SettingsIcon, Eye, EyeOff } from 'lucide-react';
+import { Settings as SettingsIcon, Eye, EyeOff, Trash2 } from 'lucide-react';
 import { cn } from '../lib/utils';
-import { useAppStore } from '../store/appStore';
+import { clearApiKey, getApiKeyStatus, setApiKey } from '../services/tauri';
+import type { ApiKeyStatus } from '../types';
 
 const API_KEY_FIELDS = [
   {
     id: 'wallhaven',
     label: 'Wallhaven',
-    storeKey: 'wallhavenApiKey',
-    setter: 'setWallhavenApiKey',
     placeholder: '输入 Wallhaven API Key',
   },
   {
     id: 'unsplash',
     label: 'Unsplash',
-    storeKey: 'unsplashApiKey',
-    setter: 'setUnsplashApiKey',
     placeholder: '输入 Unsplash Access Key',
   },
   {
     id: 'pixabay',
     label: 'Pixabay',
-    storeKey: 'pixabayApiKey',
-    setter: 'setPixabayApiKey',
     placeholder: '输入 Pixabay API Key',
   },
   {
     id: 'pexels',
     label: 'Pexels',
-    storeKey: 'pexelsApiKey',
-    setter: 'setPexelsApiKey',
     placeholder: '输入 Pexels API Key',
   },
 ] as const;
 
 type FieldId = (typeof API_KEY_FIELDS)[number]['id'];
 
-export default function Settings() {
-  const store = useAppStore();
+const BACKEND_LABELS: Record<string, string> = {
+  keyring: '系统钥匙串',
+  file: '加密文件',
+  environment: '环境变量',
+};
 
+export default function Settings() {
   const [keys, setKeys] = useState<Record<FieldId, string>>({
     wallhaven: '',
     unsplash: '',
@@ -51,21 +48,32 @@ export default function Settings() {
     pixabay: false,
     pexels: false,
   });
-  const [toast, setToast] = useState<string | null>(null);
+  const [status, setStatus] = useState<Partial<Record<FieldId, ApiKeyStatus>>>(
+    {}
+  );
+  const [saving, setSaving] = useState(false);
+  const [toast, setToast] = useState<{
+    message: string;
+    type: 'success' | 'error';
+  } | null>(null);
+
+  // 只读取是否已配置和最后几位，完整的 Key 不会返回给前端
+  const loadStatus = async () => {
+    try {
+      const list = await getApiKeyStatus();
+      setStatus(
+        Object.fromEntries(list.map((item) => [item.source, item])) as Partial<
+          Record<FieldId, ApiKeyStatus>
+        >
+      );
+    } catch (error) {
+      console.error('Failed to load API key status:', error);
+    }
+  };
 
   useEffect(() => {
-    setKeys({
-      wallhaven: store.wallhavenApiKey,
-      unsplash: store.unsplashApiKey,
-      pixabay: store.pixabayApiKey,
-      pexels: store.pexelsApiKey,
-    });
-  }, [
-    store.wallhavenApiKey,
-    store.unsplashApiKey,
-    store.pixabayApiKey,
-    store.pexelsApiKey,
-  ]);
+    loadStatus();
+  }, []);
 
   useEffect(() => {
     if (!toast) return;
@@ -81,12 +89,35 @@ export default function Settings() {
     setVisible((prev) => ({ ...prev, [id]: !prev[id] }));
   };
 
-  const handleSave = () => {
-    store.setWallhavenApiKey(keys.wallhaven);
-    store.setUnsplashApiKey(keys.unsplash);
-    store.setPixabayApiKey(keys.pixabay);
-    store.setPexelsApiKey(keys.pexels);
-    setToast('API Keys 已保存');
+  // 只保存填写了的 Key，留空的保持原样
+  const handleSave = async () => {
+    setSaving(true);
+    try {
+      for (const field of API_KEY_FIELDS) {
+        const key = keys[field.id].trim();
+        if (key) {
+          await setApiKey(field.id, key);
+        }
+      }
+      setKeys({ wallhaven: '', unsplash: '', pixabay: '', pexels: '' });
+      setToast({ message: 'API Keys 已保存', type: 'success' });
+    } catch (error) {
+      setToast({ message: `保存失败: ${error}`, type: 'error' });
+    } finally {
+      setSaving(false);
+      loadStatus();
+    }
+  };
+
+  const handleClear = async (id: FieldId) => {
+    try {
+      await clearApiKey(id);
+      setToast({ message: 'API Key 已删除', type: 'success' });
+    } catch (error) {
+      setToast({ message: `删除失败: ${error}`, type: 'error' });
+    } finally {
+      loadStatus();
+    }
   };
 
   return (
@@ -109,9 +140,32 @@ export default function Settings() {
             <div className="space-y-5">
               {API_KEY_FIELDS.map((field) => (
                 <div key={field.id}>
-                  <label className="block text-sm font-medium text-zinc-300 mb-2">
-                    {field.label}
-                  </label>
+                  <div className="flex items-center justify-between mb-2">
+                    <label className="block text-sm font-medium text-zinc-300">
+                      {field.label}
+                    </label>
+                    {status[field.id]?.configured ? (
+                      <div className="flex items-center gap-2 text-xs text-zinc-500">
+                        <span>
+                          已保存 ····{status[field.id]?.hint}
+                          {status[field.id]?.backend &&
+                            `（${BACKEND_LABELS[status[field.id]!.backend!]}）`}
+                        </span>
+                        {status[field.id]?.backend !== 'environment' && (
+                          <button
+                            type="button"
+                            onClick={() => handleClear(field.id)}
+                            className="text-zinc-500 hover:text-red-400 transition-colors"
+                            title="删除"
+                          >
+                            <Trash2 className="w-3.5 h-3.5" />
+                          </button>
+                        )}
+                      </div>
+                    ) : (
+                      <span className="text-xs text-zinc-600">未配置</span>
+                    )}
+                  </div>
                   <div className="relative">
                     <input
                       type={visible[field.id] ? 'text' : 'password'}
@@ -138,7 +192,8 @@ export default function Settings() {
 
             <button
               onClick={handleSave}
-              className="mt-8 w-full px-6 py-3 rounded-xl bg-indigo-600 hover:bg-indigo-500 text-white text-sm font-medium transition-colors"
+              disabled={saving}
+              className="mt-8 w-full px-6 py-3 rounded-xl bg-indigo-600 hover:bg-indigo-500 text-white text-sm font-medium transition-colors disabled:opacity-50"
             >
               保存
             </button>
@@ -150,11 +205,11 @@ export default function Settings() {
         <div className="absolute top-4 left-1/2 -translate-x-1/2 z-50 animate-in fade-in slide-in-from-top-4 duration-300">
           <div
             className={cn(
-              'px-4 py-2 rounded-lg shadow-lg text-sm font-medium',
-              'bg-emerald-500/90 text-white'
+              'px-4 py-2 rounded-lg shadow-lg text-sm font-medium text-white',
+              toast.type === 'success' ? 'bg-emerald-500/90' : 'bg-red-500/90'
             )}
           >
-            {toast}
+            {toast.message}
           </div>
         </div>
       )}
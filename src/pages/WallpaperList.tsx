@@ -1,4 +1,5 @@
 import { useState, useEffect, useCallback } from 'react';
+import { convertFileSrc } from '@tauri-apps/api/core';
 import {
   fetchWallpapersList,
   setWallpaper,
@@ -9,7 +10,6 @@ import type {
   WallpaperListItem,
   WallpaperInfo,
 } from '../types';
-import { getApiKeyForSource } from '../utils/apiKeys';
 import PreviewModal from '../components/PreviewModal';
 import PageHeader from '../components/PageHeader';
 import {
@@ -64,6 +64,11 @@ const supportsPagination = (sourceId: string): boolean => {
   return SOURCES.find((s) => s.id === sourceId)?.supportsPagination ?? false;
 };
 
+// 离线时列出的缓存壁纸返回本地路径，需要转换成 asset 协议地址才能加载
+const imageSrc = (url: string): string => {
+  return /^https?:\/\//.test(url) ? url : convertFileSrc(url);
+};
+
 export default function WallpaperList() {
   const {
     listPageSource,
@@ -105,11 +110,15 @@ export default function WallpaperList() {
     ) => {
       setLoading(true);
       try {
-        const apiKey = getApiKeyForSource(targetSource as WallpaperSource);
+        // 翻页时带上第一页返回的种子，随机排序的结果才能连续
+        const seed =
+          targetPage > 1 && targetSource === listPageSource
+            ? pagination?.seed
+            : null;
         const response = await fetchWallpapersList(
           targetSource as WallpaperSource,
           targetPage,
-          apiKey
+          seed ? { filters: { seed } } : null
         );
         setListPageData({
           wallpapers: response.data,
@@ -119,6 +128,7 @@ export default function WallpaperList() {
             lastPage: response.lastPage,
             perPage: response.perPage,
             total: response.total,
+            seed: response.seed ?? seed,
           },
           loaded: true,
         });
@@ -134,7 +144,7 @@ export default function WallpaperList() {
         setLoading(false);
       }
     },
-    [listPageSource, page, setListPageData]
+    [listPageSource, page, pagination, setListPageData]
   );
 
   useEffect(() => {
@@ -345,7 +355,7 @@ export default function WallpaperList() {
                       ) : (
                         <>
                           <img
-                            src={item.thumbUrl}
+                            src={imageSrc(item.thumbUrl)}
                             alt={item.title}
                             className={cn(
                               'w-full h-full object-cover transition-all duration-700 ease-out',
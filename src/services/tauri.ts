@@ -4,22 +4,23 @@ import type {
   Settings,
   WallpaperListItem,
   PaginatedResponse,
+  SourceQuery,
+  ApiKeyStatus,
 } from '../types';
 import type { WallpaperSource } from '../types';
 
 export async function fetchNextWallpaper(
-  source: WallpaperSource,
-  apiKey: string | null = null
+  source: WallpaperSource
 ): Promise<WallpaperInfo> {
-  return invoke('fetch_next_wallpaper', { source, api_key: apiKey });
+  return invoke('fetch_next_wallpaper', { source });
 }
 
 export async function fetchWallpapersList(
   source: WallpaperSource,
   page: number,
-  apiKey: string | null = null
+  query: SourceQuery | null = null
 ): Promise<PaginatedResponse<WallpaperListItem>> {
-  return invoke('fetch_wallpapers_list', { source, page, api_key: apiKey });
+  return invoke('fetch_wallpapers_list', { source, page, query });
 }
 
 export async function downloadWallpaper(info: WallpaperInfo): Promise<string> {
@@ -64,14 +65,22 @@ export async function listenToEvents(callbacks: {
 export async function setAutoSwitchConfig(
   source: WallpaperSource,
   enabled: boolean,
-  intervalSeconds: number
+  intervalSeconds: number,
+  query: SourceQuery | null = null
 ): Promise<void> {
-  return invoke('set_auto_switch_config', { source, enabled, intervalSeconds });
+  return invoke('set_auto_switch_config', {
+    source,
+    enabled,
+    intervalSeconds,
+    query,
+  });
 }
 
-export async function getAutoSwitchConfig(
-  source: WallpaperSource
-): Promise<{ enabled: boolean; intervalSeconds: number } | null> {
+export async function getAutoSwitchConfig(source: WallpaperSource): Promise<{
+  enabled: boolean;
+  intervalSeconds: number;
+  query: SourceQuery;
+} | null> {
   return invoke('get_auto_switch_config', { source });
 }
 
@@ -105,3 +114,19 @@ export async function deleteDownload(id: string): Promise<boolean> {
 export async function revealInFinder(path: string): Promise<void> {
   return invoke('reveal_in_finder', { path });
 }
+
+// API Key 保存在系统钥匙串或加密文件中，前端只能看到是否已配置和最后几位
+export async function setApiKey(
+  source: WallpaperSource,
+  key: string
+): Promise<string> {
+  return invoke<string>('set_api_key', { source, key });
+}
+
+export async function clearApiKey(source: WallpaperSource): Promise<void> {
+  return invoke('clear_api_key', { source });
+}
+
+export async function getApiKeyStatus(): Promise<ApiKeyStatus[]> {
+  return invoke<ApiKeyStatus[]>('get_api_key_status');
+}
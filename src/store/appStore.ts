@@ -5,6 +5,7 @@ import type {
   WallpaperInfo,
   PaginatedResponse,
 } from '../types';
+import { stashLegacyApiKeys } from '../utils/apiKeys';
 
 // 带完整信息的收藏项
 export interface FavoriteItem extends WallpaperInfo {
@@ -33,11 +34,6 @@ export interface AppState {
   favorites: FavoriteItem[];
   // 下载列表
   downloads: DownloadItem[];
-  // API Keys（持久化存储）
-  wallhavenApiKey: string;
-  unsplashApiKey: string;
-  pixabayApiKey: string;
-  pexelsApiKey: string;
   /* 页面数据 - 不持久化，应用重启时重置 */
   listPageData: ListPageData;
   randomPageWallpaper: WallpaperInfo | null;
@@ -48,10 +44,6 @@ export interface AppActions {
   setRandomPageSource: (source: string) => void;
   setListPageSource: (source: string) => void;
   setSelectedInterval: (interval: number) => void;
-  setWallhavenApiKey: (key: string) => void;
-  setUnsplashApiKey: (key: string) => void;
-  setPixabayApiKey: (key: string) => void;
-  setPexelsApiKey: (key: string) => void;
   // 收藏操作
   toggleFavorite: (wallpaper: WallpaperInfo) => void;
   removeFavorite: (id: string) => void;
@@ -88,10 +80,6 @@ const initialState: Omit<
   selectedInterval: 3600,
   favorites: [],
   downloads: [],
-  wallhavenApiKey: '',
-  unsplashApiKey: '',
-  pixabayApiKey: '',
-  pexelsApiKey: '',
 };
 
 export const useAppStore = create<AppStore>()(
@@ -108,11 +96,6 @@ export const useAppStore = create<AppStore>()(
 
       setSelectedInterval: (interval) => set({ selectedInterval: interval }),
 
-      setWallhavenApiKey: (key) => set({ wallhavenApiKey: key }),
-      setUnsplashApiKey: (key) => set({ unsplashApiKey: key }),
-      setPixabayApiKey: (key) => set({ pixabayApiKey: key }),
-      setPexelsApiKey: (key) => set({ pexelsApiKey: key }),
-
       // 切换收藏状态
       toggleFavorite: (wallpaper) =>
         set((state) => {
@@ -208,16 +191,21 @@ export const useAppStore = create<AppStore>()(
     }),
     {
       name: 'wallpaper-mate-storage',
+      version: 1,
+      // 版本 0 把 API Key 明文保存在这里，取出后由 migrateLegacyApiKeys 转存到安全存储
+      migrate: (persisted, version) => {
+        const state = persisted as Record<string, unknown>;
+        if (version < 1) {
+          stashLegacyApiKeys(state);
+        }
+        return state as unknown as AppStore;
+      },
       partialize: (state) => ({
         randomPageSource: state.randomPageSource,
         listPageSource: state.listPageSource,
         selectedInterval: state.selectedInterval,
         favorites: state.favorites,
         downloads: state.downloads,
-        wallhavenApiKey: state.wallhavenApiKey,
-        unsplashApiKey: state.unsplashApiKey,
-        pixabayApiKey: state.pixabayApiKey,
-        pexelsApiKey: state.pexelsApiKey,
       }),
     }
   )
@@ -5,11 +5,21 @@ export const WallpaperSource = {
   Pixabay: 'pixabay',
   Pexels: 'pexels',
   Reddit: 'reddit',
+  Generated: 'generated',
+  Local: 'local',
 } as const;
 
 export type WallpaperSource =
   (typeof WallpaperSource)[keyof typeof WallpaperSource];
 
+// 摄影师署名，链接已带 UTM 参数
+export interface Attribution {
+  name: string;
+  profileUrl: string;
+  photoUrl: string;
+  downloadLocation?: string | null;
+}
+
 export interface WallpaperInfo {
   id: string;
   title: string;
@@ -17,6 +27,8 @@ export interface WallpaperInfo {
   source: WallpaperSource;
   localPath?: string;
   cached: boolean;
+  author?: string | null;
+  attribution?: Attribution | null;
 }
 
 export interface WallpaperListItem {
@@ -25,6 +37,8 @@ export interface WallpaperListItem {
   url: string;
   thumbUrl: string;
   source: WallpaperSource;
+  author?: string | null;
+  attribution?: Attribution | null;
 }
 
 export interface PaginatedResponse<T> {
@@ -33,14 +47,26 @@ export interface PaginatedResponse<T> {
   lastPage: number;
   perPage: number;
   total: number;
+  // Wallhaven 随机排序的种子，请求后续页时传回
+  seed?: string | null;
+}
+
+export interface SourceQuery {
+  query?: string | null;
+  // 键名与来源 API 参数一致，多个值用逗号分隔
+  filters?: Record<string, string>;
 }
 
 export interface Settings {
   source: WallpaperSource;
-  wallhavenApiKey?: string;
-  unsplashApiKey?: string;
-  pixabayApiKey?: string;
-  pexelsApiKey?: string;
+}
+
+export interface ApiKeyStatus {
+  source: WallpaperSource;
+  configured: boolean;
+  backend: 'keyring' | 'file' | 'environment' | null;
+  // Key 的最后 4 位
+  hint: string | null;
 }
 
 export type WallpaperEvent =
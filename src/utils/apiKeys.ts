@@ -1,17 +1,60 @@
-import { useAppStore } from '../store/appStore';
+import { setApiKey } from '../services/tauri';
+import type { WallpaperSource } from '../types';
 
-export function getApiKeyForSource(source: string): string | null {
-  const state = useAppStore.getState();
-  switch (source) {
-    case 'wallhaven':
-      return state.wallhavenApiKey || null;
-    case 'unsplash':
-      return state.unsplashApiKey || null;
-    case 'pixabay':
-      return state.pixabayApiKey || null;
-    case 'pexels':
-      return state.pexelsApiKey || null;
-    default:
-      return null;
+// 旧版本保存在前端存储中的 API Key，等待迁移到安全存储
+const LEGACY_KEYS_STORAGE = 'wallpaper-mate-legacy-api-keys';
+
+const LEGACY_FIELDS: Record<string, WallpaperSource> = {
+  wallhavenApiKey: 'wallhaven',
+  unsplashApiKey: 'unsplash',
+  pixabayApiKey: 'pixabay',
+  pexelsApiKey: 'pexels',
+};
+
+/**
+ * 从持久化状态中取出旧版的 API Key，单独暂存后从状态中删除
+ */
+export function stashLegacyApiKeys(state: Record<string, unknown>): void {
+  const pending: Partial<Record<WallpaperSource, string>> = {};
+  for (const [field, source] of Object.entries(LEGACY_FIELDS)) {
+    const key = state[field];
+    if (typeof key === 'string' && key.trim()) {
+      pending[source] = key.trim();
+    }
+    delete state[field];
+  }
+  if (Object.keys(pending).length > 0) {
+    localStorage.setItem(LEGACY_KEYS_STORAGE, JSON.stringify(pending));
+  }
+}
+
+/**
+ * 把暂存的旧版 API Key 保存到安全存储，成功的从暂存中删除，失败的下次启动重试
+ */
+export async function migrateLegacyApiKeys(): Promise<void> {
+  const raw = localStorage.getItem(LEGACY_KEYS_STORAGE);
+  if (!raw) return;
+
+  let pending: Partial<Record<WallpaperSource, string>>;
+  try {
+    pending = JSON.parse(raw);
+  } catch {
+    localStorage.removeItem(LEGACY_KEYS_STORAGE);
+    return;
+  }
+
+  for (const [source, key] of Object.entries(pending)) {
+    try {
+      await setApiKey(source as WallpaperSource, key as string);
+      delete pending[source as WallpaperSource];
+    } catch (error) {
+      console.error(`Failed to migrate ${source} API key:`, error);
+    }
+  }
+
+  if (Object.keys(pending).length > 0) {
+    localStorage.setItem(LEGACY_KEYS_STORAGE, JSON.stringify(pending));
+  } else {
+    localStorage.removeItem(LEGACY_KEYS_STORAGE);
   }
 }